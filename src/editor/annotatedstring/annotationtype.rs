@@ -0,0 +1,23 @@
+// AnnotationType 描述一段文本在渲染时的语义类别，由终端层映射为具体颜色。
+
+/// 文本标注类型
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AnnotationType {
+    Ruler,     // 列标尺
+    OverLimit, // 超出行长限制的文本
+    TrailingWhitespace, // 行尾空白
+    Selection, // 选中的文本
+    SearchMatch, // 搜索提示中与输入内容匹配的文本
+    LineNumber, // 行号栏
+    OverflowMarker, // 行的内容超出编辑区左右边缘时两端的 < 和 >
+    Keyword,   // 语法高亮：关键字
+    StringLiteral, // 语法高亮：字符串和字符字面量
+    Number,    // 语法高亮：数字
+    Comment,   // 语法高亮：注释
+    #[cfg(feature = "diff")]
+    DiffAdded, // 与磁盘版本相比新增的行的标记
+    #[cfg(feature = "diff")]
+    DiffModified, // 与磁盘版本相比修改过的行的标记
+    #[cfg(feature = "diff")]
+    DiffRemoved, // 与磁盘版本相比在此处删除了行的标记
+}
@@ -0,0 +1,56 @@
+// AnnotatedString 是带标注的文本：由若干段文本组成，每段可附带一个标注类型，用于分段着色渲染。
+
+use std::fmt::{self, Display};
+
+mod annotationtype;
+pub use annotationtype::AnnotationType;
+
+/// 带标注的文本
+#[derive(Default, Debug)]
+pub struct AnnotatedString {
+    segments: Vec<(String, Option<AnnotationType>)>, // 文本段及其标注
+}
+
+impl AnnotatedString {
+    /// 追加一段文本；与上一段标注相同时合并
+    pub fn push_str(&mut self, text: &str, annotation_type: Option<AnnotationType>) {
+        if text.is_empty() {
+            return;
+        }
+        match self.segments.last_mut() {
+            Some((last_text, last_type)) if *last_type == annotation_type => {
+                last_text.push_str(text);
+            }
+            _ => self.segments.push((text.to_string(), annotation_type)),
+        }
+    }
+
+    /// 追加单个字符
+    pub fn push(&mut self, character: char, annotation_type: Option<AnnotationType>) {
+        self.push_str(character.encode_utf8(&mut [0; 4]), annotation_type);
+    }
+
+    /// 追加另一段带标注的文本
+    pub fn append(&mut self, other: &Self) {
+        for (text, annotation_type) in other.segments() {
+            self.push_str(text, annotation_type);
+        }
+    }
+
+    /// 遍历所有文本段
+    pub fn segments(&self) -> impl Iterator<Item = (&str, Option<AnnotationType>)> {
+        self.segments
+            .iter()
+            .map(|(text, annotation_type)| (text.as_str(), *annotation_type))
+    }
+}
+
+impl Display for AnnotatedString {
+    /// 输出不带标注的纯文本
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        for (text, _) in &self.segments {
+            write!(formatter, "{text}")?;
+        }
+        Ok(())
+    }
+}
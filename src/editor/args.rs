@@ -0,0 +1,135 @@
+// Args 解析命令行参数：要打开的文件名以及 --name=value 形式的选项。
+
+use std::time::Duration;
+
+use super::{messages::Language, terminal::Theme, uicomponents::LineNumbers};
+
+/// 未指定列号时列标尺的默认位置
+const DEFAULT_RULER_COLUMN: usize = 80;
+/// 未指定列数时行长限制的默认值
+const DEFAULT_LINE_LENGTH_LIMIT: usize = 100;
+
+/// 解析参数时遇到的问题
+pub enum ArgWarning {
+    /// 未知选项（选项名）
+    UnknownOption(String),
+    /// 选项值无效（选项名、值）
+    InvalidValue(String, String),
+}
+
+/// 命令行参数
+#[derive(Default)]
+pub struct Args {
+    /// 要打开的文件名
+    pub file_name: Option<String>,
+    /// 消息栏普通消息的显示时长（--message-timeout=秒）
+    pub message_duration: Option<Duration>,
+    /// 有未保存更改时退出所需的 Ctrl+Q 次数（--quit-times=次数）
+    pub quit_times: Option<u8>,
+    /// 列标尺所在的列（--ruler 或 --ruler=列号，默认 80）
+    pub ruler_column: Option<usize>,
+    /// 行长限制（--line-limit 或 --line-limit=列数，默认 100）
+    pub line_length_limit: Option<usize>,
+    /// 界面语言（--lang=zh|en）
+    pub language: Option<Language>,
+    /// 行号显示方式（--line-numbers=off|absolute|relative，覆盖配置文件）
+    pub line_numbers: Option<LineNumbers>,
+    /// 配色方案（--theme=dark|light，覆盖配置文件）
+    pub theme: Option<Theme>,
+    /// 搜索到达文件末尾时是否回绕到开头（--search-wrap=on|off，默认开）
+    pub search_wrap: Option<bool>,
+    /// 水平滚动时在行的两端显示 < 和 > 标记（--overflow-markers=on|off，覆盖配置文件）
+    pub overflow_markers: Option<bool>,
+    /// 启用 vi 风格的模态编辑（--modal），启动时处于普通模式
+    pub modal: bool,
+    /// 以只读方式打开所有文件（--readonly），拒绝一切修改
+    pub read_only: bool,
+    /// 不输出颜色和反色属性（--no-color），也可用环境变量 NO_COLOR 指定
+    pub no_color: bool,
+    /// 调试日志文件（--log=路径），也可用环境变量 TEXT_EDITOR_LOG 指定
+    pub log_file: Option<String>,
+    /// 要恢复的会话文件（--session 或 --session=路径，空字符串表示默认位置）
+    pub session: Option<String>,
+    /// 解析过程中遇到的问题，启动后显示在消息栏
+    pub warnings: Vec<ArgWarning>,
+}
+
+impl Args {
+    /// 解析参数列表（不含程序名）
+    pub fn parse(args: impl Iterator<Item = String>) -> Self {
+        let mut result = Self::default();
+        for arg in args {
+            if let Some(option) = arg.strip_prefix("--") {
+                let (name, value) = option.split_once('=').unwrap_or((option, ""));
+                result.apply_option(name, value);
+            } else if result.file_name.is_none() && !arg.is_empty() {
+                result.file_name = Some(arg);
+            }
+        }
+        result
+    }
+
+    /// 应用单个选项
+    fn apply_option(&mut self, name: &str, value: &str) {
+        match name {
+            "message-timeout" => match value.parse::<u64>() {
+                Ok(seconds) => self.message_duration = Some(Duration::from_secs(seconds)),
+                Err(_) => self.invalid_value(name, value),
+            },
+            "quit-times" => match value.parse::<u8>() {
+                Ok(times) if times > 0 => self.quit_times = Some(times),
+                _ => self.invalid_value(name, value),
+            },
+            "ruler" if value.is_empty() => self.ruler_column = Some(DEFAULT_RULER_COLUMN),
+            "ruler" => match value.parse::<usize>() {
+                Ok(column) if column > 0 => self.ruler_column = Some(column),
+                _ => self.invalid_value(name, value),
+            },
+            "line-limit" if value.is_empty() => {
+                self.line_length_limit = Some(DEFAULT_LINE_LENGTH_LIMIT);
+            }
+            "line-limit" => match value.parse::<usize>() {
+                Ok(limit) => self.line_length_limit = Some(limit),
+                Err(_) => self.invalid_value(name, value),
+            },
+            "line-numbers" => match LineNumbers::from_name(value) {
+                Some(line_numbers) => self.line_numbers = Some(line_numbers),
+                None => self.invalid_value(name, value),
+            },
+            "theme" => match Theme::from_name(value) {
+                Some(theme) => self.theme = Some(theme),
+                None => self.invalid_value(name, value),
+            },
+            "search-wrap" => match value {
+                "" | "on" => self.search_wrap = Some(true),
+                "off" => self.search_wrap = Some(false),
+                _ => self.invalid_value(name, value),
+            },
+            "overflow-markers" => match value {
+                "" | "on" => self.overflow_markers = Some(true),
+                "off" => self.overflow_markers = Some(false),
+                _ => self.invalid_value(name, value),
+            },
+            "modal" if value.is_empty() => self.modal = true,
+            "readonly" if value.is_empty() => self.read_only = true,
+            "no-color" if value.is_empty() => self.no_color = true,
+            "session" => self.session = Some(value.to_string()),
+            "log" if !value.is_empty() => self.log_file = Some(value.to_string()),
+            "lang" => match Language::from_code(value) {
+                Some(language) => self.language = Some(language),
+                None => self.invalid_value(name, value),
+            },
+            _ => self
+                .warnings
+                .push(ArgWarning::UnknownOption(name.to_string())),
+        }
+    }
+
+    /// 记录一个无效的选项值
+    fn invalid_value(&mut self, name: &str, value: &str) {
+        self.warnings.push(ArgWarning::InvalidValue(
+            name.to_string(),
+            value.to_string(),
+        ));
+    }
+}
@@ -0,0 +1,206 @@
+// BufferList 保存所有打开的文件（视图及其目录列表状态），支持在它们之间切换。
+// 当前文件的视图由 Editor 直接持有以便访问；列表中对应的位置只放一个空的占位项，切换时互相交换。
+
+use std::mem::swap;
+
+use super::{uicomponents::View, DirListing};
+
+/// 一个打开的文件：视图，以及视图列出目录时的目录列表
+#[derive(Default)]
+pub struct BufferSlot {
+    pub view: View,
+    pub dir_listing: Option<DirListing>,
+}
+
+/// 打开的文件列表
+pub struct BufferList {
+    slots: Vec<BufferSlot>, // 按打开顺序排列，active_idx 处为当前文件的占位项
+    active_idx: usize,      // 当前文件的序号
+}
+
+impl Default for BufferList {
+    /// 启动时只有一个文件
+    fn default() -> Self {
+        Self {
+            slots: vec![BufferSlot::default()],
+            active_idx: 0,
+        }
+    }
+}
+
+impl BufferList {
+    /// 打开的文件数
+    pub fn count(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// 当前文件的序号（从 0 开始）
+    pub const fn active_idx(&self) -> usize {
+        self.active_idx
+    }
+
+    /// 下一个（或上一个）文件的序号，到达末尾时回到开头
+    pub fn adjacent_idx(&self, forward: bool) -> usize {
+        let count = self.slots.len();
+        if forward {
+            self.active_idx.saturating_add(1) % count
+        } else {
+            self.active_idx.checked_sub(1).unwrap_or(count - 1)
+        }
+    }
+
+    /// 将当前文件换成第 idx 个，当前文件放回列表；idx 无效或就是当前文件时返回 false
+    pub fn switch_to(&mut self, idx: usize, active: &mut BufferSlot) -> bool {
+        if idx == self.active_idx || idx >= self.slots.len() {
+            return false;
+        }
+        swap(active, &mut self.slots[self.active_idx]);
+        swap(active, &mut self.slots[idx]);
+        self.active_idx = idx;
+        true
+    }
+
+    /// 在末尾加入新文件并切换过去，原来的当前文件放回列表
+    pub fn open(&mut self, slot: BufferSlot, active: &mut BufferSlot) {
+        self.slots.push(slot);
+        self.switch_to(self.slots.len().saturating_sub(1), active);
+    }
+
+    /// 关闭当前文件，切换到它前面的一个（关闭的是第一个时为后面的一个）；只剩一个文件时不关闭
+    pub fn close_active(&mut self, active: &mut BufferSlot) -> bool {
+        if self.slots.len() <= 1 {
+            return false;
+        }
+        let closed_idx = self.active_idx;
+        let next_idx = if closed_idx == 0 { 1 } else { closed_idx - 1 };
+        // 切换后原当前文件回到 closed_idx 处，再将其移除
+        self.switch_to(next_idx, active);
+        self.slots.remove(closed_idx);
+        if next_idx > closed_idx {
+            self.active_idx = next_idx - 1;
+        }
+        true
+    }
+
+    /// 查找打开了 file_name 的其他文件，返回其序号
+    pub fn find(&self, file_name: &str) -> Option<usize> {
+        self.others()
+            .find(|(_, slot)| slot.view.is_current_file(file_name))
+            .map(|(idx, _)| idx)
+    }
+
+    /// 按顺序遍历所有文件的视图，当前文件的位置上为 active
+    #[cfg(any(test, feature = "session"))]
+    pub fn views<'a>(&'a self, active: &'a View) -> impl Iterator<Item = &'a View> {
+        self.slots.iter().enumerate().map(move |(idx, slot)| {
+            if idx == self.active_idx {
+                active
+            } else {
+                &slot.view
+            }
+        })
+    }
+
+    /// 遍历当前文件以外的视图
+    pub fn other_views_mut(&mut self) -> impl Iterator<Item = &mut View> {
+        let active_idx = self.active_idx;
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter(move |(idx, _)| *idx != active_idx)
+            .map(|(_, slot)| &mut slot.view)
+    }
+
+    /// 遍历当前文件以外的各项及其序号
+    fn others(&self) -> impl Iterator<Item = (usize, &BufferSlot)> {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| *idx != self.active_idx)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 关联文件名 file_name 的项（文件不存在时视图为空）
+    fn slot(file_name: &str) -> BufferSlot {
+        let mut view = View::default();
+        view.load(file_name).unwrap();
+        BufferSlot { view, dir_listing: None }
+    }
+
+    /// 依次打开各文件，最后一个为当前文件
+    fn opened(file_names: &[&str]) -> (BufferList, BufferSlot) {
+        let mut list = BufferList::default();
+        let mut active = slot(file_names[0]);
+        for file_name in &file_names[1..] {
+            list.open(slot(file_name), &mut active);
+        }
+        (list, active)
+    }
+
+    fn file_names(list: &BufferList, active: &BufferSlot) -> Vec<String> {
+        list.views(&active.view)
+            .map(|view| view.file_path().unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn find_locates_already_open_path_instead_of_opening_again() {
+        let (mut list, mut active) = opened(&["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(list.find("a.txt"), Some(0));
+        // 当前文件不在查找范围内，由调用方直接判断
+        assert_eq!(list.find("c.txt"), None);
+        assert_eq!(list.find("d.txt"), None);
+        assert!(list.switch_to(list.find("b.txt").unwrap(), &mut active));
+        assert!(active.view.is_current_file("b.txt"));
+        assert_eq!(list.count(), 3);
+        assert_eq!(file_names(&list, &active), ["a.txt", "b.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn adjacent_idx_wraps_around() {
+        let (mut list, mut active) = opened(&["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(list.active_idx(), 2);
+        assert_eq!(list.adjacent_idx(true), 0);
+        assert!(list.switch_to(list.adjacent_idx(true), &mut active));
+        assert!(active.view.is_current_file("a.txt"));
+        assert_eq!(list.adjacent_idx(false), 2);
+        assert!(list.switch_to(list.adjacent_idx(false), &mut active));
+        assert!(active.view.is_current_file("c.txt"));
+        // 切换到自己或越界的序号无效
+        assert!(!list.switch_to(2, &mut active));
+        assert!(!list.switch_to(3, &mut active));
+    }
+
+    #[test]
+    fn close_active_moves_to_previous_or_next_slot() {
+        let (mut list, mut active) = opened(&["a.txt", "b.txt", "c.txt"]);
+        // 关闭最后一个：切换到前一个
+        assert!(list.close_active(&mut active));
+        assert_eq!(list.active_idx(), 1);
+        assert!(active.view.is_current_file("b.txt"));
+        assert_eq!(file_names(&list, &active), ["a.txt", "b.txt"]);
+        // 关闭第一个：切换到后一个，序号随之前移
+        list.switch_to(0, &mut active);
+        assert!(list.close_active(&mut active));
+        assert_eq!(list.active_idx(), 0);
+        assert!(active.view.is_current_file("b.txt"));
+        // 只剩一个时不关闭
+        assert!(!list.close_active(&mut active));
+        assert_eq!(list.count(), 1);
+    }
+
+    #[test]
+    fn close_active_in_the_middle_keeps_order() {
+        let (mut list, mut active) = opened(&["a.txt", "b.txt", "c.txt"]);
+        list.switch_to(1, &mut active);
+        assert!(list.close_active(&mut active));
+        assert_eq!(list.active_idx(), 0);
+        assert!(active.view.is_current_file("a.txt"));
+        assert_eq!(file_names(&list, &active), ["a.txt", "c.txt"]);
+    }
+}
@@ -0,0 +1,112 @@
+// Clipboard 保存复制和剪切的文本。以 clipboard 功能构建时同时写入和读取系统剪贴板
+// （通过 pbcopy、wl-copy、xclip、xsel 或 clip 等命令，不引入额外依赖）；
+// 系统剪贴板不可用时文本只保存在编辑器内部。
+
+/// 剪贴板
+#[derive(Default)]
+pub struct Clipboard {
+    text: Option<String>, // 最近一次复制或剪切的文本
+    synced: bool,         // 该文本是否也写入了系统剪贴板
+}
+
+impl Clipboard {
+    /// 保存文本并尝试写入系统剪贴板；启用了系统剪贴板但写入失败时返回 false
+    pub fn copy(&mut self, text: &str) -> bool {
+        self.text = Some(text.to_string());
+        self.synced = system::copy(text);
+        self.synced || !cfg!(feature = "clipboard")
+    }
+
+    /// 返回要粘贴的文本：优先读取系统剪贴板；上次复制没能写入系统剪贴板或读取失败时
+    /// 使用编辑器内部保存的文本。两者都没有时返回 None
+    pub fn paste(&self) -> Option<String> {
+        if self.text.is_some() && !self.synced {
+            return self.text.clone();
+        }
+        system::paste().or_else(|| self.text.clone())
+    }
+}
+
+#[cfg(feature = "clipboard")]
+mod system {
+    use std::{
+        io::Write,
+        process::{Command, Stdio},
+    };
+
+    /// 依次尝试的写入命令（程序、参数）
+    #[cfg(target_os = "macos")]
+    const COPY_COMMANDS: &[(&str, &[&str])] = &[("pbcopy", &[])];
+    #[cfg(windows)]
+    const COPY_COMMANDS: &[(&str, &[&str])] = &[("clip", &[])];
+    #[cfg(not(any(target_os = "macos", windows)))]
+    const COPY_COMMANDS: &[(&str, &[&str])] = &[
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+
+    /// 依次尝试的读取命令（程序、参数）
+    #[cfg(target_os = "macos")]
+    const PASTE_COMMANDS: &[(&str, &[&str])] = &[("pbpaste", &[])];
+    #[cfg(windows)]
+    const PASTE_COMMANDS: &[(&str, &[&str])] =
+        &[("powershell", &["-NoProfile", "-Command", "Get-Clipboard -Raw"])];
+    #[cfg(not(any(target_os = "macos", windows)))]
+    const PASTE_COMMANDS: &[(&str, &[&str])] = &[
+        ("wl-paste", &["--no-newline"]),
+        ("xclip", &["-selection", "clipboard", "-o"]),
+        ("xsel", &["--clipboard", "--output"]),
+    ];
+
+    /// 写入系统剪贴板，所有命令都不可用时返回 false
+    pub fn copy(text: &str) -> bool {
+        COPY_COMMANDS.iter().any(|(program, args)| {
+            // 输出不能连到终端，否则会打乱编辑器的画面
+            let Ok(mut child) = Command::new(program)
+                .args(*args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn()
+            else {
+                return false;
+            };
+            // 写完后关闭标准输入，命令才会结束
+            let written = child
+                .stdin
+                .take()
+                .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+            child.wait().is_ok_and(|status| status.success()) && written
+        })
+    }
+
+    /// 读取系统剪贴板，所有命令都不可用或内容不是 UTF-8 文本时返回 None
+    pub fn paste() -> Option<String> {
+        PASTE_COMMANDS.iter().find_map(|(program, args)| {
+            let output = Command::new(program)
+                .args(*args)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            String::from_utf8(output.stdout).ok()
+        })
+    }
+}
+
+#[cfg(not(feature = "clipboard"))]
+mod system {
+    /// 未启用 clipboard 功能，不使用系统剪贴板
+    pub fn copy(_text: &str) -> bool {
+        false
+    }
+
+    /// 未启用 clipboard 功能，不使用系统剪贴板
+    pub fn paste() -> Option<String> {
+        None
+    }
+}
@@ -1,17 +1,60 @@
 // 编辑命令枚举及快捷键映射：处理文本插入、删除、新行等编辑操作。
 
 use crossterm::event::{
-    KeyCode::{Backspace, Char, Delete, Enter, Tab},
+    KeyCode::{BackTab, Backspace, Char, Delete, Down, Enter, Tab, Up, F},
     KeyEvent, KeyModifiers,
 };
 
+use crate::editor::timestamp::TimestampFormat;
+
 // Edit 枚举，表示各种编辑命令，如插入字符、插入新行、删除字符、向后删除字符
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Edit {
     Insert(char),
     InsertNewline,
     Delete,
     DeleteBackward,
+    DeleteBackwardCodepoint,
+    MoveLineUp,
+    MoveLineDown,
+    InsertTimestamp(TimestampFormat),
+    Indent,
+    Dedent,
+    ExpandTabs,
+    UnexpandTabs,
+    DeleteToStartOfLine,
+    DeleteLine,
+    DuplicateLine,
+    CompleteWord,
+    Undo,
+    Redo,
+}
+
+impl Edit {
+    /// 通过按键配置中的动作名称解析，插入字符不能绑定
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "insert-newline" => Some(Self::InsertNewline),
+            "delete" => Some(Self::Delete),
+            "delete-backward" => Some(Self::DeleteBackward),
+            "delete-backward-codepoint" => Some(Self::DeleteBackwardCodepoint),
+            "move-line-up" => Some(Self::MoveLineUp),
+            "move-line-down" => Some(Self::MoveLineDown),
+            "insert-timestamp" => Some(Self::InsertTimestamp(TimestampFormat::Iso8601)),
+            "insert-date" => Some(Self::InsertTimestamp(TimestampFormat::ShortDate)),
+            "indent" => Some(Self::Indent),
+            "dedent" => Some(Self::Dedent),
+            "expand-tabs" => Some(Self::ExpandTabs),
+            "unexpand-tabs" => Some(Self::UnexpandTabs),
+            "delete-to-start-of-line" => Some(Self::DeleteToStartOfLine),
+            "delete-line" => Some(Self::DeleteLine),
+            "duplicate-line" => Some(Self::DuplicateLine),
+            "complete-word" => Some(Self::CompleteWord),
+            "undo" => Some(Self::Undo),
+            "redo" => Some(Self::Redo),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<KeyEvent> for Edit {
@@ -23,9 +66,28 @@ impl TryFrom<KeyEvent> for Edit {
                 Ok(Self::Insert(character))
             }
             (Tab, KeyModifiers::NONE) => Ok(Self::Insert('\t')),
+            (BackTab, KeyModifiers::NONE | KeyModifiers::SHIFT) => Ok(Self::Dedent),
             (Enter, KeyModifiers::NONE) => Ok(Self::InsertNewline),
             (Backspace, KeyModifiers::NONE) => Ok(Self::DeleteBackward),
+            (Backspace, KeyModifiers::ALT) => Ok(Self::DeleteBackwardCodepoint),
             (Delete, KeyModifiers::NONE) => Ok(Self::Delete),
+            (Up, KeyModifiers::ALT) => Ok(Self::MoveLineUp),
+            (Down, KeyModifiers::ALT) => Ok(Self::MoveLineDown),
+            (Char('t'), KeyModifiers::ALT) => Ok(Self::ExpandTabs),
+            (Char('T'), modifiers)
+                if modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT
+                    || modifiers == KeyModifiers::ALT =>
+            {
+                Ok(Self::UnexpandTabs)
+            }
+            (Char('u'), KeyModifiers::CONTROL) => Ok(Self::DeleteToStartOfLine),
+            (Char('z'), KeyModifiers::CONTROL) => Ok(Self::Undo),
+            (Char('y'), KeyModifiers::CONTROL) => Ok(Self::Redo),
+            (Char('n'), KeyModifiers::CONTROL) => Ok(Self::CompleteWord),
+            (Char('k'), KeyModifiers::CONTROL) => Ok(Self::DeleteLine),
+            (Char('d'), KeyModifiers::CONTROL) => Ok(Self::DuplicateLine),
+            (F(5), KeyModifiers::NONE) => Ok(Self::InsertTimestamp(TimestampFormat::Iso8601)),
+            (F(5), KeyModifiers::SHIFT) => Ok(Self::InsertTimestamp(TimestampFormat::ShortDate)),
             _ => Err(format!(
                 "Unsupported key code {:?} with modifiers {:?}",
                 event.code, event.modifiers
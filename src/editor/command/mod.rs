@@ -14,13 +14,24 @@ mod system;
 pub use system::System;
 
 //  Command 枚举，用于表示不同类型的命令：移动命令、编辑命令和系统命令
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Command {
     Move(Move),
+    Select(Move), // 按住 Shift 的移动：移动光标并扩展选择
     Edit(Edit),
     System(System),
 }
 
+impl Command {
+    /// 通过按键配置中的动作名称（如 quit、page-down、undo）解析
+    pub fn from_name(name: &str) -> Option<Self> {
+        System::from_name(name)
+            .map(Command::System)
+            .or_else(|| Move::from_name(name).map(Command::Move))
+            .or_else(|| Edit::from_name(name).map(Command::Edit))
+    }
+}
+
 impl TryFrom<Event> for Command {
     type Error = String;
     // 将 Event 转换为 Command
@@ -29,8 +40,10 @@ impl TryFrom<Event> for Command {
             Event::Key(key_event) => Edit::try_from(key_event)
                 .map(Command::Edit)
                 .or_else(|_| Move::try_from(key_event).map(Command::Move))
+                .or_else(|_| Move::try_from_shifted(key_event).map(Command::Select))
                 .or_else(|_| System::try_from(key_event).map(Command::System))
                 .map_err(|_err| format!("Event not supported: {key_event:?}")),
+            Event::Mouse(mouse_event) => Move::try_from(mouse_event).map(Command::Move),
             Event::Resize(width_u16, height_u16) => Ok(Self::System(System::Resize(Size {
                 height: height_u16 as usize,
                 width: width_u16 as usize,
@@ -1,21 +1,30 @@
-// Move 处理上下左右等移动操作。
+// Move 处理上下左右等移动操作，以及鼠标点击和滚轮。
 
 use crossterm::event::{
-    KeyCode::{Down, End, Home, Left, PageDown, PageUp, Right, Up},
-    KeyEvent, KeyModifiers,
+    KeyCode::{Char, Down, End, Home, Left, PageDown, PageUp, Right, Up},
+    KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
+use crate::prelude::*;
 
 /// 光标移动命令枚举，表示各种方向和范围的移动
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum Move {
     PageUp,       // 向上翻页
     PageDown,     // 向下翻页
-    StartOfLine,  // 移动到行首
+    HalfPageUp,   // 向上翻半页
+    HalfPageDown, // 向下翻半页
+    MatchingBracket, // 跳转到匹配的括号
+    JumpBack,     // 回到上一次跳转前的位置
+    JumpForward,  // 重新前往回退前的位置
+    StartOfLine,  // 移动到第一个非空白字符，已在该处时移动到行首
     EndOfLine,    // 移动到行尾
     Up,           // 向上移动一行
     Left,         // 向左移动一列
     Right,        // 向右移动一列
     Down,         // 向下移动一行
+    ScreenPosition(Position), // 鼠标点击：移动到终端中该位置下的字素
+    ScrollUp,     // 鼠标滚轮向上：视口上移几行，光标不动
+    ScrollDown,   // 鼠标滚轮向下：视口下移几行，光标不动
 }
 
 impl TryFrom<KeyEvent> for Move {
@@ -38,10 +47,69 @@ impl TryFrom<KeyEvent> for Move {
                 End => Ok(Self::EndOfLine),
                 _ => Err(format!("Unsupported code: {code:?}")),
             }
+        } else if modifiers == KeyModifiers::CONTROL && code == Char('b') {
+            Ok(Self::MatchingBracket)
+        } else if modifiers == KeyModifiers::ALT {
+            match code {
+                Char('d') => Ok(Self::HalfPageDown),
+                Char('u') => Ok(Self::HalfPageUp),
+                Left => Ok(Self::JumpBack),
+                Right => Ok(Self::JumpForward),
+                _ => Err(format!("Unsupported ALT+{code:?} combination")),
+            }
         } else {
             Err(format!(
                 "Unsupported key code {code:?} or modifier {modifiers:?}"
             ))
         }
     }
-}
\ No newline at end of file
+}
+
+impl Move {
+    /// 通过按键配置中的动作名称解析，鼠标产生的移动不能绑定
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "page-up" => Some(Self::PageUp),
+            "page-down" => Some(Self::PageDown),
+            "half-page-up" => Some(Self::HalfPageUp),
+            "half-page-down" => Some(Self::HalfPageDown),
+            "matching-bracket" => Some(Self::MatchingBracket),
+            "jump-back" => Some(Self::JumpBack),
+            "jump-forward" => Some(Self::JumpForward),
+            "start-of-line" => Some(Self::StartOfLine),
+            "end-of-line" => Some(Self::EndOfLine),
+            "up" => Some(Self::Up),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "down" => Some(Self::Down),
+            _ => None,
+        }
+    }
+
+    /// 将按住 Shift 的移动键（方向键、Home、End、翻页）转换为对应的移动命令，用于扩展选择
+    pub fn try_from_shifted(event: KeyEvent) -> Result<Self, String> {
+        if event.modifiers != KeyModifiers::SHIFT {
+            return Err(format!("Unsupported modifier {:?}", event.modifiers));
+        }
+        Self::try_from(KeyEvent {
+            modifiers: KeyModifiers::NONE,
+            ..event
+        })
+    }
+}
+
+impl TryFrom<MouseEvent> for Move {
+    type Error = String;
+    /// 将鼠标事件转换为移动命令，只处理左键按下和滚轮
+    fn try_from(event: MouseEvent) -> Result<Self, Self::Error> {
+        match event.kind {
+            MouseEventKind::Down(MouseButton::Left) => Ok(Self::ScreenPosition(Position {
+                col: event.column as usize,
+                row: event.row as usize,
+            })),
+            MouseEventKind::ScrollUp => Ok(Self::ScrollUp),
+            MouseEventKind::ScrollDown => Ok(Self::ScrollDown),
+            kind => Err(format!("Unsupported mouse event {kind:?}")),
+        }
+    }
+}
@@ -7,12 +7,103 @@ use crossterm::event::{
 };
 
 /// 系统命令枚举，表示保存、调整大小、退出、取消等操作
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub enum System {
     Save,         // 保存文件
     Resize(Size), // 调整终端大小
     Quit,         // 退出编辑器
     Dismiss,      // 取消/关闭当前操作
+    ShowMessageLog, // 显示最近的消息记录
+    ToggleWhitespace, // 切换空白字符的可见显示
+    ShowCharInfo, // 显示光标处字符的码位信息
+    ShowRecentFiles, // 列出最近文件并选择打开
+    ForceQuit, // 立即退出，丢弃未保存的更改
+    SaveAll, // 保存所有已修改的文件
+    Redraw, // 清屏并重绘整个界面
+    CountDigit(u8), // 输入重复次数的一位数字
+    RepeatLastEdit, // 重复上一次编辑
+    ShowWordCount, // 显示全文的词数和字符数
+    ToggleTrailingWhitespace, // 切换行尾空白高亮
+    ToggleLineEnding, // 在 LF 与 CRLF 之间切换换行符
+    ToggleAutoIndent, // 切换换行时是否沿用当前行的缩进
+    ToggleReadOnly, // 切换只读状态（强制写入）
+    InsertFile, // 将另一个文件的内容插入到光标处
+    PipeThroughCommand, // 用 shell 命令过滤全文
+    SetMark, // 在光标处设置书签
+    JumpToMark, // 跳转到书签
+    ToggleMacroRecording, // 开始或停止录制宏
+    PlayMacro, // 回放录制的宏
+    ToggleBlockSelection, // 进入或退出块选择模式
+    SaveSession, // 保存当前会话
+    Search, // 进入搜索提示
+    SearchNext, // 查找上一次搜索内容的下一个匹配
+    SearchPrevious, // 查找上一次搜索内容的上一个匹配
+    ToggleSearchWrap, // 切换搜索到达文件末尾时是否回绕
+    ToggleWholeWordSearch, // 切换搜索是否只匹配完整的词
+    ToggleDiffMarks, // 显示或隐藏与磁盘版本比较的改动标记
+    NextChange, // 跳到下一处改动
+    PreviousChange, // 跳到上一处改动
+    CheckIndentation, // 检查缩进是否混用制表符和空格
+    CycleLineNumbers, // 依次切换行号显示方式：关闭、绝对行号、相对行号
+    Copy, // 复制块选择的内容，没有块选择时复制当前行
+    Cut, // 剪切块选择的内容，没有块选择时剪切当前行
+    Paste, // 在光标处粘贴剪贴板中的文本
+    GoToLine, // 输入行号并跳转
+    Open, // 输入文件名并打开
+    Replace, // 输入搜索内容和替换内容，逐个确认替换
+    NextBuffer, // 切换到下一个打开的文件
+    PreviousBuffer, // 切换到上一个打开的文件
+}
+
+impl System {
+    /// 通过按键配置中的动作名称解析，带参数的命令（调整大小、重复次数）不能绑定
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "save" => Some(Self::Save),
+            "quit" => Some(Self::Quit),
+            "dismiss" => Some(Self::Dismiss),
+            "show-message-log" => Some(Self::ShowMessageLog),
+            "toggle-whitespace" => Some(Self::ToggleWhitespace),
+            "show-char-info" => Some(Self::ShowCharInfo),
+            "show-recent-files" => Some(Self::ShowRecentFiles),
+            "force-quit" => Some(Self::ForceQuit),
+            "save-all" => Some(Self::SaveAll),
+            "redraw" => Some(Self::Redraw),
+            "repeat-last-edit" => Some(Self::RepeatLastEdit),
+            "show-word-count" => Some(Self::ShowWordCount),
+            "toggle-trailing-whitespace" => Some(Self::ToggleTrailingWhitespace),
+            "toggle-line-ending" => Some(Self::ToggleLineEnding),
+            "toggle-auto-indent" => Some(Self::ToggleAutoIndent),
+            "toggle-read-only" => Some(Self::ToggleReadOnly),
+            "insert-file" => Some(Self::InsertFile),
+            "pipe-through-command" => Some(Self::PipeThroughCommand),
+            "set-mark" => Some(Self::SetMark),
+            "jump-to-mark" => Some(Self::JumpToMark),
+            "toggle-macro-recording" => Some(Self::ToggleMacroRecording),
+            "play-macro" => Some(Self::PlayMacro),
+            "toggle-block-selection" => Some(Self::ToggleBlockSelection),
+            "save-session" => Some(Self::SaveSession),
+            "search" => Some(Self::Search),
+            "search-next" => Some(Self::SearchNext),
+            "search-previous" => Some(Self::SearchPrevious),
+            "toggle-search-wrap" => Some(Self::ToggleSearchWrap),
+            "toggle-whole-word-search" => Some(Self::ToggleWholeWordSearch),
+            "toggle-diff-marks" => Some(Self::ToggleDiffMarks),
+            "next-change" => Some(Self::NextChange),
+            "previous-change" => Some(Self::PreviousChange),
+            "check-indentation" => Some(Self::CheckIndentation),
+            "cycle-line-numbers" => Some(Self::CycleLineNumbers),
+            "copy" => Some(Self::Copy),
+            "cut" => Some(Self::Cut),
+            "paste" => Some(Self::Paste),
+            "go-to-line" => Some(Self::GoToLine),
+            "open" => Some(Self::Open),
+            "replace" => Some(Self::Replace),
+            "next-buffer" => Some(Self::NextBuffer),
+            "previous-buffer" => Some(Self::PreviousBuffer),
+            _ => None,
+        }
+    }
 }
 
 impl TryFrom<KeyEvent> for System {
@@ -27,10 +118,71 @@ impl TryFrom<KeyEvent> for System {
             match code {
                 Char('q') => Ok(Self::Quit),   // Ctrl+Q 退出
                 Char('s') => Ok(Self::Save),   // Ctrl+S 保存
+                Char('e') => Ok(Self::ShowRecentFiles), // Ctrl+E 最近文件
+                Char('l') => Ok(Self::Redraw), // Ctrl+L 重绘屏幕
+                Char('f') => Ok(Self::Search), // Ctrl+F 搜索
+                Char('w') => Ok(Self::ToggleWholeWordSearch), // Ctrl+W 整词搜索
+                Char('c') => Ok(Self::Copy), // Ctrl+C 复制
+                Char('x') => Ok(Self::Cut), // Ctrl+X 剪切
+                Char('v') => Ok(Self::Paste), // Ctrl+V 粘贴
+                Char('g') => Ok(Self::GoToLine), // Ctrl+G 跳转到行
+                Char('o') => Ok(Self::Open), // Ctrl+O 打开文件
+                Char('r') => Ok(Self::Replace), // Ctrl+R 查找替换
+                KeyCode::PageDown => Ok(Self::NextBuffer), // Ctrl+PageDown 下一个文件
+                KeyCode::PageUp => Ok(Self::PreviousBuffer), // Ctrl+PageUp 上一个文件
                 _ => Err(format!("Unsupported CONTROL+{code:?} combination")),
             }
+        } else if modifiers == KeyModifiers::ALT {
+            match code {
+                Char('w') => Ok(Self::ToggleWhitespace), // Alt+W 显示/隐藏空白字符
+                Char('W') => Ok(Self::ToggleTrailingWhitespace), // Alt+Shift+W 行尾空白高亮
+                Char('F') => Ok(Self::ToggleSearchWrap), // Alt+Shift+F 搜索回绕
+                Char('N') => Ok(Self::PreviousChange), // Alt+Shift+N 上一处改动
+                Char('I') => Ok(Self::CheckIndentation), // Alt+Shift+I 检查缩进
+                Char('n') => Ok(Self::NextChange), // Alt+N 下一处改动
+                Char('q') => Ok(Self::ForceQuit), // Alt+Q 强制退出
+                Char('s') => Ok(Self::SaveAll), // Alt+S 全部保存
+                Char('i') => Ok(Self::ShowCharInfo),     // Alt+I 显示字符信息
+                Char('c') => Ok(Self::ShowWordCount), // Alt+C 统计词数
+                Char('l') => Ok(Self::ToggleLineEnding), // Alt+L 切换换行符
+                Char('a') => Ok(Self::ToggleAutoIndent), // Alt+A 切换自动缩进
+                Char('r') => Ok(Self::ToggleReadOnly), // Alt+R 切换只读
+                Char('f') => Ok(Self::InsertFile), // Alt+F 插入文件
+                Char('p') => Ok(Self::PipeThroughCommand), // Alt+P 通过命令过滤
+                Char('m') => Ok(Self::SetMark), // Alt+M 设置书签
+                Char('j') => Ok(Self::JumpToMark), // Alt+J 跳转到书签
+                Char('b') => Ok(Self::ToggleBlockSelection), // Alt+B 块选择
+                Char('g') => Ok(Self::CycleLineNumbers), // Alt+G 切换行号
+                Char(digit @ '0'..='9') => Ok(Self::CountDigit(digit as u8 - b'0')), // Alt+数字 重复次数
+                _ => Err(format!("Unsupported ALT+{code:?} combination")),
+            }
+        } else if modifiers == KeyModifiers::ALT | KeyModifiers::SHIFT {
+            match code {
+                Char('W') => Ok(Self::ToggleTrailingWhitespace), // Alt+Shift+W 行尾空白高亮
+                Char('F') => Ok(Self::ToggleSearchWrap), // Alt+Shift+F 搜索回绕
+                Char('N') => Ok(Self::PreviousChange), // Alt+Shift+N 上一处改动
+                Char('I') => Ok(Self::CheckIndentation), // Alt+Shift+I 检查缩进
+                _ => Err(format!("Unsupported ALT+SHIFT+{code:?} combination")),
+            }
         } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::Esc) {
             Ok(Self::Dismiss) // Esc 取消
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(2)) {
+            Ok(Self::ShowMessageLog) // F2 显示消息记录
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(3)) {
+            Ok(Self::SearchNext) // F3 查找下一个
+        } else if modifiers == KeyModifiers::SHIFT && matches!(code, KeyCode::F(3) | KeyCode::Enter) {
+            // Shift+F3 查找上一个；终端能区分 Shift+Enter 时它也可以
+            Ok(Self::SearchPrevious)
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(4)) {
+            Ok(Self::RepeatLastEdit) // F4 重复上一次编辑
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(6)) {
+            Ok(Self::ToggleMacroRecording) // F6 开始/停止录制宏
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(7)) {
+            Ok(Self::PlayMacro) // F7 回放宏
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(8)) {
+            Ok(Self::SaveSession) // F8 保存会话
+        } else if modifiers == KeyModifiers::NONE && matches!(code, KeyCode::F(9)) {
+            Ok(Self::ToggleDiffMarks) // F9 与磁盘版本比较
         } else {
             Err(format!(
                 "Unsupported key code {code:?} or modifier {modifiers:?}"
@@ -0,0 +1,136 @@
+// Config 读取 TOML 格式的配置文件 .editorrc：先查找当前目录，再查找配置目录下的 editorrc。
+// 文件不存在时使用默认设置；无法读取或格式有误时同样使用默认设置，并在启动后提示。
+
+use std::path::PathBuf;
+#[cfg(feature = "config")]
+use std::fs::read_to_string;
+
+#[cfg(feature = "config")]
+use serde::Deserialize;
+
+use super::{
+    configdir,
+    terminal::Theme,
+    uicomponents::{LineNumbers, StatusTemplate},
+};
+
+/// 当前目录中的配置文件名
+const LOCAL_FILE_NAME: &str = ".editorrc";
+/// 配置目录中的配置文件名
+const USER_FILE_NAME: &str = "editorrc";
+/// 制表符宽度的上限，避免误设过大的值
+#[cfg(feature = "config")]
+const MAX_TAB_WIDTH: usize = 16;
+
+/// 编辑器设置，配置文件中未出现的项使用默认值
+#[derive(Clone)]
+#[cfg_attr(
+    feature = "config",
+    derive(Deserialize),
+    serde(default, rename_all = "kebab-case", deny_unknown_fields)
+)]
+pub struct Config {
+    pub tab_width: usize,          // 制表符对应的空格数（tab-width）
+    pub expand_tabs: bool,         // 按 Tab 时插入空格而不是制表符（expand-tabs）
+    pub line_numbers: LineNumbers, // 行号显示方式（line-numbers = "off" | "absolute" | "relative"）
+    pub theme: Theme,              // 配色（theme = "dark" | "light"）
+    pub scroll_off: usize,         // 光标上下至少保留的行数（scroll-off）
+    pub quit_times: u8,            // 有未保存更改时退出所需的 Ctrl+Q 次数（quit-times）
+    pub auto_indent: bool,         // 换行时沿用上一行的缩进（auto-indent）
+    pub ensure_final_newline: bool, // 保存时确保文件以换行符结尾（ensure-final-newline）
+    pub backup: bool,              // 保存时把原有的内容复制到 文件名.bak（backup）
+    pub end_marker: String,        // 文件末尾之后各行显示的标记，空字符串表示留空（end-marker）
+    pub first_end_marker: Option<String>, // 紧接文件末尾的第一行的标记，未设置时与 end-marker 相同（first-end-marker）
+    pub overflow_markers: bool,    // 行的内容超出左右边缘时在两端显示 < 和 >（overflow-markers）
+    pub syntax_highlight: bool,    // 为 Rust、JSON 文件的关键字、字符串、数字和注释着色（syntax-highlight）
+    pub paste_guard_ms: u64,       // 按键间隔短于该毫秒数时视为粘贴并暂停自动缩进，0 表示关闭（paste-guard-ms）
+    pub status_left: StatusTemplate, // 状态栏左对齐部分的模板（status-left）
+    pub status_right: StatusTemplate, // 状态栏右对齐部分的模板（status-right）
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            expand_tabs: false,
+            line_numbers: LineNumbers::Absolute,
+            theme: Theme::Dark,
+            scroll_off: 3,
+            quit_times: 3,
+            auto_indent: true,
+            ensure_final_newline: false,
+            backup: false,
+            end_marker: "_".to_string(),
+            first_end_marker: None,
+            overflow_markers: false,
+            syntax_highlight: true,
+            paste_guard_ms: 10,
+            status_left: StatusTemplate::default_left(),
+            status_right: StatusTemplate::default_right(),
+        }
+    }
+}
+
+/// 读取配置文件时遇到的问题
+pub enum ConfigWarning {
+    /// 文件存在但无法读取（路径、错误信息）
+    #[cfg(feature = "config")]
+    Unreadable(PathBuf, String),
+    /// 文件内容不是有效的配置（路径、错误信息）
+    #[cfg(feature = "config")]
+    Malformed(PathBuf, String),
+    /// 找到了配置文件，但构建时未启用 config 功能（路径）
+    #[cfg(not(feature = "config"))]
+    Unsupported(PathBuf),
+}
+
+impl Config {
+    /// 按查找顺序返回第一个存在的配置文件
+    fn find_file() -> Option<PathBuf> {
+        std::iter::once(Some(PathBuf::from(LOCAL_FILE_NAME)))
+            .chain(std::iter::once(configdir::config_file(USER_FILE_NAME)))
+            .flatten()
+            .find(|path| path.is_file())
+    }
+
+    /// 读取配置文件；没有配置文件时返回默认设置，出错时返回默认设置和警告
+    #[cfg(feature = "config")]
+    pub fn load() -> (Self, Option<ConfigWarning>) {
+        let Some(path) = Self::find_file() else {
+            return (Self::default(), None);
+        };
+        let contents = match read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) => return (Self::default(), Some(ConfigWarning::Unreadable(path, err.to_string()))),
+        };
+        match Self::parse(&contents) {
+            Ok(config) => (config, None),
+            Err(err) => (Self::default(), Some(ConfigWarning::Malformed(path, err))),
+        }
+    }
+
+    /// 未启用 config 功能时始终使用默认设置，找到配置文件时提示需要重新构建
+    #[cfg(not(feature = "config"))]
+    pub fn load() -> (Self, Option<ConfigWarning>) {
+        (Self::default(), Self::find_file().map(ConfigWarning::Unsupported))
+    }
+
+    /// 解析并检查配置内容
+    #[cfg(feature = "config")]
+    fn parse(contents: &str) -> Result<Self, String> {
+        // 错误信息可能有多行，合并为一行以便在消息栏显示
+        let config: Self =
+            toml::from_str(contents).map_err(|err| err.message().trim().replace('\n', "; "))?;
+        if config.tab_width == 0 || config.tab_width > MAX_TAB_WIDTH {
+            return Err(format!("tab-width = {} (1..={MAX_TAB_WIDTH})", config.tab_width));
+        }
+        if config.quit_times == 0 {
+            return Err("quit-times = 0".to_string());
+        }
+        let markers = std::iter::once(&config.end_marker).chain(&config.first_end_marker);
+        if let Some(marker) = markers.into_iter().find(|marker| marker.chars().any(char::is_control)) {
+            return Err(format!("end-marker = {marker:?}"));
+        }
+        Ok(config)
+    }
+}
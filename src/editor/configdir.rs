@@ -0,0 +1,23 @@
+// configdir 定位编辑器保存配置与状态文件的目录：$XDG_CONFIG_HOME/text-editor，默认为 ~/.config/text-editor。
+
+use std::{env, path::PathBuf};
+
+use crate::prelude::*;
+
+/// 编辑器的配置目录
+pub fn config_dir() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            env::var_os("HOME")
+                .or_else(|| env::var_os("USERPROFILE"))
+                .map(|home| PathBuf::from(home).join(".config"))
+        })?;
+    Some(base.join(NAME))
+}
+
+/// 配置目录中指定文件的路径
+pub fn config_file(file_name: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(file_name))
+}
@@ -0,0 +1,101 @@
+// CursorPositions 按文件的绝对路径记录上次的光标位置，持久化到配置目录下的纯文本文件。
+// 每行格式为：行号<Tab>字素索引<Tab>路径。
+
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+use super::configdir;
+use crate::prelude::*;
+
+/// 保存光标位置的文件名
+const FILE_NAME: &str = "cursor_positions";
+/// 最多记录的文件数
+const LIMIT: usize = 200;
+
+/// 各文件上次的光标位置（最近记录的在前）
+#[derive(Default)]
+pub struct CursorPositions {
+    entries: Vec<(PathBuf, Location)>,
+}
+
+impl CursorPositions {
+    /// 从配置目录读取记录，文件不存在或无法读取时返回空记录
+    pub fn load() -> Self {
+        let entries = configdir::config_file(FILE_NAME)
+            .and_then(|file| read_to_string(file).ok())
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(Self::parse_entry)
+                    .take(LIMIT)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { entries }
+    }
+
+    /// 查询文件上次的光标位置
+    pub fn get(&self, path: &Path) -> Option<Location> {
+        let path = Self::normalize(path);
+        self.entries
+            .iter()
+            .find(|(existing, _)| existing == &path)
+            .map(|(_, location)| *location)
+    }
+
+    /// 记录文件的光标位置，并写回磁盘
+    pub fn set(&mut self, path: &Path, location: Location) {
+        let path = Self::normalize(path);
+        self.entries.retain(|(existing, _)| existing != &path);
+        self.entries.insert(0, (path, location));
+        self.entries.truncate(LIMIT);
+        // 位置记录只是便利功能，写入失败不影响编辑
+        let _ = self.save();
+    }
+
+    /// 将记录写入配置目录
+    fn save(&self) -> Result<(), Error> {
+        let Some(file) = configdir::config_file(FILE_NAME) else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            create_dir_all(dir)?;
+        }
+        let contents: String = self
+            .entries
+            .iter()
+            .map(|(path, location)| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    location.line_idx,
+                    location.grapheme_idx,
+                    path.display()
+                )
+            })
+            .collect();
+        write(file, contents)
+    }
+
+    /// 解析一行记录，格式不正确时忽略
+    fn parse_entry(line: &str) -> Option<(PathBuf, Location)> {
+        let mut parts = line.splitn(3, '\t');
+        let line_idx = parts.next()?.parse().ok()?;
+        let grapheme_idx = parts.next()?.parse().ok()?;
+        let path = parts.next().filter(|path| !path.is_empty())?;
+        Some((
+            PathBuf::from(path),
+            Location {
+                grapheme_idx,
+                line_idx,
+            },
+        ))
+    }
+
+    /// 统一为绝对路径，无法解析时保留原样
+    fn normalize(path: &Path) -> PathBuf {
+        path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+    }
+}
@@ -0,0 +1,35 @@
+// debuglog 将收到的命令、终端尺寸变化、保存结果和错误追加写入日志文件，便于排查问题。
+// 默认关闭：设置环境变量 TEXT_EDITOR_LOG=路径，或以 --log=路径 启动时才写入。
+
+use std::{
+    fs::{File, OpenOptions},
+    io::{Error, Write},
+    path::Path,
+    sync::{Mutex, OnceLock},
+};
+
+use super::timestamp::{self, TimestampFormat};
+
+/// 指定日志文件路径的环境变量
+pub const ENV_VAR: &str = "TEXT_EDITOR_LOG";
+
+/// 已打开的日志文件，未启用日志时为空
+static LOG_FILE: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// 以追加方式打开日志文件并启用日志，只在启动时调用一次
+pub fn init(path: &Path) -> Result<(), Error> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let _ = LOG_FILE.set(Mutex::new(file));
+    log(|| format!("--- {} started", crate::prelude::NAME));
+    Ok(())
+}
+
+/// 写入一条日志；未启用日志时不会调用 message，几乎没有开销
+pub fn log(message: impl FnOnce() -> String) {
+    let Some(file) = LOG_FILE.get() else {
+        return;
+    };
+    if let Ok(mut file) = file.lock() {
+        let _ = writeln!(file, "{} {}", timestamp::now(TimestampFormat::Iso8601), message());
+    }
+}
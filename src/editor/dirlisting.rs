@@ -0,0 +1,64 @@
+// DirListing 读取目录中的条目，打开目录时在编辑区逐行列出，供按回车选择打开。
+
+use std::{
+    fs::read_dir,
+    io::Error,
+    path::{Path, PathBuf},
+};
+use crate::prelude::*;
+
+/// 返回上级目录的条目
+const PARENT_ENTRY: &str = "../";
+
+/// 目录列表：子目录在前（以 / 结尾），文件在后，各自按名称排序
+pub struct DirListing {
+    dir: PathBuf,         // 列出的目录
+    entries: Vec<String>, // 各行显示的条目，第一行可能是上级目录
+}
+
+impl DirListing {
+    /// 读取目录；无法读取（如没有权限）时返回错误
+    pub fn read(dir: &Path) -> Result<Self, Error> {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut dirs = Vec::new();
+        let mut files = Vec::new();
+        for entry in read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() {
+                dirs.push(format!("{name}/"));
+            } else {
+                files.push(name);
+            }
+        }
+        dirs.sort();
+        files.sort();
+        let parent = dir.parent().map(|_| PARENT_ENTRY.to_string());
+        let entries = parent.into_iter().chain(dirs).chain(files).collect();
+        Ok(Self { dir, entries })
+    }
+
+    /// 返回列出的目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 返回各行显示的条目
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// 判断目录是否为空（不计上级目录）
+    pub fn is_empty(&self) -> bool {
+        self.entries.iter().all(|entry| entry == PARENT_ENTRY)
+    }
+
+    /// 返回第 line_idx 行条目对应的路径
+    pub fn path_at(&self, line_idx: LineIdx) -> Option<PathBuf> {
+        let entry = self.entries.get(line_idx)?;
+        if entry == PARENT_ENTRY {
+            return self.dir.parent().map(Path::to_path_buf);
+        }
+        Some(self.dir.join(entry.trim_end_matches('/')))
+    }
+}
@@ -0,0 +1,84 @@
+// Document 是不连接终端的文档接口：载入文本或文件，在光标处插入、删除一段文本或执行任意命令，再读回全文。
+// 内部是一个 headless 的 Editor，命令的效果（撤销记录、自动缩进、只读检查等）与按键触发时相同。
+
+use std::{
+    fmt::{self, Display},
+    io::Error,
+};
+
+use crate::prelude::*;
+
+use super::{command::Command, Editor};
+
+/// 文档使用的编辑区尺寸，影响翻页等与屏幕有关的命令
+const SIZE: Size = Size {
+    height: 24,
+    width: 80,
+};
+
+/// 不连接终端的文档
+pub struct Document {
+    editor: Editor,
+}
+
+impl Document {
+    /// 由文本创建文档，按换行符分行
+    pub fn new(text: &str) -> Self {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        Self {
+            editor: Editor::headless(&lines, SIZE),
+        }
+    }
+
+    /// 载入文件；文件不存在时为关联该文件名的空文档
+    pub fn open(file_name: &str) -> Result<Self, Error> {
+        let mut editor = Editor::headless(&[], SIZE);
+        editor.view.load(file_name)?;
+        Ok(Self { editor })
+    }
+
+    /// 在光标处原样插入文本，光标移到插入内容之后；只读时不做修改
+    pub fn insert_str(&mut self, text: &str) {
+        if self.editor.view.is_read_only() {
+            return;
+        }
+        self.editor.view.insert_str(text);
+        self.editor.view.finish_undo_step(false);
+    }
+
+    /// 删除 from 与 to 之间的文本（两者顺序不限），光标移到删除处；只读时不做修改
+    pub fn delete_range(&mut self, from: Location, to: Location) {
+        if self.editor.view.is_read_only() {
+            return;
+        }
+        self.editor.view.delete_range(from, to);
+        self.editor.view.finish_undo_step(false);
+    }
+
+    /// 执行一条命令，与按键触发时的处理相同
+    pub fn apply(&mut self, command: Command) {
+        self.editor.apply([command]);
+    }
+
+    /// 将光标移到指定位置，超出范围时校正到最近的有效位置
+    pub fn set_text_location(&mut self, location: Location) {
+        self.editor.view.restore_text_location(location);
+    }
+
+    /// 返回光标的文本位置（行、字素）
+    pub const fn text_location(&self) -> Location {
+        self.editor.text_location()
+    }
+
+    /// 返回文档的所有行
+    pub fn lines(&self) -> Vec<String> {
+        self.editor.lines()
+    }
+}
+
+impl Display for Document {
+    /// 输出全文，各行以换行符分隔
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "{}", self.lines().join("\n"))
+    }
+}
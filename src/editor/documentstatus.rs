@@ -1,39 +1,152 @@
-// DocumentStatus 记录文档的总行数、当前行、是否已修改、文件名，并提供格式化显示方法。
+// DocumentStatus 记录文档的总行数、当前行、是否已修改、文件名、视口位置，并提供格式化显示方法。
 
 use crate::prelude::*;
+use super::{messages, FileType, LineEnding, Mode};
 
 /// 文档状态信息
 #[derive(Default, Eq, PartialEq, Debug)]
 pub struct DocumentStatus {
     /// 文档总行数
     pub total_lines: usize,
+    /// 全文词数
+    pub total_words: usize,
+    /// 全文字符数（按字素计）
+    pub total_chars: usize,
+    /// 保存时的字节数
+    pub total_bytes: usize,
     /// 当前行号
     pub current_line_idx: LineIdx,
+    /// 当前列（字素索引）
+    pub current_col_idx: GraphemeIdx,
+    /// 当前列的显示位置（按显示宽度计，全角字符占两列）
+    pub current_col: ColIdx,
     /// 是否已修改
     pub is_modified: bool,
     /// 文件名
     pub file_name: String,
+    /// 文件类型
+    pub file_type: FileType,
+    /// 换行符
+    pub line_ending: LineEnding,
+    /// 是否只读
+    pub read_only: bool,
+    /// 文件是否按有损方式解码
+    pub lossy: bool,
+    /// 是否为按需读取的大文件（不统计词数和字符数）
+    pub large_file: bool,
+    /// 是否正在录制宏
+    pub recording_macro: bool,
+    /// 模态编辑的当前模式，None 表示未启用模态编辑
+    pub mode: Option<Mode>,
+    /// 视口顶部的行号
+    pub first_visible_line_idx: LineIdx,
+    /// 视口可显示的行数
+    pub visible_line_count: usize,
+    /// 当前文件在打开的文件中的序号（从 0 开始）
+    pub buffer_idx: usize,
+    /// 打开的文件数
+    pub buffer_count: usize,
 }
 
 impl DocumentStatus {
+    /// 返回文件名，文档已修改时在前面加上“*”
+    pub fn file_name_to_string(&self) -> String {
+        if self.is_modified {
+            format!("*{}", self.file_name)
+        } else {
+            self.file_name.clone()
+        }
+    }
     /// 返回“(modified)”或空字符串，指示文档是否被修改
     pub fn modified_indicator_to_string(&self) -> String {
         if self.is_modified {
-            "(modified)".to_string()
+            messages::modified_indicator().to_string()
+        } else {
+            String::new()
+        }
+    }
+    /// 返回“[RO]”或空字符串，指示文档是否只读
+    pub fn read_only_indicator_to_string(&self) -> String {
+        if self.read_only {
+            "[RO]".to_string()
+        } else {
+            String::new()
+        }
+    }
+    /// 返回“[lossy]”或空字符串，指示文件是否按有损方式解码
+    pub fn lossy_indicator_to_string(&self) -> String {
+        if self.lossy {
+            "[lossy]".to_string()
+        } else {
+            String::new()
+        }
+    }
+    /// 返回“录制中”或空字符串，指示是否正在录制宏
+    pub fn recording_indicator_to_string(&self) -> String {
+        if self.recording_macro {
+            messages::recording_indicator().to_string()
+        } else {
+            String::new()
+        }
+    }
+    /// 返回当前模式的名称，未启用模态编辑时为空字符串
+    pub fn mode_indicator_to_string(&self) -> String {
+        self.mode
+            .map(|mode| messages::mode_indicator(mode).to_string())
+            .unwrap_or_default()
+    }
+    /// 打开了多个文件时返回“[当前序号/文件数] ”，只有一个文件时为空字符串
+    pub fn buffer_indicator_to_string(&self) -> String {
+        if self.buffer_count > 1 {
+            format!("[{}/{}] ", self.buffer_idx.saturating_add(1), self.buffer_count)
         } else {
             String::new()
         }
     }
     /// 返回“xx lines”格式的总行数字符串
     pub fn line_count_to_string(&self) -> String {
-        format!("{} lines", self.total_lines)
+        messages::line_count(self.total_lines)
+    }
+    /// 返回全文统计，按详细程度从高到低：词数、字符数和字节数，词数和字符数，只有字符数。
+    /// 状态栏放不下时依次换用更短的一种
+    pub fn counts_to_strings(&self) -> [String; 3] {
+        if self.large_file {
+            let bytes = messages::byte_count(self.total_bytes);
+            return [bytes.clone(), bytes.clone(), bytes];
+        }
+        [
+            messages::text_counts(Some(self.total_words), self.total_chars, Some(self.total_bytes)),
+            messages::text_counts(Some(self.total_words), self.total_chars, None),
+            messages::text_counts(None, self.total_chars, None),
+        ]
     }
-    /// 返回“当前行/总行”格式的光标位置字符串
+    /// 返回“当前行/总行 : 列”格式的光标位置字符串，列按显示宽度计（从 1 开始）
     pub fn position_indicator_to_string(&self) -> String {
         format!(
-            "{}/{}",
+            "{}/{} : {}",
             self.current_line_idx.saturating_add(1),
-            self.total_lines
+            self.total_lines,
+            self.current_col.saturating_add(1)
         )
     }
+    /// 返回视口在文档中的位置：“All”、“Top”、“Bot”或“NN%”（与 vim 标尺一致）
+    pub fn scroll_indicator_to_string(&self) -> String {
+        let lines_above = self.first_visible_line_idx;
+        let lines_below = self.total_lines.saturating_sub(
+            self.first_visible_line_idx
+                .saturating_add(self.visible_line_count),
+        );
+        match (lines_above, lines_below) {
+            (0, 0) => "All".to_string(),
+            (0, _) => "Top".to_string(),
+            (_, 0) => "Bot".to_string(),
+            _ => {
+                let percent = lines_above
+                    .saturating_mul(100)
+                    .checked_div(lines_above.saturating_add(lines_below))
+                    .unwrap_or(0);
+                format!("{percent}%")
+            }
+        }
+    }
 }
@@ -0,0 +1,79 @@
+// FileType 根据文件扩展名识别文件类型，供状态栏显示和语法高亮使用。
+
+use std::{
+    fmt::{self, Display},
+    path::Path,
+};
+
+use super::messages;
+
+/// 文件类型
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum FileType {
+    Rust,
+    Markdown,
+    Json,
+    Toml,
+    Yaml,
+    Python,
+    C,
+    Cpp,
+    JavaScript,
+    TypeScript,
+    Html,
+    Css,
+    Shell,
+    #[default]
+    PlainText,
+}
+
+impl FileType {
+    /// 根据路径的扩展名识别文件类型，未知或没有扩展名时为纯文本
+    pub fn from_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(Self::PlainText, Self::from_extension)
+    }
+
+    /// 根据扩展名（不含点，忽略大小写）识别文件类型
+    pub fn from_extension(extension: &str) -> Self {
+        match extension.to_ascii_lowercase().as_str() {
+            "rs" => Self::Rust,
+            "md" | "markdown" => Self::Markdown,
+            "json" => Self::Json,
+            "toml" => Self::Toml,
+            "yaml" | "yml" => Self::Yaml,
+            "py" => Self::Python,
+            "c" | "h" => Self::C,
+            "cpp" | "cc" | "cxx" | "hpp" | "hh" => Self::Cpp,
+            "js" | "mjs" | "cjs" => Self::JavaScript,
+            "ts" => Self::TypeScript,
+            "html" | "htm" => Self::Html,
+            "css" => Self::Css,
+            "sh" | "bash" | "zsh" => Self::Shell,
+            _ => Self::PlainText,
+        }
+    }
+}
+
+impl Display for FileType {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Rust => "Rust",
+            Self::Markdown => "Markdown",
+            Self::Json => "JSON",
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+            Self::Python => "Python",
+            Self::C => "C",
+            Self::Cpp => "C++",
+            Self::JavaScript => "JavaScript",
+            Self::TypeScript => "TypeScript",
+            Self::Html => "HTML",
+            Self::Css => "CSS",
+            Self::Shell => "Shell",
+            Self::PlainText => messages::plain_text(),
+        };
+        write!(formatter, "{name}")
+    }
+}
@@ -0,0 +1,222 @@
+// KeyMap 读取配置目录下的 keys.toml，用其中的按键绑定覆盖默认的快捷键。
+// 每行一条绑定，如 "ctrl+w" = "quit"；动作写作 none 表示取消该键的默认绑定。
+// 未写出的按键沿用默认绑定；文件格式有误时全部使用默认绑定，无法识别的按键或动作只忽略该行。
+
+use std::{collections::HashMap, fs::read_to_string, io::ErrorKind, path::PathBuf};
+
+use crossterm::event::{Event, KeyCode, KeyModifiers};
+
+use super::{command::Command, configdir};
+
+/// 配置目录中的按键配置文件名
+const FILE_NAME: &str = "keys.toml";
+/// 取消默认绑定的动作名称
+const UNBIND_ACTION: &str = "none";
+
+/// 读取按键配置时遇到的问题
+pub enum KeyMapWarning {
+    /// 文件存在但无法读取（路径、错误信息）
+    Unreadable(PathBuf, String),
+    /// 文件某一行不是“按键 = 动作”的格式，已全部使用默认绑定（路径、行号）
+    Malformed(PathBuf, usize),
+    /// 无法识别的按键或动作，这些行已忽略（路径、各行内容）
+    Ignored(PathBuf, Vec<String>),
+}
+
+/// 用户自定义的按键绑定
+#[derive(Default)]
+pub struct KeyMap {
+    bindings: HashMap<(KeyCode, KeyModifiers), Option<Command>>, // None 表示取消该键的默认绑定
+}
+
+impl KeyMap {
+    /// 读取配置目录中的按键配置；文件不存在时只使用默认绑定，出错时附带警告
+    pub fn load() -> (Self, Option<KeyMapWarning>) {
+        let Some(path) = configdir::config_file(FILE_NAME) else {
+            return (Self::default(), None);
+        };
+        Self::load_from(path)
+    }
+
+    /// 读取指定的按键配置文件
+    fn load_from(path: PathBuf) -> (Self, Option<KeyMapWarning>) {
+        let contents = match read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return (Self::default(), None),
+            Err(err) => {
+                return (Self::default(), Some(KeyMapWarning::Unreadable(path, err.to_string())))
+            }
+        };
+        match Self::parse(&contents) {
+            Ok((key_map, ignored)) if ignored.is_empty() => (key_map, None),
+            Ok((key_map, ignored)) => (key_map, Some(KeyMapWarning::Ignored(path, ignored))),
+            Err(line_number) => (Self::default(), Some(KeyMapWarning::Malformed(path, line_number))),
+        }
+    }
+
+    /// 解析按键配置，返回绑定和无法识别的行；格式有误时返回出错的行号（从 1 开始）
+    fn parse(contents: &str) -> Result<(Self, Vec<String>), usize> {
+        let mut key_map = Self::default();
+        let mut ignored = Vec::new();
+        for (line_idx, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            // 跳过空行、注释和 [keys] 这样的表头
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') {
+                continue;
+            }
+            let malformed = line_idx.saturating_add(1);
+            let (key, action) = line.split_once('=').ok_or(malformed)?;
+            let (key, action) = (unquote(key), unquote(action));
+            if key.is_empty() || action.is_empty() {
+                return Err(malformed);
+            }
+            let command = if action == UNBIND_ACTION {
+                Some(None)
+            } else {
+                Command::from_name(action).map(Some)
+            };
+            match (parse_key(key), command) {
+                (Some(key), Some(command)) => {
+                    key_map.bindings.insert(key, command);
+                }
+                _ => ignored.push(line.to_string()),
+            }
+        }
+        Ok((key_map, ignored))
+    }
+
+    /// 将终端事件转换为命令：按键先查自定义绑定，没有绑定时使用默认的映射
+    pub fn translate(&self, event: Event) -> Result<Command, String> {
+        if let Event::Key(key_event) = event {
+            if let Some(binding) = self.bindings.get(&normalize(key_event.code, key_event.modifiers)) {
+                return binding.ok_or_else(|| format!("Key unbound: {key_event:?}"));
+            }
+        }
+        Command::try_from(event)
+    }
+}
+
+/// 去掉两端的空白和成对的引号
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| text.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or(text)
+}
+
+/// 字符键的大小写已经体现了 Shift，查找时忽略 Shift 修饰键，使 alt+shift+f 与 Alt+F 一致
+fn normalize(code: KeyCode, modifiers: KeyModifiers) -> (KeyCode, KeyModifiers) {
+    match code {
+        KeyCode::Char(_) => (code, modifiers - KeyModifiers::SHIFT),
+        _ => (code, modifiers),
+    }
+}
+
+/// 解析“修饰键+按键”形式的按键，如 ctrl+w、alt+shift+f、ctrl+pagedown、f3，不区分大小写
+fn parse_key(spec: &str) -> Option<(KeyCode, KeyModifiers)> {
+    let mut parts: Vec<&str> = spec.split('+').map(str::trim).collect();
+    let key = parts.pop()?;
+    let mut modifiers = KeyModifiers::NONE;
+    for part in parts {
+        modifiers |= match part.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" | "meta" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => return None,
+        };
+    }
+    let mut chars = key.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(character), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+            KeyCode::Char(character.to_ascii_uppercase())
+        }
+        (Some(character), None) => KeyCode::Char(character.to_ascii_lowercase()),
+        _ => match key.to_ascii_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "page-up" => KeyCode::PageUp,
+            "pagedown" | "page-down" => KeyCode::PageDown,
+            "enter" => KeyCode::Enter,
+            // 终端把 Shift+Tab 报告为 BackTab
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "esc" | "escape" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            name => KeyCode::F(name.strip_prefix('f')?.parse().ok().filter(|n| (1..=24).contains(n))?),
+        },
+    };
+    Some(normalize(code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEvent;
+    use crate::editor::tempfile::TempFile;
+
+    /// 将按键转换为命令，返回其调试输出，便于比较
+    fn translated(key_map: &KeyMap, code: KeyCode, modifiers: KeyModifiers) -> String {
+        format!("{:?}", key_map.translate(Event::Key(KeyEvent::new(code, modifiers))))
+    }
+
+    /// 写入临时的按键配置文件并读取
+    fn load(name: &str, contents: &str) -> (KeyMap, Option<KeyMapWarning>) {
+        let file = TempFile::with_contents(&format!("{name}.toml"), contents);
+        KeyMap::load_from(file.path().to_path_buf())
+    }
+
+    #[test]
+    fn custom_binding_overrides_the_default() {
+        let defaults = KeyMap::default();
+        assert_eq!(
+            translated(&defaults, KeyCode::Char('s'), KeyModifiers::CONTROL),
+            format!("{:?}", Ok::<_, String>(Command::from_name("save").unwrap()))
+        );
+        let (key_map, warning) = load("override", "[keys]\n\"ctrl+s\" = \"quit\"\nctrl+q = none\n");
+        assert!(warning.is_none());
+        assert_eq!(
+            translated(&key_map, KeyCode::Char('s'), KeyModifiers::CONTROL),
+            format!("{:?}", Ok::<_, String>(Command::from_name("quit").unwrap()))
+        );
+        assert!(key_map.translate(Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL))).is_err());
+        // 未写出的按键沿用默认绑定
+        assert_eq!(
+            translated(&key_map, KeyCode::Char('f'), KeyModifiers::CONTROL),
+            translated(&defaults, KeyCode::Char('f'), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn malformed_file_falls_back_to_defaults() {
+        let (key_map, warning) = load("malformed", "ctrl+s = quit\nthis line has no equals sign\n");
+        assert!(matches!(warning, Some(KeyMapWarning::Malformed(_, 2))));
+        assert!(key_map.bindings.is_empty());
+        assert_eq!(
+            translated(&key_map, KeyCode::Char('s'), KeyModifiers::CONTROL),
+            translated(&KeyMap::default(), KeyCode::Char('s'), KeyModifiers::CONTROL)
+        );
+    }
+
+    #[test]
+    fn unknown_keys_and_actions_are_ignored_line_by_line() {
+        let (key_map, warning) = load("ignored", "hyper+x = quit\nctrl+w = fly\nalt+shift+f = quit\n");
+        let Some(KeyMapWarning::Ignored(_, lines)) = warning else {
+            panic!("expected ignored lines");
+        };
+        assert_eq!(lines, ["hyper+x = quit", "ctrl+w = fly"]);
+        assert_eq!(key_map.bindings.len(), 1);
+        // alt+shift+f 与 Alt+F 相同
+        assert_eq!(
+            translated(&key_map, KeyCode::Char('F'), KeyModifiers::ALT | KeyModifiers::SHIFT),
+            format!("{:?}", Ok::<_, String>(Command::from_name("quit").unwrap()))
+        );
+    }
+}
@@ -1,7 +1,7 @@
 // GraphemeWidth 用于区分半宽和全宽字符的显示宽度。
 
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
 /// 字素宽度：Half 表示半宽，Full 表示全宽
 pub enum GraphemeWidth {
     Half, 
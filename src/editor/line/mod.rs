@@ -3,26 +3,46 @@
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 use std::{
-    collections::VecDeque,
+    cmp::{max, min},
     fmt::{self, Display},
     ops::{Deref, Range},
 };
 use crate::prelude::*;
 
+use super::{AnnotatedString, AnnotationType};
+
 mod graphemewidth;
-use graphemewidth::GraphemeWidth;
+pub use graphemewidth::GraphemeWidth;
 
 mod textfragment;
 use textfragment::TextFragment;
 
+/// 超过该字节数的行在调试构建中不再逐次校验增量更新的分片，避免长行编辑变成平方复杂度
+const MAX_CHECKED_LEN: ByteIdx = 4096;
+
 /// 行结构体，包含文本内容和分片信息
 #[derive(Default, Clone)]
 pub struct Line {
     fragments: Vec<TextFragment>, // fragments（文本片段向量）
     string: String, // string（字符串）
+    // 从 shift_from 开始的分片，其起始位置还需加上 shift_by 才是实际位置。编辑后其后的分片不立即平移，
+    // 下一次在别处编辑时才更新两处之间的分片，连续在同一处输入时每次只需常数时间
+    shift_from: GraphemeIdx,
+    shift_by: isize,
 }
 
 impl Line {
+    /// 返回从 col 列开始的片段所占的列数：制表符延伸到下一个制表位（每 tab_width 列一个），
+    /// 其他字素按渲染宽度
+    fn fragment_width(fragment: &TextFragment, col: ColIdx, tab_width: ColIdx) -> ColIdx {
+        if fragment.grapheme == "\t" {
+            let tab_width = tab_width.max(1);
+            tab_width.saturating_sub(col % tab_width)
+        } else {
+            fragment.rendered_width.into()
+        }
+    }
+
     /// 通过字符串构建一个 Line 实例
     pub fn from(line_str: &str) -> Self {
         debug_assert!(line_str.is_empty() || line_str.lines().count() == 1);
@@ -30,40 +50,116 @@ impl Line {
         Self {
             fragments,
             string: String::from(line_str),
+            ..Self::default()
+        }
+    }
+
+    /// 返回分片的起始字节位置（已计入尚未平移的偏移）
+    fn fragment_start(&self, idx: GraphemeIdx) -> ByteIdx {
+        let start = self.fragments[idx].start;
+        if idx >= self.shift_from {
+            start.wrapping_add_signed(self.shift_by)
+        } else {
+            start
         }
     }
 
+    /// 将尚未平移的范围的起点移到 idx，两者之间的分片直接更新。
+    /// 分片中暂存的位置可能是“实际位置减去偏移”，按回绕运算处理，加上偏移后总是正确的
+    fn move_shift_to(&mut self, idx: GraphemeIdx) {
+        let shift_by = self.shift_by;
+        if shift_by != 0 {
+            if idx > self.shift_from {
+                for fragment in &mut self.fragments[self.shift_from..idx] {
+                    fragment.start = fragment.start.wrapping_add_signed(shift_by);
+                }
+            } else {
+                for fragment in &mut self.fragments[idx..self.shift_from] {
+                    fragment.start = fragment.start.wrapping_add_signed(shift_by.wrapping_neg());
+                }
+            }
+        }
+        self.shift_from = idx;
+    }
+
+    /// 返回各分片及其实际起始位置，用于校验增量更新的结果
+    fn shifted_fragments(&self) -> Vec<TextFragment> {
+        (0..self.fragments.len())
+            .map(|idx| TextFragment {
+                start: self.fragment_start(idx),
+                ..self.fragments[idx].clone()
+            })
+            .collect()
+    }
+
     /// 字符串转换为文本片段的向量
     /// 每个片段包含 grapheme（字素）、rendered_width（渲染宽度）、start（开始位置）
     fn str_to_fragments(line_str: &str) -> Vec<TextFragment> {
         line_str
             .grapheme_indices(true)
-            .map(|(byte_idx, grapheme)| {
-                let (_replacement, rendered_width) = Self::get_replacement_character(grapheme)
-                    .map_or_else(
-                        || {
-                            let unicode_width = grapheme.width();
-                            let rendered_width = match unicode_width {
-                                0 | 1 => GraphemeWidth::Half,
-                                _ => GraphemeWidth::Full,
-                            };
-                            (None, rendered_width)
-                        },
-                        |replacement| (Some(replacement), GraphemeWidth::Half),
-                    );
-
-                TextFragment {
-                    grapheme: grapheme.to_string(),
-                    rendered_width,
-                    start: byte_idx,
-                }
-            })
+            .map(|(byte_idx, grapheme)| Self::grapheme_to_fragment(grapheme, byte_idx))
             .collect()
     }
-   
-    /// 重新构建分片信息
-    fn rebuild_fragments(&mut self) {
-        self.fragments = Self::str_to_fragments(&self.string);
+
+    /// 为从 start 字节开始的字素构建文本片段
+    fn grapheme_to_fragment(grapheme: &str, start: ByteIdx) -> TextFragment {
+        let (replacement, rendered_width) = Self::get_replacement_character(grapheme)
+            .map_or_else(
+                || {
+                    let unicode_width = grapheme.width();
+                    let rendered_width = match unicode_width {
+                        0 | 1 => GraphemeWidth::Half,
+                        _ => GraphemeWidth::Full,
+                    };
+                    (None, rendered_width)
+                },
+                |replacement| (Some(replacement), GraphemeWidth::Half),
+            );
+
+        TextFragment {
+            grapheme: grapheme.to_string(),
+            rendered_width,
+            replacement,
+            start,
+        }
+    }
+
+    /// 字符串中从 edit_start 开始的 removed_len 个字节被替换为 inserted_len 个字节之后更新分片：
+    /// 只重新切分受影响的字素，其后的分片记为待平移（见 shift_from），避免长行每输入一个字符都更新全部分片
+    fn update_fragments(&mut self, edit_start: ByteIdx, removed_len: usize, inserted_len: usize) {
+        // 新内容可能与前一个字素组合（如输入组合重音符），从前一个字素开始重新切分
+        let first = self.grapheme_idx_at_byte(edit_start).saturating_sub(1);
+        self.move_shift_to(first);
+        let resegment_start = self.byte_idx(first).min(edit_start);
+        let new_edit_end = edit_start.saturating_add(inserted_len);
+        let mut old_idx = self.grapheme_idx_at_byte(edit_start.saturating_add(removed_len));
+        let mut tail_idx = self.fragments.len();
+        let mut new_fragments = Vec::new();
+        for (offset, grapheme) in self.string[resegment_start..].grapheme_indices(true) {
+            let start = resegment_start.saturating_add(offset);
+            if start >= new_edit_end {
+                // 切分回到修改之后原有的字素边界时，其后的分片不受影响
+                let old_start = start.saturating_sub(inserted_len).saturating_add(removed_len);
+                while old_idx < self.fragments.len() && self.fragment_start(old_idx) < old_start {
+                    old_idx = old_idx.saturating_add(1);
+                }
+                if old_idx < self.fragments.len() && self.fragment_start(old_idx) == old_start {
+                    tail_idx = old_idx;
+                    break;
+                }
+            }
+            new_fragments.push(Self::grapheme_to_fragment(grapheme, start));
+        }
+        // 修改之后的分片留待以后平移
+        self.shift_from = first.saturating_add(new_fragments.len());
+        self.shift_by = self
+            .shift_by
+            .wrapping_add_unsigned(inserted_len)
+            .wrapping_sub_unsigned(removed_len);
+        self.fragments.splice(first..tail_idx, new_fragments);
+        debug_assert!(
+            self.string.len() > MAX_CHECKED_LEN || self.shifted_fragments() == Self::str_to_fragments(&self.string)
+        );
     }
 
     /// 根据输入字符串返回一个替代字符，用于表示特定的控制字符或空白字符
@@ -86,14 +182,183 @@ impl Line {
         }
     }
 
-    /// 获取给定列索引中可见的字素。
-    /// 只保留基础字符串截取
-    pub fn get_visible_graphemes(&self, range: Range<ColIdx>) -> String {
-        // 假设 range.start/end 是字素索引，直接用 grapheme_indices 截取
-        let graphemes: Vec<&str> = self.string.graphemes(true).collect();
-        let start = range.start.min(graphemes.len());
-        let end = range.end.min(graphemes.len());
-        graphemes[start..end].concat()
+    /// 显示空白字符时使用的符号
+    fn get_whitespace_symbol(grapheme: &str) -> Option<char> {
+        match grapheme {
+            " " => Some('·'),
+            "\t" => Some('→'),
+            _ => None,
+        }
+    }
+
+    /// 获取给定列范围（按渲染宽度计算）中可见的字素。
+    /// show_whitespace 为真时空格显示为 `·`、制表符显示为 `→`（仅影响渲染）。
+    pub fn get_visible_graphemes(&self, range: Range<ColIdx>, show_whitespace: bool, tab_width: ColIdx) -> String {
+        self.get_annotated_visible_graphemes(range, show_whitespace, tab_width, |_, _| None)
+            .to_string()
+    }
+
+    /// 获取给定列范围中可见的字素，并按 annotate(字素索引, 起始列) 的结果为每个字素添加标注。
+    /// 被范围边界截断的全宽字素用空格填充其可见部分，以保持列对齐；制表符以空格填充到下一个制表位。
+    pub fn get_annotated_visible_graphemes(
+        &self,
+        range: Range<ColIdx>,
+        show_whitespace: bool,
+        tab_width: ColIdx,
+        annotate: impl Fn(GraphemeIdx, ColIdx) -> Option<AnnotationType>,
+    ) -> AnnotatedString {
+        let mut result = AnnotatedString::default();
+        if range.start >= range.end {
+            return result;
+        }
+        let mut current_pos: ColIdx = 0;
+        for (grapheme_idx, fragment) in self.fragments.iter().enumerate() {
+            let fragment_end = current_pos.saturating_add(Self::fragment_width(fragment, current_pos, tab_width));
+            if current_pos >= range.end {
+                break;
+            }
+            if fragment_end > range.start {
+                let annotation_type = annotate(grapheme_idx, current_pos);
+                if current_pos < range.start || fragment_end > range.end {
+                    // 字素只有一部分落在可见范围内
+                    let visible_width =
+                        min(fragment_end, range.end).saturating_sub(max(current_pos, range.start));
+                    result.push_str(&" ".repeat(visible_width), annotation_type);
+                } else if let Some(symbol) = show_whitespace
+                    .then(|| Self::get_whitespace_symbol(&fragment.grapheme))
+                    .flatten()
+                {
+                    result.push(symbol, annotation_type);
+                    Self::pad_tab(&mut result, fragment, fragment_end - current_pos, annotation_type);
+                } else if let Some(replacement) = fragment.replacement {
+                    result.push(replacement, annotation_type);
+                    Self::pad_tab(&mut result, fragment, fragment_end - current_pos, annotation_type);
+                } else {
+                    result.push_str(&fragment.grapheme, annotation_type);
+                }
+            }
+            current_pos = fragment_end;
+        }
+        result
+    }
+
+    /// 制表符已输出第一列的符号后，用空格补齐其余的列
+    fn pad_tab(
+        result: &mut AnnotatedString,
+        fragment: &TextFragment,
+        width: ColIdx,
+        annotation_type: Option<AnnotationType>,
+    ) {
+        if fragment.grapheme == "\t" && width > 1 {
+            result.push_str(&" ".repeat(width - 1), annotation_type);
+        }
+    }
+
+    /// 返回指定索引处的字素及其渲染宽度
+    pub fn grapheme_at(&self, grapheme_idx: GraphemeIdx) -> Option<(&str, GraphemeWidth)> {
+        self.fragments
+            .get(grapheme_idx)
+            .map(|fragment| (fragment.grapheme.as_str(), fragment.rendered_width))
+    }
+
+    /// 返回指定字素范围内的文本，范围超出行尾的部分忽略
+    pub fn text_range(&self, range: Range<GraphemeIdx>) -> &str {
+        let start = self.byte_idx(range.start);
+        &self.string[start..max(start, self.byte_idx(range.end))]
+    }
+
+    /// 将指定字素范围内的内容替换为 text，返回替换内容之后第一个字素的索引
+    pub fn replace_range(&mut self, range: Range<GraphemeIdx>, text: &str) -> GraphemeIdx {
+        let start = self.byte_idx(range.start);
+        let end = max(start, self.byte_idx(range.end));
+        self.string.replace_range(start..end, text);
+        self.update_fragments(start, end.saturating_sub(start), text.len());
+        self.grapheme_idx_at_byte(start.saturating_add(text.len()))
+    }
+
+    /// 将字节范围 range 内的内容替换为 text，range 的两端须在字符边界上
+    pub fn replace_bytes(&mut self, range: Range<ByteIdx>, text: &str) {
+        self.string.replace_range(range.clone(), text);
+        self.update_fragments(range.start, range.len(), text.len());
+    }
+
+    /// 返回字素的起始字节位置，超出行尾时为行的字节长度
+    fn byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
+        if grapheme_idx < self.fragments.len() {
+            self.fragment_start(grapheme_idx)
+        } else {
+            self.string.len()
+        }
+    }
+
+    /// 返回第一个不早于指定字节位置开始的字素的索引，没有时为字素数量（行尾）
+    pub fn grapheme_idx_at_byte(&self, byte_idx: ByteIdx) -> GraphemeIdx {
+        // 分片的起始位置递增，二分查找
+        let (mut low, mut high) = (0, self.fragments.len());
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.fragment_start(mid) < byte_idx {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+        low
+    }
+
+    /// 返回行尾连续空格和制表符中第一个字素的索引，没有行尾空白时等于字素数量
+    pub fn trailing_whitespace_start(&self) -> GraphemeIdx {
+        let trailing = self
+            .fragments
+            .iter()
+            .rev()
+            .take_while(|fragment| matches!(fragment.grapheme.as_str(), " " | "\t"))
+            .count();
+        self.grapheme_count().saturating_sub(trailing)
+    }
+
+    /// 返回行首连续的空格和制表符
+    pub fn indentation(&self) -> &str {
+        let content_len = self.string.trim_start_matches([' ', '\t']).len();
+        &self.string[..self.string.len().saturating_sub(content_len)]
+    }
+
+    /// 返回 query 在行中每次出现的起始字素索引（按顺序，允许重叠）。
+    /// 只计起止都落在字素边界上的匹配，避免匹配到组合字符或表情的一部分。
+    pub fn match_grapheme_indices(&self, query: &str) -> Vec<GraphemeIdx> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        (0..self.grapheme_count())
+            .filter(|&grapheme_idx| {
+                let start = self.fragment_start(grapheme_idx);
+                self.string[start..].starts_with(query)
+                    && self.is_grapheme_boundary(start.saturating_add(query.len()))
+            })
+            .collect()
+    }
+
+    /// 判断从 at 开始的 query 是否为一个完整的词：起止两端都是 Unicode 单词边界
+    /// （行首、行尾或与非单词字素相邻），例如 "cat" 不匹配 "category" 中的部分
+    pub fn is_whole_word(&self, at: GraphemeIdx, query: &str) -> bool {
+        if at >= self.grapheme_count() {
+            return false;
+        }
+        let start = self.fragment_start(at);
+        let end = start.saturating_add(query.len());
+        let mut boundaries = self
+            .string
+            .split_word_bound_indices()
+            .map(|(byte_idx, _)| byte_idx)
+            .chain(Some(self.string.len()));
+        boundaries.any(|byte_idx| byte_idx == start) && boundaries.any(|byte_idx| byte_idx == end)
+    }
+
+    /// 判断字节索引是否位于字素边界（含行尾）
+    fn is_grapheme_boundary(&self, byte_idx: usize) -> bool {
+        let grapheme_idx = self.grapheme_idx_at_byte(byte_idx);
+        byte_idx == self.string.len()
+            || (grapheme_idx < self.grapheme_count() && self.fragment_start(grapheme_idx) == byte_idx)
     }
 
     /// 返回行中的字素数量
@@ -101,33 +366,43 @@ impl Line {
         self.fragments.len()
     }
 
-    /// 计算直到指定字素的列宽
-    pub fn width_until(&self, grapheme_idx: GraphemeIdx) -> ColIdx {
+    /// 计算直到指定字素的列宽，制表符按所在列对齐到下一个制表位
+    pub fn width_until(&self, grapheme_idx: GraphemeIdx, tab_width: ColIdx) -> ColIdx {
         self.fragments
             .iter()
             .take(grapheme_idx)
-            .map(|fragment| match fragment.rendered_width {
-                GraphemeWidth::Half => 1,
-                GraphemeWidth::Full => 2,
-            })
-            .sum()
+            .fold(0, |col, fragment| col.saturating_add(Self::fragment_width(fragment, col, tab_width)))
+    }
+
+    /// 返回覆盖指定列的字素索引；列超出行宽时返回字素数量（行尾）
+    pub fn grapheme_idx_at_col(&self, col: ColIdx, tab_width: ColIdx) -> GraphemeIdx {
+        let mut current_pos: ColIdx = 0;
+        for (grapheme_idx, fragment) in self.fragments.iter().enumerate() {
+            current_pos = current_pos.saturating_add(Self::fragment_width(fragment, current_pos, tab_width));
+            if current_pos > col {
+                return grapheme_idx;
+            }
+        }
+        self.grapheme_count()
     }
 
     /// 返回整行的列宽
-    pub fn width(&self) -> ColIdx {
-        self.width_until(self.grapheme_count())
+    pub fn width(&self, tab_width: ColIdx) -> ColIdx {
+        self.width_until(self.grapheme_count(), tab_width)
+    }
+
+    /// 按 Unicode 单词边界统计词数：拉丁文按单词计，每个汉字单独计为一个词，标点和表情不计
+    pub fn word_count(&self) -> usize {
+        self.string.unicode_words().count()
     }
 
     /// 在指定字素索引处插入字符
     /// 将一个字符插入到行中，或者如果 at == grapheme_count + 1，则将其附加到行尾
     pub fn insert_char(&mut self, character: char, at: GraphemeIdx) {
         debug_assert!(at.saturating_sub(1) <= self.grapheme_count());
-        if let Some(fragment) = self.fragments.get(at) {
-            self.string.insert(fragment.start, character);
-        } else {
-            self.string.push(character);
-        }
-        self.rebuild_fragments();
+        let start = self.byte_idx(at);
+        self.string.insert(start, character);
+        self.update_fragments(start, 0, character.len_utf8());
     }
 
     /// 追加字符
@@ -139,13 +414,38 @@ impl Line {
     pub fn delete(&mut self, at: GraphemeIdx) {
         debug_assert!(at <= self.grapheme_count());
         if let Some(fragment) = self.fragments.get(at) {
-            let start = fragment.start;
-            let end = fragment.start.saturating_add(fragment.grapheme.len());
+            let start = self.fragment_start(at);
+            let end = start.saturating_add(fragment.grapheme.len());
             self.string.drain(start..end);
-            self.rebuild_fragments();
+            self.update_fragments(start, end.saturating_sub(start), 0);
         }
     }
 
+    /// 删除指定字素的最后一个码位，例如把 "é"（e + 组合重音符）变为 "e"；
+    /// 字素只有一个码位时整个删除。删除后剩余的码位可能与相邻字素重新组合
+    pub fn delete_last_codepoint(&mut self, at: GraphemeIdx) {
+        let Some(fragment) = self.fragments.get(at) else {
+            return;
+        };
+        let end = self.fragment_start(at).saturating_add(fragment.grapheme.len());
+        let last_len = fragment.grapheme.chars().last().map_or(0, char::len_utf8);
+        self.string.drain(end.saturating_sub(last_len)..end);
+        self.update_fragments(end.saturating_sub(last_len), last_len, 0);
+    }
+
+    /// 删除指定字素范围内的内容：按分片的字节偏移一次性移除对应的字节，只更新一次分片。
+    /// 范围超出行尾的部分忽略
+    pub fn remove_range(&mut self, range: Range<GraphemeIdx>) {
+        let end = min(range.end, self.grapheme_count());
+        if range.start >= end {
+            return;
+        }
+        let start_byte = self.fragment_start(range.start);
+        let end_byte = self.byte_idx(end);
+        self.string.drain(start_byte..end_byte);
+        self.update_fragments(start_byte, end_byte.saturating_sub(start_byte), 0);
+    }
+
     /// 删除行末尾的字符
     pub fn delete_last(&mut self) {
         self.delete(self.grapheme_count().saturating_sub(1));
@@ -153,51 +453,32 @@ impl Line {
 
     /// 将另一行的内容附加到当前行，并更新 fragments
     pub fn append(&mut self, other: &Self) {
+        let start = self.string.len();
         self.string.push_str(&other.string);
-        self.rebuild_fragments();
+        self.update_fragments(start, 0, other.string.len());
     }
 
     /// 在指定字素索引处拆分行，并返回拆分后的剩余部分
     pub fn split(&mut self, at: GraphemeIdx) -> Self {
-        if let Some(fragment) = self.fragments.get(at) {
-            let remainder = self.string.split_off(fragment.start);
-            self.rebuild_fragments();
-            Self::from(&remainder)
+        if at < self.grapheme_count() {
+            // 在字素边界拆开时两部分各自的切分不变，剩余部分的分片只需平移起始位置，同样延迟进行
+            self.move_shift_to(at);
+            let start = self.fragment_start(at);
+            let rest = Self {
+                fragments: self.fragments.split_off(at),
+                string: self.string.split_off(start),
+                shift_from: 0,
+                shift_by: self.shift_by.wrapping_sub_unsigned(start),
+            };
+            self.shift_by = 0;
+            debug_assert!(
+                rest.string.len() > MAX_CHECKED_LEN || rest.shifted_fragments() == Self::str_to_fragments(&rest.string)
+            );
+            rest
         } else {
             Self::default()
         }
     }
-
-    /// 将字节索引转换为字素索引
-    fn byte_idx_to_grapheme_idx(&self, byte_idx: ByteIdx) -> Option<GraphemeIdx> {
-        if byte_idx > self.string.len() {
-            return None;
-        }
-        self.fragments
-            .iter()
-            .position(|fragment| fragment.start >= byte_idx)
-    }
-
-    /// 将字素索引转换为字节索引
-    fn grapheme_idx_to_byte_idx(&self, grapheme_idx: GraphemeIdx) -> ByteIdx {
-        debug_assert!(grapheme_idx <= self.grapheme_count());
-        if grapheme_idx == 0 || self.grapheme_count() == 0 {
-            return 0;
-        }
-        self.fragments.get(grapheme_idx).map_or_else(
-            || {
-                #[cfg(debug_assertions)]
-                {
-                    panic!("Fragment not found for grapheme index: {grapheme_idx:?}");
-                }
-                #[cfg(not(debug_assertions))]
-                {
-                    0
-                }
-            },
-            |fragment| fragment.start,
-        )
-    }
 }
 
 impl Display for Line {
@@ -212,4 +493,194 @@ impl Deref for Line {
     fn deref(&self) -> &Self::Target {
         &self.string
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_extends_to_next_tab_stop() {
+        let line = Line::from("ab\tc");
+        assert_eq!(line.width_until(3, 4), 4);
+        assert_eq!(line.width_until(3, 8), 8);
+        assert_eq!(line.width(4), 5);
+        assert_eq!(line.grapheme_idx_at_col(3, 4), 2);
+        assert_eq!(line.grapheme_idx_at_col(4, 4), 3);
+    }
+
+    #[test]
+    fn visible_ascii_is_sliced_by_column() {
+        let line = Line::from("abcdef");
+        assert_eq!(line.get_visible_graphemes(0..3, false, 4), "abc");
+        assert_eq!(line.get_visible_graphemes(2..4, false, 4), "cd");
+        assert_eq!(line.get_visible_graphemes(4..10, false, 4), "ef");
+        assert_eq!(line.get_visible_graphemes(6..10, false, 4), "");
+    }
+
+    #[test]
+    fn visible_cjk_counts_two_columns_and_pads_cut_graphemes() {
+        let line = Line::from("你好世界");
+        assert_eq!(line.width(4), 8);
+        assert_eq!(line.get_visible_graphemes(2..6, false, 4), "好世");
+        // 两端被截断的字各剩一列，用空格填充
+        assert_eq!(line.get_visible_graphemes(1..5, false, 4), " 好 ");
+        assert_eq!(line.get_visible_graphemes(3..4, false, 4), " ");
+        assert_eq!(line.get_visible_graphemes(7..20, false, 4), " ");
+    }
+
+    #[test]
+    fn visible_combining_mark_stays_with_its_base() {
+        let line = Line::from("e\u{301}x字");
+        assert_eq!(line.grapheme_count(), 3);
+        assert_eq!(line.width(4), 4);
+        assert_eq!(line.get_visible_graphemes(0..1, false, 4), "e\u{301}");
+        assert_eq!(line.get_visible_graphemes(1..4, false, 4), "x字");
+        assert_eq!(line.get_visible_graphemes(0..3, false, 4), "e\u{301}x ");
+    }
+
+    #[test]
+    fn visible_text_fills_exactly_the_range_width() {
+        let line = Line::from("a字e\u{301}b字字c");
+        let width = line.width(4);
+        for start in 0..width {
+            for end in start..=width {
+                let text = line.get_visible_graphemes(start..end, false, 4);
+                assert_eq!(text.width(), end - start, "{start}..{end}: {text:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn word_count_follows_unicode_word_boundaries() {
+        // 拉丁文按单词计，撇号和小数点不拆分单词，标点不计
+        assert_eq!(Line::from("Hello, world! don't 3.14").word_count(), 4);
+        // 每个汉字单独计为一个词
+        assert_eq!(Line::from("你好，世界").word_count(), 4);
+        // 表情不计：Rust、编、程、is、fun
+        assert_eq!(Line::from("Rust 编程 is fun 😀🦀!").word_count(), 5);
+        assert_eq!(Line::from("👨\u{200d}👩\u{200d}👧 🇨🇳").word_count(), 0);
+        assert_eq!(Line::from("").word_count(), 0);
+    }
+
+    #[test]
+    fn character_count_is_in_graphemes() {
+        // 组合字符、ZWJ 序列和国旗各算一个字符
+        assert_eq!(Line::from("e\u{301}字👨\u{200d}👩\u{200d}👧🇨🇳a").grapheme_count(), 5);
+        assert_eq!(Line::from("Rust 编程 is fun 😀🦀!").grapheme_count(), 18);
+    }
+
+    #[test]
+    fn remove_range_with_wide_and_combining_graphemes() {
+        // 字素：a 字 e\u{301} 🇨🇳 b 界
+        let text = "a字e\u{301}🇨🇳b界";
+        let mut line = Line::from(text);
+        assert_eq!(line.grapheme_count(), 6);
+        line.remove_range(1..3);
+        assert_eq!(line.to_string(), "a🇨🇳b界");
+        assert_eq!(line.width(4), 6);
+        assert_fragments_match(&line);
+        let mut line = Line::from(text);
+        line.remove_range(2..4);
+        assert_eq!(line.to_string(), "a字b界");
+        assert_eq!(line.width_until(3, 4), 4);
+        assert_fragments_match(&line);
+    }
+
+    #[test]
+    fn remove_range_clamps_and_ignores_empty_ranges() {
+        let text = "字e\u{301}界";
+        let mut line = Line::from(text);
+        line.remove_range(2..2);
+        line.remove_range(3..9);
+        assert_eq!(line.to_string(), text);
+        line.remove_range(1..9);
+        assert_eq!(line.to_string(), "字");
+        assert_fragments_match(&line);
+        line.remove_range(0..1);
+        assert_eq!(line.to_string(), "");
+        assert_eq!(line.grapheme_count(), 0);
+    }
+
+    /// 断言增量更新的分片与整行重建的结果相同
+    fn assert_fragments_match(line: &Line) {
+        assert!(line.shifted_fragments() == Line::str_to_fragments(&line.string));
+    }
+
+    /// 生成 len 个字符的长行，混合 ASCII、汉字和组合字符
+    fn long_line(len: usize) -> Line {
+        let text: String = "ab字e\u{301}".chars().cycle().take(len).collect();
+        Line::from(&text)
+    }
+
+    #[test]
+    fn combining_mark_joins_previous_grapheme() {
+        let mut line = Line::from("hello");
+        line.insert_char('\u{301}', 2);
+        assert_eq!(line.grapheme_count(), 5);
+        assert_eq!(line.grapheme_at(1), Some(("e\u{301}", GraphemeWidth::Half)));
+        assert_eq!(line.byte_idx(2), 4);
+        assert_fragments_match(&line);
+    }
+
+    #[test]
+    fn deleting_combining_mark_splits_grapheme() {
+        let mut line = Line::from("cafe\u{301}!");
+        line.delete_last_codepoint(3);
+        assert_eq!(&*line, "cafe!");
+        assert_eq!(line.grapheme_count(), 5);
+        assert_fragments_match(&line);
+    }
+
+    #[test]
+    fn zero_width_joiner_and_flags_join_across_inserts() {
+        let mut line = Line::from("x👨y");
+        line.insert_char('\u{200d}', 2);
+        line.insert_char('👩', 2);
+        assert_eq!(line.grapheme_count(), 3);
+        assert_eq!(line.grapheme_at(1).map(|(grapheme, _)| grapheme), Some("👨\u{200d}👩"));
+        let mut flag = Line::from("🇺");
+        flag.append_char('🇸');
+        assert_eq!(flag.grapheme_count(), 1);
+        assert_fragments_match(&line);
+        assert_fragments_match(&flag);
+    }
+
+    #[test]
+    fn edits_at_both_ends_and_middle_keep_offsets() {
+        let mut line = Line::from("字ab");
+        line.insert_char('x', 0);
+        line.append_char('字');
+        line.insert_char('y', 2);
+        line.delete(0);
+        line.remove_range(1..3);
+        assert_eq!(&*line, "字b字");
+        assert_eq!(line.byte_idx(2), 4);
+        assert_fragments_match(&line);
+    }
+
+    #[test]
+    fn split_keeps_pending_offsets() {
+        let mut line = Line::from("ab字cd");
+        line.insert_char('字', 1);
+        let rest = line.split(3);
+        assert_eq!((&*line, &*rest), ("a字b", "字cd"));
+        assert_eq!(rest.byte_idx(1), 3);
+        assert_eq!(rest.grapheme_idx_at_byte(4), 2);
+        assert_fragments_match(&line);
+        assert_fragments_match(&rest);
+    }
+
+    #[test]
+    fn scattered_edits_on_long_line_match_rebuild() {
+        let mut line = long_line(10_000);
+        for step in 0..200 {
+            let at = step * 37 % line.grapheme_count();
+            if step % 3 == 0 {
+                line.delete(at);
+            } else {
+                line.insert_char(if step % 2 == 0 { '字' } else { '\u{301}' }, at);
+            }
+        }
+        assert_fragments_match(&line);
+    }
+}
@@ -0,0 +1,76 @@
+// LineEnding 表示文件使用的换行符（LF 或 CRLF），加载时检测，保存时按原样写回。
+// 混用两种换行符的文件统一为其中较多的一种。
+
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+/// 换行符
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
+pub enum LineEnding {
+    #[default]
+    Lf,   // \n
+    CrLf, // \r\n
+}
+
+impl LineEnding {
+    /// 检测文本使用的换行符：取出现次数多的一种，次数相同时取第一个换行符的类型，
+    /// 没有换行时默认为 LF。第二个返回值表示文本是否混用了两种换行符
+    pub fn detect(contents: &str) -> (Self, bool) {
+        let total = contents.matches('\n').count();
+        let crlf = contents.matches("\r\n").count();
+        let lf = total.saturating_sub(crlf);
+        let line_ending = match crlf.cmp(&lf) {
+            Ordering::Greater => Self::CrLf,
+            Ordering::Less => Self::Lf,
+            Ordering::Equal => match contents.find('\n') {
+                Some(idx) if contents[..idx].ends_with('\r') => Self::CrLf,
+                _ => Self::Lf,
+            },
+        };
+        (line_ending, crlf > 0 && lf > 0)
+    }
+
+    /// 换行符本身
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+
+    /// 另一种换行符
+    pub const fn toggled(self) -> Self {
+        match self {
+            Self::Lf => Self::CrLf,
+            Self::CrLf => Self::Lf,
+        }
+    }
+}
+
+impl Display for LineEnding {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Lf => "LF",
+            Self::CrLf => "CRLF",
+        };
+        write!(formatter, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_picks_the_majority_line_ending() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\n"), (LineEnding::CrLf, false));
+        assert_eq!(LineEnding::detect("a\nb\n"), (LineEnding::Lf, false));
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), (LineEnding::CrLf, true));
+        assert_eq!(LineEnding::detect("a\nb\nc\r\n"), (LineEnding::Lf, true));
+        // 数量相同时以第一个换行符为准
+        assert_eq!(LineEnding::detect("a\r\nb\n"), (LineEnding::CrLf, true));
+        assert_eq!(LineEnding::detect("no newline"), (LineEnding::Lf, false));
+    }
+}
@@ -0,0 +1,991 @@
+// messages 集中管理所有面向用户的界面文字，启动时选择语言（默认中文）。
+// 新增语言时只需扩展 Language 并在每个函数中补充对应文本。
+
+use std::{
+    io::{self, ErrorKind},
+    path::Path,
+    sync::OnceLock,
+};
+
+use super::{
+    config::ConfigWarning,
+    keymap::KeyMapWarning,
+    shellfilter::FilterError,
+    uicomponents::{LineNumbers, SearchDirection},
+    GraphemeWidth, LineEnding, Mode,
+};
+
+/// 界面语言
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum Language {
+    #[default]
+    Chinese,
+    English,
+}
+
+impl Language {
+    /// 通过语言代码（如 zh、en）解析语言
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.to_ascii_lowercase().as_str() {
+            "zh" | "zh-cn" | "cn" => Some(Self::Chinese),
+            "en" | "en-us" => Some(Self::English),
+            _ => None,
+        }
+    }
+}
+
+static LANGUAGE: OnceLock<Language> = OnceLock::new();
+
+/// 设置界面语言，只在启动时生效一次
+pub fn set_language(language: Language) {
+    let _ = LANGUAGE.set(language);
+}
+
+/// 当前界面语言
+fn language() -> Language {
+    LANGUAGE.get().copied().unwrap_or_default()
+}
+
+/// 按当前语言在固定文本中选择
+fn pick(chinese: &'static str, english: &'static str) -> &'static str {
+    match language() {
+        Language::Chinese => chinese,
+        Language::English => english,
+    }
+}
+
+// 启动与退出
+
+/// 启动时的快捷键提示
+pub fn help() -> &'static str {
+    pick(
+        "Ctrl + S = 保存 | Ctrl + Q = 退出",
+        "Ctrl + S = save | Ctrl + Q = quit",
+    )
+}
+
+/// 退出后的告别语
+pub fn farewell() -> &'static str {
+    pick("欢迎下次使用。\r\n", "Goodbye.\r\n")
+}
+
+/// 未知的命令行选项
+pub fn unknown_option(name: &str) -> String {
+    match language() {
+        Language::Chinese => format!("未知选项: --{name}"),
+        Language::English => format!("Unknown option: --{name}"),
+    }
+}
+
+/// 命令行选项的值无效
+pub fn invalid_option_value(name: &str, value: &str) -> String {
+    match language() {
+        Language::Chinese => format!("无效的选项值: --{name}={value}"),
+        Language::English => format!("Invalid option value: --{name}={value}"),
+    }
+}
+
+/// 配置文件有问题，已改用默认设置
+pub fn config_warning(warning: &ConfigWarning) -> String {
+    match (language(), warning) {
+        #[cfg(feature = "config")]
+        (Language::Chinese, ConfigWarning::Unreadable(path, err)) => {
+            format!("无法读取配置文件 {}，已使用默认设置：{err}", path.display())
+        }
+        #[cfg(feature = "config")]
+        (Language::English, ConfigWarning::Unreadable(path, err)) => {
+            format!("Could not read {}, using defaults: {err}", path.display())
+        }
+        #[cfg(feature = "config")]
+        (Language::Chinese, ConfigWarning::Malformed(path, err)) => {
+            format!("配置文件 {} 有误，已使用默认设置：{err}", path.display())
+        }
+        #[cfg(feature = "config")]
+        (Language::English, ConfigWarning::Malformed(path, err)) => {
+            format!("Invalid config {}, using defaults: {err}", path.display())
+        }
+        #[cfg(not(feature = "config"))]
+        (Language::Chinese, ConfigWarning::Unsupported(path)) => format!(
+            "已忽略配置文件 {}（请以 --features config 构建）",
+            path.display()
+        ),
+        #[cfg(not(feature = "config"))]
+        (Language::English, ConfigWarning::Unsupported(path)) => format!(
+            "Ignored {} (build with --features config)",
+            path.display()
+        ),
+    }
+}
+
+/// 按键配置文件有问题时的提示
+pub fn key_map_warning(warning: &KeyMapWarning) -> String {
+    match (language(), warning) {
+        (Language::Chinese, KeyMapWarning::Unreadable(path, err)) => {
+            format!("无法读取按键配置 {}，已使用默认按键：{err}", path.display())
+        }
+        (Language::English, KeyMapWarning::Unreadable(path, err)) => {
+            format!("Could not read {}, using default keys: {err}", path.display())
+        }
+        (Language::Chinese, KeyMapWarning::Malformed(path, line_number)) => format!(
+            "按键配置 {} 第 {line_number} 行格式有误，已使用默认按键",
+            path.display()
+        ),
+        (Language::English, KeyMapWarning::Malformed(path, line_number)) => format!(
+            "Invalid line {line_number} in {}, using default keys",
+            path.display()
+        ),
+        (Language::Chinese, KeyMapWarning::Ignored(path, lines)) => format!(
+            "已忽略按键配置 {} 中无法识别的绑定：{}",
+            path.display(),
+            lines.join("；")
+        ),
+        (Language::English, KeyMapWarning::Ignored(path, lines)) => format!(
+            "Ignored unknown bindings in {}: {}",
+            path.display(),
+            lines.join("; ")
+        ),
+    }
+}
+
+/// 无法打开调试日志文件
+pub fn log_open_failed(path: &str, err: &io::Error) -> String {
+    match language() {
+        Language::Chinese => format!("无法打开日志文件 {path}：{err}"),
+        Language::English => format!("Could not open log file {path}: {err}"),
+    }
+}
+
+/// 读取终端事件失败（仅发布模式下提示，调试模式直接 panic）
+#[cfg(not(debug_assertions))]
+pub fn read_event_failed() -> &'static str {
+    pick("读取事件时发生错误，请重试。", "Failed to read input, please try again.")
+}
+
+// 文件操作
+
+/// 无法打开文件，按错误类型给出具体原因
+pub fn open_failed(file_name: &str, err: &io::Error) -> String {
+    let reason = match err.kind() {
+        ErrorKind::PermissionDenied => pick("没有读取权限", "permission denied"),
+        ErrorKind::InvalidData => pick("二进制文件", "binary file"),
+        ErrorKind::IsADirectory => pick("目标是一个目录", "target is a directory"),
+        _ => pick("读取失败", "read error"),
+    };
+    match language() {
+        Language::Chinese => format!("ERROR: 无法打开文件: {file_name}（{reason}）"),
+        Language::English => format!("ERROR: Could not open file: {file_name} ({reason})"),
+    }
+}
+
+/// 打开的文件不存在，作为新文件编辑
+pub fn new_file(file_name: &str) -> String {
+    match language() {
+        Language::Chinese => format!("新文件：{file_name}（保存时创建）"),
+        Language::English => format!("New file: {file_name} (created on save)"),
+    }
+}
+
+/// 保存成功
+pub fn save_succeeded() -> &'static str {
+    pick("文件保存成功！", "File saved.")
+}
+
+/// 保存失败，按错误类型给出具体原因
+pub fn save_failed(err: &io::Error) -> String {
+    let reason = match err.kind() {
+        ErrorKind::PermissionDenied => Some(pick("没有写入权限", "permission denied")),
+        ErrorKind::ReadOnlyFilesystem => Some(pick("文件系统为只读", "read-only file system")),
+        ErrorKind::NotFound => Some(pick("目录不存在", "directory does not exist")),
+        ErrorKind::StorageFull => Some(pick("磁盘空间不足", "no space left on device")),
+        ErrorKind::IsADirectory => Some(pick("目标是一个目录", "target is a directory")),
+        _ => None,
+    };
+    match (language(), reason) {
+        (Language::Chinese, Some(reason)) => format!("文件写入失败：{reason}"),
+        (Language::English, Some(reason)) => format!("Error writing file: {reason}"),
+        (Language::Chinese, None) => format!("文件写入失败！({err})"),
+        (Language::English, None) => format!("Error writing file! ({err})"),
+    }
+}
+
+/// 全部保存时某个文件保存失败
+pub fn save_file_failed(file_name: &str, err: &io::Error) -> String {
+    format!("{file_name}: {}", save_failed(err))
+}
+
+/// 全部保存完成
+pub fn saved_files(count: usize) -> String {
+    match language() {
+        Language::Chinese => format!("保存了 {count} 个文件"),
+        Language::English => format!("Saved {count} file(s)"),
+    }
+}
+
+/// 全部保存时有文件保存失败，汇总所有错误
+pub fn save_all_failed(saved: usize, failures: &[String]) -> String {
+    let failures = failures.join("; ");
+    match language() {
+        Language::Chinese => format!("保存了 {saved} 个文件，{failures}"),
+        Language::English => format!("Saved {saved} file(s); {failures}"),
+    }
+}
+
+/// 缩进混用了制表符和空格（line_idx 从 0 开始）
+pub fn mixed_indentation(line_idx: usize) -> String {
+    let line = line_idx.saturating_add(1);
+    match language() {
+        Language::Chinese => format!(
+            "第 {line} 行起缩进混用了制表符和空格（Alt-T 全部转为空格，Alt-Shift-T 转为制表符）"
+        ),
+        Language::English => format!(
+            "Tabs and spaces are mixed in indentation from line {line} (Alt-T: spaces, Alt-Shift-T: tabs)"
+        ),
+    }
+}
+
+/// 缩进没有混用制表符和空格
+pub fn indentation_consistent() -> &'static str {
+    pick("缩进没有混用制表符和空格", "Indentation does not mix tabs and spaces")
+}
+
+/// 打开了目录，列出其中的条目
+pub fn dir_listing(dir: &Path) -> String {
+    let dir = dir.display();
+    match language() {
+        Language::Chinese => format!("目录 {dir}：按回车打开光标所在行的条目"),
+        Language::English => format!("Directory {dir}: press Enter to open the entry on the caret line"),
+    }
+}
+
+/// 打开的目录为空
+pub fn dir_empty(dir: &Path) -> String {
+    let dir = dir.display();
+    match language() {
+        Language::Chinese => format!("目录 {dir} 为空"),
+        Language::English => format!("Directory {dir} is empty"),
+    }
+}
+
+/// 无法读取目录（如没有权限）
+pub fn dir_read_failed(dir: &Path, err: &io::Error) -> String {
+    let dir = dir.display();
+    match language() {
+        Language::Chinese => format!("ERROR: 无法列出目录 {dir}：{err}"),
+        Language::English => format!("ERROR: Could not list directory {dir}: {err}"),
+    }
+}
+
+/// 以只读方式打开了不可写的文件
+pub fn opened_read_only() -> &'static str {
+    pick(
+        "文件不可写，只读打开（Alt-R 允许编辑并强制写入）",
+        "File is not writable, opened read-only (Alt-R to edit and force write)",
+    )
+}
+
+/// 文件不是有效的 UTF-8，已按有损方式解码并只读打开
+pub fn opened_lossy() -> &'static str {
+    pick(
+        "文件不是有效的 UTF-8，无法解码的字节显示为 �，只读打开（Alt-R 允许编辑，保存时会替换原有的字节）",
+        "File is not valid UTF-8, undecodable bytes shown as �, opened read-only (Alt-R to edit; saving replaces the original bytes)",
+    )
+}
+
+/// 很大的文件按需读取，只读打开
+pub fn large_file_read_only() -> &'static str {
+    pick(
+        "文件很大，按需读取并只读打开",
+        "Large file, loaded on demand and opened read-only",
+    )
+}
+
+/// 按需读取的大文件不支持需要全文的功能
+pub fn large_file_unsupported() -> &'static str {
+    pick(
+        "大文件按需读取，不支持此功能",
+        "Not available for large files loaded on demand",
+    )
+}
+
+/// 只读文件拒绝保存
+pub fn save_read_only() -> &'static str {
+    pick(
+        "文件为只读，未保存（Alt-R 强制写入）",
+        "File is read-only, not saved (Alt-R to force write)",
+    )
+}
+
+/// 切换只读状态后的提示
+pub fn read_only_toggled(read_only: bool) -> &'static str {
+    if read_only {
+        pick("只读：开", "Read-only: on")
+    } else {
+        pick("只读：关，保存时将尝试写入文件", "Read-only: off, saving will try to write the file")
+    }
+}
+
+/// 只读时拒绝修改内容
+pub fn buffer_read_only() -> &'static str {
+    pick("缓冲区为只读（Alt-R 允许编辑）", "Buffer is read-only (Alt-R to allow editing)")
+}
+
+/// 保存已取消
+pub fn save_cancelled() -> &'static str {
+    pick("保存已取消。", "Save aborted.")
+}
+
+/// 另存为提示符
+pub fn save_as_prompt() -> &'static str {
+    pick("保存为（Esc 取消）: ", "Save as (Esc to cancel): ")
+}
+
+/// 插入文件的提示符
+pub fn insert_file_prompt() -> &'static str {
+    pick("插入文件（Esc 取消）: ", "Insert file (Esc to cancel): ")
+}
+
+/// 已将文件内容插入到光标处
+pub fn file_inserted(file_name: &str) -> String {
+    match language() {
+        Language::Chinese => format!("已插入 {file_name}"),
+        Language::English => format!("Inserted {file_name}"),
+    }
+}
+
+/// 读取要插入的文件失败
+pub fn insert_file_failed(file_name: &str, err: &io::Error) -> String {
+    let reason = match err.kind() {
+        ErrorKind::NotFound => pick("文件不存在", "file not found"),
+        ErrorKind::PermissionDenied => pick("没有读取权限", "permission denied"),
+        ErrorKind::InvalidData => pick("不是 UTF-8 文本", "not valid UTF-8"),
+        ErrorKind::IsADirectory => pick("目标是一个目录", "target is a directory"),
+        _ => pick("读取失败", "read error"),
+    };
+    match language() {
+        Language::Chinese => format!("无法插入 {file_name}：{reason}"),
+        Language::English => format!("Cannot insert {file_name}: {reason}"),
+    }
+}
+
+/// 输入过滤命令的提示符
+pub fn pipe_prompt() -> &'static str {
+    pick("通过命令过滤（Esc 取消）: ", "Pipe through command (Esc to cancel): ")
+}
+
+/// 过滤命令执行成功
+pub fn pipe_succeeded(command: &str) -> String {
+    match language() {
+        Language::Chinese => format!("已通过 `{command}` 过滤全文"),
+        Language::English => format!("Filtered buffer through `{command}`"),
+    }
+}
+
+/// 过滤命令执行失败，缓冲区未修改
+pub fn pipe_failed(err: &FilterError) -> String {
+    match (language(), err) {
+        (Language::Chinese, FilterError::Spawn(err)) => format!("无法执行命令：{err}"),
+        (Language::English, FilterError::Spawn(err)) => format!("Cannot run command: {err}"),
+        (Language::Chinese, FilterError::Failed(stderr)) => format!("命令执行失败：{stderr}"),
+        (Language::English, FilterError::Failed(stderr)) => format!("Command failed: {stderr}"),
+        (Language::Chinese, FilterError::InvalidOutput) => "命令输出不是 UTF-8 文本".to_string(),
+        (Language::English, FilterError::InvalidOutput) => {
+            "Command output is not valid UTF-8".to_string()
+        }
+    }
+}
+
+/// 搜索提示符，整词模式下显示 [w]，notice 为上一次搜索的提示（回绕、未找到等）
+pub fn search_prompt(whole_word: bool, notice: Option<&str>) -> String {
+    let mode = if whole_word { "[w]" } else { "" };
+    match (language(), notice) {
+        (Language::Chinese, None) => format!("搜索{mode}（↑↓ 上/下一个，Esc 取消）: "),
+        (Language::English, None) => format!("Search{mode} (↑↓ prev/next, Esc to cancel): "),
+        (Language::Chinese, Some(notice)) => format!("搜索{mode}（{notice}）: "),
+        (Language::English, Some(notice)) => format!("Search{mode} ({notice}): "),
+    }
+}
+
+/// 跳转到行的提示符，notice 为上一次输入的问题（如行号无效）
+pub fn go_to_line_prompt(notice: Option<&str>) -> String {
+    match (language(), notice) {
+        (Language::Chinese, None) => "跳转到行（Esc 取消）: ".to_string(),
+        (Language::English, None) => "Go to line (Esc to cancel): ".to_string(),
+        (Language::Chinese, Some(notice)) => format!("跳转到行（{notice}）: "),
+        (Language::English, Some(notice)) => format!("Go to line ({notice}): "),
+    }
+}
+
+/// 替换提示，notice 为输入无效时附加的说明
+pub fn replace_prompt(notice: Option<&str>) -> String {
+    match (language(), notice) {
+        (Language::Chinese, None) => "替换（Esc 取消）: ".to_string(),
+        (Language::English, None) => "Replace (Esc to cancel): ".to_string(),
+        (Language::Chinese, Some(notice)) => format!("替换（{notice}）: "),
+        (Language::English, Some(notice)) => format!("Replace ({notice}): "),
+    }
+}
+
+/// 要替换的内容为空
+pub fn empty_replace_query() -> &'static str {
+    pick("替换内容不能为空", "search term is empty")
+}
+
+/// 替换为提示
+pub fn replace_with_prompt(query: &str) -> String {
+    match language() {
+        Language::Chinese => format!("将 {query} 替换为（Esc 取消）: "),
+        Language::English => format!("Replace {query} with (Esc to cancel): "),
+    }
+}
+
+/// 逐个确认替换的提示
+pub fn confirm_replace_prompt() -> &'static str {
+    pick(
+        "替换？(y 替换 / n 跳过 / a 全部 / q 结束)",
+        "Replace? (y = yes / n = skip / a = all / q = quit)",
+    )
+}
+
+/// 替换结束后的提示
+pub fn replaced_count(count: usize) -> String {
+    match language() {
+        Language::Chinese => format!("已替换 {count} 处"),
+        Language::English if count == 1 => "Replaced 1 occurrence".to_string(),
+        Language::English => format!("Replaced {count} occurrences"),
+    }
+}
+
+/// 输入的不是有效的行号
+pub fn invalid_line_number() -> &'static str {
+    pick("行号无效", "invalid line number")
+}
+
+/// 行号超出文件末尾，停在了最后一行（line_idx 从 0 开始）
+pub fn line_number_clamped(line_idx: usize) -> String {
+    let line = line_idx.saturating_add(1);
+    match language() {
+        Language::Chinese => format!("文件只有 {line} 行，已跳到最后一行"),
+        Language::English => format!("File has only {line} lines; moved to the last line"),
+    }
+}
+
+/// 搜索越过文件末尾（或开头）后回绕
+pub fn search_wrapped(direction: SearchDirection) -> &'static str {
+    match direction {
+        SearchDirection::Forward => pick("已从顶部继续搜索", "Search wrapped to top"),
+        SearchDirection::Backward => pick("已从底部继续搜索", "Search wrapped to bottom"),
+    }
+}
+
+/// 未开启回绕时搜索到达文件末尾（或开头）
+pub fn search_reached_end(direction: SearchDirection) -> &'static str {
+    match direction {
+        SearchDirection::Forward => pick("已到文件末尾", "Reached end of file"),
+        SearchDirection::Backward => pick("已到文件开头", "Reached start of file"),
+    }
+}
+
+/// 当前匹配的序号和总匹配数，如 "3/17"，notice 为附加的提示（回绕等）
+pub fn search_match_count(ordinal: Option<usize>, total: usize, notice: Option<&str>) -> String {
+    let ordinal = ordinal.map_or_else(|| "?".to_string(), |ordinal| ordinal.to_string());
+    match (language(), notice) {
+        (_, None) => format!("{ordinal}/{total}"),
+        (Language::Chinese, Some(notice)) => format!("{ordinal}/{total}，{notice}"),
+        (Language::English, Some(notice)) => format!("{ordinal}/{total}, {notice}"),
+    }
+}
+
+/// 全文没有匹配
+pub fn search_not_found(query: &str) -> String {
+    match language() {
+        Language::Chinese => format!("未找到：{query}"),
+        Language::English => format!("Not found: {query}"),
+    }
+}
+
+/// 还没有可重复的搜索
+pub fn no_search_query() -> &'static str {
+    pick("还没有搜索过（Ctrl-F 搜索）", "No previous search (Ctrl-F to search)")
+}
+
+/// 切换整词搜索后的提示
+pub fn search_whole_word_toggled(enabled: bool) -> &'static str {
+    if enabled {
+        pick("整词搜索：开", "Whole-word search: on")
+    } else {
+        pick("整词搜索：关", "Whole-word search: off")
+    }
+}
+
+/// 切换搜索回绕后的提示
+pub fn search_wrap_toggled(enabled: bool) -> &'static str {
+    if enabled {
+        pick("搜索回绕：开", "Search wrap: on")
+    } else {
+        pick("搜索回绕：关", "Search wrap: off")
+    }
+}
+
+/// 设置书签的提示符
+pub fn set_mark_prompt() -> &'static str {
+    pick("设置书签（输入字母，Esc 取消）: ", "Set mark (letter, Esc to cancel): ")
+}
+
+/// 跳转书签的提示符
+pub fn jump_to_mark_prompt() -> &'static str {
+    pick("跳转到书签（输入字母，Esc 取消）: ", "Jump to mark (letter, Esc to cancel): ")
+}
+
+/// 已设置书签
+pub fn mark_set(name: char) -> String {
+    match language() {
+        Language::Chinese => format!("已设置书签 {name}"),
+        Language::English => format!("Mark {name} set"),
+    }
+}
+
+/// 已跳转到书签
+pub fn mark_jumped(name: char) -> String {
+    match language() {
+        Language::Chinese => format!("已跳转到书签 {name}"),
+        Language::English => format!("Jumped to mark {name}"),
+    }
+}
+
+/// 书签未设置
+pub fn mark_not_set(name: char) -> String {
+    match language() {
+        Language::Chinese => format!("书签 {name} 未设置"),
+        Language::English => format!("Mark {name} is not set"),
+    }
+}
+
+/// 没有可回退或前进的跳转记录
+pub fn no_jump_history() -> &'static str {
+    pick("没有更多跳转记录", "No more jump history")
+}
+
+/// 开始录制宏
+pub fn macro_recording_started() -> &'static str {
+    pick("开始录制宏（F6 停止）", "Recording macro (F6 to stop)")
+}
+
+/// 宏录制完成
+pub fn macro_recorded(count: usize) -> String {
+    match language() {
+        Language::Chinese => format!("宏录制完成，共 {count} 条命令（F7 回放）"),
+        Language::English => format!("Macro recorded, {count} commands (F7 to play)"),
+    }
+}
+
+/// 录制过程中不能回放宏
+pub fn macro_play_while_recording() -> &'static str {
+    pick("正在录制宏，请先按 F6 停止", "Recording a macro, press F6 to stop first")
+}
+
+/// 还没有录制宏
+pub fn no_macro() -> &'static str {
+    pick("还没有录制宏", "No macro recorded")
+}
+
+/// 没有可用的补全
+pub fn no_completions() -> &'static str {
+    pick("没有可补全的单词", "No completions")
+}
+
+/// 会话已保存
+#[cfg(feature = "session")]
+pub fn session_saved(path: &Path) -> String {
+    match language() {
+        Language::Chinese => format!("会话已保存到 {}", path.display()),
+        Language::English => format!("Session saved to {}", path.display()),
+    }
+}
+
+/// 保存会话失败
+#[cfg(feature = "session")]
+pub fn session_save_failed(err: &io::Error) -> String {
+    match language() {
+        Language::Chinese => format!("保存会话失败：{err}"),
+        Language::English => format!("Could not save session: {err}"),
+    }
+}
+
+/// 读取会话失败
+#[cfg(feature = "session")]
+pub fn session_restore_failed(err: &io::Error) -> String {
+    match language() {
+        Language::Chinese => format!("无法恢复会话：{err}"),
+        Language::English => format!("Could not restore session: {err}"),
+    }
+}
+
+/// 会话中的部分文件已不存在
+#[cfg(feature = "session")]
+pub fn session_files_missing(names: &[String]) -> String {
+    let names = names.join(", ");
+    match language() {
+        Language::Chinese => format!("会话中的文件已不存在，已跳过：{names}"),
+        Language::English => format!("Skipped missing session files: {names}"),
+    }
+}
+
+/// 显示或隐藏改动标记后的提示
+#[cfg(feature = "diff")]
+pub fn diff_marks(enabled: bool) -> &'static str {
+    if enabled {
+        pick(
+            "已与磁盘版本比较：+ 新增，~ 修改，- 删除（Alt-N / Alt-Shift-N 跳转）",
+            "Compared with disk: + added, ~ modified, - removed (Alt-N / Alt-Shift-N to jump)",
+        )
+    } else {
+        pick("已隐藏改动标记", "Change markers hidden")
+    }
+}
+
+/// 文件尚未保存过，没有磁盘版本可比较
+#[cfg(feature = "diff")]
+pub fn diff_needs_file() -> &'static str {
+    pick("文件尚未保存，没有可比较的磁盘版本", "File has not been saved, nothing to compare with")
+}
+
+/// 读取磁盘版本失败
+#[cfg(feature = "diff")]
+pub fn diff_failed(err: &io::Error) -> String {
+    match language() {
+        Language::Chinese => format!("无法读取磁盘上的文件：{err}"),
+        Language::English => format!("Could not read the file on disk: {err}"),
+    }
+}
+
+/// 未显示改动标记时跳转改动
+#[cfg(feature = "diff")]
+pub fn diff_marks_hidden() -> &'static str {
+    pick("未显示改动标记（F9 与磁盘版本比较）", "Change markers are off (F9 to compare with disk)")
+}
+
+/// 没有更多改动
+#[cfg(feature = "diff")]
+pub fn no_more_changes() -> &'static str {
+    pick("没有更多改动", "No more changes")
+}
+
+/// 未启用比较功能
+#[cfg(not(feature = "diff"))]
+pub fn diff_unavailable() -> &'static str {
+    pick(
+        "未启用比较功能（请以 --features diff 构建）",
+        "Diff is not enabled (build with --features diff)",
+    )
+}
+
+/// 未启用会话功能
+#[cfg(not(feature = "session"))]
+pub fn session_unavailable() -> &'static str {
+    pick(
+        "未启用会话功能（请以 --features session 构建）",
+        "Sessions are not enabled (build with --features session)",
+    )
+}
+
+/// 最近文件列表的标题
+pub fn recent_files_header() -> &'static str {
+    pick("最近文件", "Recent files")
+}
+
+/// 最近文件列表为空
+pub fn no_recent_files() -> &'static str {
+    pick("没有最近文件", "No recent files")
+}
+
+/// 选择最近文件的提示符
+pub fn open_recent_prompt() -> &'static str {
+    pick("打开最近文件（输入序号，Esc 取消）: ", "Open recent file (number, Esc to cancel): ")
+}
+
+/// 最近文件序号无效
+pub fn invalid_recent_file_number() -> &'static str {
+    pick("无效的序号", "Invalid number")
+}
+
+/// 输入要打开的文件名的提示符
+pub fn open_prompt() -> &'static str {
+    pick("打开文件（Esc 取消）: ", "Open file (Esc to cancel): ")
+}
+
+/// 切换到了另一个打开的文件（idx 从 0 开始）
+pub fn buffer_switched(idx: usize, count: usize, file_name: &str) -> String {
+    let number = idx.saturating_add(1);
+    match language() {
+        Language::Chinese => format!("文件 {number}/{count}：{file_name}"),
+        Language::English => format!("File {number}/{count}: {file_name}"),
+    }
+}
+
+/// 只打开了一个文件，无法切换
+pub fn only_one_buffer() -> &'static str {
+    pick(
+        "只打开了一个文件（Ctrl-O 打开更多文件）",
+        "Only one file is open (Ctrl-O opens another)",
+    )
+}
+
+/// 后台的未命名文件无法在全部保存时保存
+pub fn unnamed_buffer_not_saved() -> &'static str {
+    pick("未命名的文件：请切换过去后保存", "unnamed file: switch to it and save")
+}
+
+/// 另存为目标已存在时的覆盖确认提示符
+pub fn confirm_overwrite_prompt(file_name: &str) -> String {
+    match language() {
+        Language::Chinese => format!("文件 {file_name} 已存在，覆盖？(y/n): "),
+        Language::English => format!("{file_name} already exists. Overwrite? (y/n): "),
+    }
+}
+
+/// 另存为的目标目录不存在时的确认提示
+pub fn confirm_create_dir_prompt(dir: &str) -> String {
+    match language() {
+        Language::Chinese => format!("目录 {dir} 不存在，创建？(y/n): "),
+        Language::English => format!("Directory {dir} does not exist. Create it? (y/n): "),
+    }
+}
+
+/// 有未保存更改时的退出警告，modified_files 为有未保存更改的文件数
+pub fn unsaved_quit_warning(remaining: u8, modified_files: usize) -> String {
+    match (language(), modified_files) {
+        (Language::Chinese, 0..=1) => {
+            format!("WARNING! 文件有未保存的更改。再按 Ctrl-Q {remaining} 次以退出（Alt-Q 立即退出）。")
+        }
+        (Language::Chinese, _) => {
+            format!("WARNING! {modified_files} 个文件有未保存的更改。再按 Ctrl-Q {remaining} 次以退出（Alt-Q 立即退出）。")
+        }
+        (Language::English, 0..=1) => {
+            format!("WARNING! File has unsaved changes. Press Ctrl-Q {remaining} more times to quit (Alt-Q quits now).")
+        }
+        (Language::English, _) => {
+            format!("WARNING! {modified_files} files have unsaved changes. Press Ctrl-Q {remaining} more times to quit (Alt-Q quits now).")
+        }
+    }
+}
+
+/// 正在输入的重复次数
+pub fn repeat_count(count: usize) -> String {
+    match language() {
+        Language::Chinese => format!("重复 {count} 次"),
+        Language::English => format!("Repeat {count} times"),
+    }
+}
+
+/// 还没有可重复的编辑
+pub fn nothing_to_repeat() -> &'static str {
+    pick("没有可重复的编辑", "Nothing to repeat")
+}
+
+/// 没有可撤销的修改
+pub fn nothing_to_undo() -> &'static str {
+    pick("没有可撤销的修改", "Nothing to undo")
+}
+
+/// 没有可重做的修改
+pub fn nothing_to_redo() -> &'static str {
+    pick("没有可重做的修改", "Nothing to redo")
+}
+
+/// 复制或剪切完成
+pub fn copied(line_count: usize, cut: bool) -> String {
+    match (language(), cut) {
+        (Language::Chinese, false) => format!("已复制 {line_count} 行"),
+        (Language::Chinese, true) => format!("已剪切 {line_count} 行"),
+        (Language::English, false) => format!("Copied {line_count} line(s)"),
+        (Language::English, true) => format!("Cut {line_count} line(s)"),
+    }
+}
+
+/// 无法写入系统剪贴板，文本只保存在编辑器内部
+pub fn system_clipboard_unavailable() -> &'static str {
+    pick(
+        "系统剪贴板不可用（需要 wl-copy、xclip 或 xsel），只能在编辑器内粘贴",
+        "System clipboard unavailable (needs wl-copy, xclip or xsel); text can only be pasted in the editor",
+    )
+}
+
+/// 没有可复制的内容
+pub fn nothing_to_copy() -> &'static str {
+    pick("没有可复制的内容", "Nothing to copy")
+}
+
+/// 剪贴板为空
+pub fn clipboard_empty() -> &'static str {
+    pick("剪贴板为空", "Clipboard is empty")
+}
+
+// 显示设置
+
+/// 切换空白字符显示后的提示
+pub fn show_whitespace(enabled: bool) -> &'static str {
+    if enabled {
+        pick("显示空白字符：开", "Show whitespace: on")
+    } else {
+        pick("显示空白字符：关", "Show whitespace: off")
+    }
+}
+
+/// 切换行号显示方式后的提示
+pub fn line_numbers(line_numbers: LineNumbers) -> &'static str {
+    match line_numbers {
+        LineNumbers::Off => pick("行号：关", "Line numbers: off"),
+        LineNumbers::Absolute => pick("行号：绝对", "Line numbers: absolute"),
+        LineNumbers::Relative => pick("行号：相对", "Line numbers: relative"),
+    }
+}
+
+/// 打开的文件混用了 LF 和 CRLF，保存时统一为出现较多的一种
+pub fn mixed_line_endings(line_ending: LineEnding) -> String {
+    match language() {
+        Language::Chinese => format!("文件混用了 LF 和 CRLF，保存时统一为 {line_ending}"),
+        Language::English => format!("File mixes LF and CRLF line endings; saving will use {line_ending}"),
+    }
+}
+
+/// 切换换行符后的提示
+pub fn line_ending_changed(line_ending: LineEnding) -> String {
+    match language() {
+        Language::Chinese => format!("换行符已改为 {line_ending}，保存后生效"),
+        Language::English => format!("Line ending changed to {line_ending}, takes effect on save"),
+    }
+}
+
+/// 进入或退出块选择模式后的提示
+pub fn block_selection(enabled: bool) -> &'static str {
+    if enabled {
+        pick(
+            "块选择：移动光标选择矩形，输入的字符插入到每一行（Alt-B 或 Esc 退出）",
+            "Block selection: move to select, typing inserts on every row (Alt-B or Esc to exit)",
+        )
+    } else {
+        pick("块选择：关", "Block selection: off")
+    }
+}
+
+/// 切换自动缩进后的提示
+pub fn auto_indent(enabled: bool) -> &'static str {
+    if enabled {
+        pick("自动缩进：开", "Auto-indent: on")
+    } else {
+        pick("自动缩进：关", "Auto-indent: off")
+    }
+}
+
+/// 切换行尾空白高亮后的提示
+pub fn highlight_trailing_whitespace(enabled: bool) -> &'static str {
+    if enabled {
+        pick("高亮行尾空白：开", "Highlight trailing whitespace: on")
+    } else {
+        pick("高亮行尾空白：关", "Highlight trailing whitespace: off")
+    }
+}
+
+// 字符信息
+
+/// 光标处字素的码位与宽度，如 “'é' U+0065 U+0301 半宽”
+pub fn grapheme_info(grapheme: &str, width: GraphemeWidth) -> String {
+    let codepoints: Vec<String> = grapheme
+        .chars()
+        .map(|character| format!("U+{:04X}", u32::from(character)))
+        .collect();
+    let codepoints = codepoints.join(" ");
+    let width = match width {
+        GraphemeWidth::Half => pick("半宽", "Half"),
+        GraphemeWidth::Full => pick("全宽", "Full"),
+    };
+    let grapheme = grapheme.escape_debug();
+    match language() {
+        Language::Chinese => format!("'{grapheme}' {codepoints} 宽度: {width}"),
+        Language::English => format!("'{grapheme}' {codepoints} width: {width}"),
+    }
+}
+
+/// 全文的词数和字符数
+pub fn word_count(words: usize, chars: usize) -> String {
+    match language() {
+        Language::Chinese => format!("{words} 个词，{chars} 个字符"),
+        Language::English => format!("{words} words, {chars} characters"),
+    }
+}
+
+/// 光标位于行尾，没有字符可显示
+pub fn no_grapheme_at_caret() -> &'static str {
+    pick("光标处没有字符", "No character under the cursor")
+}
+
+/// 找不到匹配的括号
+pub fn no_matching_bracket() -> &'static str {
+    pick("无匹配括号", "No matching bracket")
+}
+
+// 界面元素
+
+/// 消息记录浮层的标题
+pub fn message_log_header() -> &'static str {
+    pick("最近消息（按任意键关闭）", "Recent messages (press any key to close)")
+}
+
+/// 未命名文件的显示名
+pub fn unnamed_file() -> &'static str {
+    pick("[default]", "[No Name]")
+}
+
+/// 无法识别类型的文件
+pub fn plain_text() -> &'static str {
+    pick("纯文本", "Plain Text")
+}
+
+/// 状态栏的修改标记
+pub fn modified_indicator() -> &'static str {
+    pick("(modified)", "(modified)")
+}
+
+/// 状态栏的宏录制指示
+pub fn recording_indicator() -> &'static str {
+    pick("录制中 ", "REC ")
+}
+
+/// 状态栏的模式指示
+pub fn mode_indicator(mode: Mode) -> &'static str {
+    match mode {
+        Mode::Normal => pick("普通 ", "NORMAL "),
+        Mode::Insert => pick("插入 ", "INSERT "),
+    }
+}
+
+/// 状态栏中间的全文统计，词数和字节数可以省略
+pub fn text_counts(words: Option<usize>, chars: usize, bytes: Option<usize>) -> String {
+    let (words_unit, chars_unit, bytes_unit) = match language() {
+        Language::Chinese => (" 词", " 字符", " 字节"),
+        Language::English => (" words", " chars", " bytes"),
+    };
+    words
+        .map(|words| format!("{words}{words_unit}"))
+        .into_iter()
+        .chain(Some(format!("{chars}{chars_unit}")))
+        .chain(bytes.map(|bytes| format!("{bytes}{bytes_unit}")))
+        .collect::<Vec<_>>()
+        .join(pick("，", ", "))
+}
+
+/// 按需读取的大文件只在状态栏显示字节数
+pub fn byte_count(bytes: usize) -> String {
+    match language() {
+        Language::Chinese => format!("{bytes} 字节"),
+        Language::English => format!("{bytes} bytes"),
+    }
+}
+
+/// 状态栏的总行数
+pub fn line_count(total_lines: usize) -> String {
+    match language() {
+        Language::Chinese => format!("{total_lines} lines"),
+        Language::English => format!("{total_lines} lines"),
+    }
+}
@@ -5,40 +5,137 @@
 //   - 状态栏、消息栏、命令栏的统一管理
 //   - 编辑区的渲染与状态刷新
 
-use crossterm::event::{read, Event, KeyEvent, KeyEventKind};
+use crossterm::{
+    cursor::SetCursorStyle,
+    event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+};
 use std::{
+    cmp::min,
     env,
+    fs::{create_dir_all, read_to_string},
     io::Error,
     panic::{set_hook, take_hook},
+    path::Path,
+    time::{Duration, Instant},
 };
 use crate::prelude::*;
 
-mod command;
+pub mod command;
 use command::{
-    Command::{self, Edit, Move, System},
-    Edit::InsertNewline,
-    Move::{Down, Left, Right, Up},
-    System::{Dismiss, Quit, Resize, Save},
+    Command::{self, Edit, Move, Select, System},
+    Edit::{self as EditCommand, CompleteWord, Delete, DeleteLine, Insert, InsertNewline, Redo, Undo},
+    Move::{Down, EndOfLine, JumpBack, JumpForward, Left, MatchingBracket, Right, StartOfLine, Up},
+    System::{
+        CountDigit, Dismiss, ForceQuit, InsertFile, JumpToMark, PipeThroughCommand, PlayMacro, Quit, Redraw, RepeatLastEdit, Resize, Save, SaveAll, SaveSession, Search,
+        SearchNext, SearchPrevious, SetMark, ShowCharInfo, ShowMessageLog, ShowRecentFiles, ShowWordCount,
+        ToggleAutoIndent, ToggleBlockSelection, ToggleLineEnding, ToggleMacroRecording, ToggleReadOnly, ToggleSearchWrap,
+        ToggleTrailingWhitespace, ToggleWhitespace, ToggleWholeWordSearch, ToggleDiffMarks, NextChange,
+        PreviousChange, CheckIndentation, CycleLineNumbers, Copy, Cut, Paste, GoToLine, Open, Replace, NextBuffer, PreviousBuffer,
+    },
 };
 
 mod line;
-use line::Line;
+pub use line::Line;
+use line::GraphemeWidth;
+
+mod annotatedstring;
+use annotatedstring::{AnnotatedString, AnnotationType};
 
 mod terminal;
-use terminal::Terminal;
+use terminal::{Screen, Terminal};
 
 mod uicomponents;
-use uicomponents::{View, CommandBar, MessageBar, StatusBar, UIComponent};
+pub use uicomponents::{Buffer, LineRef};
+use uicomponents::{View, CommandBar, MessageBar, SearchDirection, SearchOutcome, StatusBar, UIComponent};
 
 mod documentstatus;
 use documentstatus::DocumentStatus;
 
-const QUIT_TIMES: u8 = 3;
+mod filetype;
+use filetype::FileType;
+
+mod syntax;
+
+mod lineending;
+use lineending::LineEnding;
+
+mod mode;
+use mode::Mode;
+
+mod args;
+use args::{ArgWarning, Args};
+
+mod messages;
+
+mod timestamp;
+
+mod debuglog;
+
+mod shellfilter;
+
+#[cfg(feature = "session")]
+mod session;
+#[cfg(feature = "session")]
+use session::{Session, SessionFile};
+#[cfg(feature = "session")]
+use std::path::PathBuf;
+
+mod dirlisting;
+use dirlisting::DirListing;
+
+mod clipboard;
+use clipboard::Clipboard;
+
+mod configdir;
+
+mod config;
+use config::Config;
+
+mod recentfiles;
+use recentfiles::RecentFiles;
 
-/// 编辑器提示类型（仅支持保存提示）
+mod cursorpositions;
+use cursorpositions::CursorPositions;
+
+mod replacesession;
+use replacesession::ReplaceSession;
+
+mod bufferlist;
+use bufferlist::{BufferList, BufferSlot};
+
+mod keymap;
+use keymap::KeyMap;
+
+mod document;
+pub use document::Document;
+
+#[cfg(test)]
+mod tempfile;
+
+/// 重复次数的上限，避免误输入过大的数字导致长时间卡顿
+const MAX_REPEAT_COUNT: usize = 9999;
+/// 连续多少次按键间隔都很短时视为粘贴
+const PASTE_GUARD_STREAK: usize = 2;
+/// 设置为非空值时不输出颜色的环境变量（见 no-color.org）
+const NO_COLOR_VAR: &str = "NO_COLOR";
+
+/// 编辑器提示类型
 #[derive(Eq, PartialEq, Default)]
 enum PromptType {
     Save,
+    ConfirmOverwrite, // 另存为的目标文件已存在，等待确认
+    ConfirmCreateDir, // 另存为的目标目录不存在，等待确认创建
+    OpenRecent,       // 输入最近文件的序号
+    Open,             // 输入要打开的文件名
+    InsertFile,       // 输入要插入到光标处的文件名
+    PipeCommand,      // 输入用于过滤全文的 shell 命令
+    SetMark,          // 输入要设置的书签字母
+    JumpToMark,       // 输入要跳转的书签字母
+    Search,           // 输入搜索内容，边输入边跳转
+    GoToLine,         // 输入要跳转到的行号
+    ReplaceSearch,    // 输入要替换的内容
+    ReplaceWith,      // 输入替换后的内容
+    ConfirmReplace,   // 逐个确认是否替换当前匹配
     #[default]
     None,
 }
@@ -46,7 +143,23 @@ enum PromptType {
 impl PromptType {
     /// 判断当前是否为提示模式
     fn is_prompt(&self) -> bool {
-        matches!(self, Self::Save)
+        matches!(
+            self,
+            Self::Save
+                | Self::ConfirmOverwrite
+                | Self::ConfirmCreateDir
+                | Self::OpenRecent
+                | Self::Open
+                | Self::InsertFile
+                | Self::PipeCommand
+                | Self::SetMark
+                | Self::JumpToMark
+                | Self::Search
+                | Self::GoToLine
+                | Self::ReplaceSearch
+                | Self::ReplaceWith
+                | Self::ConfirmReplace
+        )
     }
 }
 
@@ -62,6 +175,32 @@ pub struct Editor {
     terminal_size: Size,    // 终端尺寸
     title: String,          // 终端标题
     quit_times: u8,         // 退出确认计数
+    required_quit_times: u8, // 有未保存更改时退出所需的按键次数
+    overlay: Option<(String, Vec<String>)>, // 覆盖在编辑区底部的临时列表（标题、各行）
+    pending_save_as: Option<String>, // 等待覆盖确认的另存为文件名
+    recent_files: RecentFiles, // 最近打开或保存的文件
+    cursor_positions: CursorPositions, // 各文件上次的光标位置
+    pending_count: Option<usize>, // 已输入、等待应用到下一条命令的重复次数
+    last_edit: Vec<EditCommand>, // 上一次编辑：连续输入的一段文字，或单条其他编辑命令
+    in_insert_run: bool, // 上一条命令是否为连续输入中的字符或换行
+    recording_macro: Option<Vec<Command>>, // 正在录制的宏，None 表示未在录制
+    recorded_macro: Vec<Command>, // 上一次录制完成的宏
+    session_file: Option<String>, // 启动时指定的会话文件，保存会话时写回此处
+    search_query: String, // 上一次搜索的内容
+    search_origin: Option<(Location, Position)>, // 进入搜索提示前的光标位置和滚动偏移
+    mode: Option<Mode>, // 模态编辑的当前模式，None 表示未启用模态编辑
+    pending_delete: bool, // 普通模式下已按下一次 d，等待第二个 d 删除整行
+    read_only_mode: bool, // 以 --readonly 启动，打开的文件一律只读
+    headless: bool, // 不连接终端（脚本和测试使用），退出时不清理终端
+    screen: Box<dyn Screen>, // 界面输出的目标，不连接终端时为什么也不输出的 NullTerminal
+    dir_listing: Option<DirListing>, // 编辑区正在列出的目录，None 表示编辑区显示的是文件
+    paste_guard_threshold: Duration, // 按键间隔短于此值视为粘贴，为 0 时不检测
+    last_key_time: Option<Instant>, // 上一次按键的时间
+    fast_key_streak: usize, // 连续间隔很短的按键次数
+    clipboard: Clipboard, // 复制、剪切的文本
+    replace_session: Option<ReplaceSession>, // 正在逐个确认的替换
+    buffer_list: BufferList, // 打开的所有文件，当前文件的视图和目录列表即 view 和 dir_listing
+    key_map: KeyMap, // 用户在 keys.toml 中自定义的按键绑定
 }
 
 impl Editor {
@@ -80,18 +219,77 @@ impl Editor {
         // 初始化终端
         Terminal::initialize()?;
 
+        let args = Args::parse(env::args().skip(1));
+        messages::set_language(args.language.unwrap_or_default());
+        let (mut config, config_warning) = Config::load();
+        let (key_map, key_map_warning) = KeyMap::load();
+        config.theme = args.theme.unwrap_or(config.theme);
+        config.line_numbers = args.line_numbers.unwrap_or(config.line_numbers);
+        config.overflow_markers = args.overflow_markers.unwrap_or(config.overflow_markers);
+        Terminal::set_theme(config.theme);
+        Terminal::set_plain(args.no_color || env::var_os(NO_COLOR_VAR).is_some_and(|value| !value.is_empty()));
+
         let mut editor = Self::default();
+        editor.screen = Box::new(Terminal);
         let size = Terminal::size().unwrap_or_default();
         editor.handle_resize_command(size);
-        editor.update_message("Ctrl + S = 保存 | Ctrl + Q = 退出");
 
-        let args: Vec<String> = env::args().collect();
-        if let Some(file_name) = args.get(1) {
-            debug_assert!(!file_name.is_empty());
-            if editor.view.load(file_name).is_err() {
-                editor.update_message(&format!("ERROR: 无法打开文件: {file_name}"));
+        editor.key_map = key_map;
+        editor.recent_files = RecentFiles::load();
+        editor.cursor_positions = CursorPositions::load();
+        editor.required_quit_times = args.quit_times.unwrap_or(config.quit_times);
+        editor.paste_guard_threshold = Duration::from_millis(config.paste_guard_ms);
+        editor.view.set_config(&config);
+        editor.command_bar.set_tab_width(config.tab_width);
+        editor
+            .status_bar
+            .set_templates(config.status_left.clone(), config.status_right.clone());
+        editor.view.set_ruler_column(args.ruler_column);
+        editor.view.set_line_length_limit(args.line_length_limit);
+        editor.view.set_search_wrap(args.search_wrap.unwrap_or(true));
+        if args.modal {
+            editor.mode = Some(Mode::Normal);
+        }
+        if args.read_only {
+            editor.read_only_mode = true;
+            editor.view.set_read_only(true);
+        }
+        if let Some(duration) = args.message_duration {
+            editor.message_bar.set_default_duration(duration);
+        }
+        editor.update_message(messages::help());
+        if let Some(warning) = &config_warning {
+            editor.update_message(&messages::config_warning(warning));
+        }
+        if let Some(warning) = &key_map_warning {
+            editor.update_message(&messages::key_map_warning(warning));
+        }
+        let log_file = args
+            .log_file
+            .clone()
+            .or_else(|| env::var(debuglog::ENV_VAR).ok().filter(|path| !path.is_empty()));
+        if let Some(log_file) = log_file {
+            if let Err(err) = debuglog::init(Path::new(&log_file)) {
+                editor.update_message(&messages::log_open_failed(&log_file, &err));
             }
         }
+        if let Some(warning) = args.warnings.first() {
+            let text = match warning {
+                ArgWarning::UnknownOption(name) => messages::unknown_option(name),
+                ArgWarning::InvalidValue(name, value) => {
+                    messages::invalid_option_value(name, value)
+                }
+            };
+            editor.update_message(&text);
+        }
+
+        if let Some(file_name) = &args.file_name {
+            debug_assert!(!file_name.is_empty());
+            editor.load_file(file_name);
+        } else if let Some(session_file) = &args.session {
+            editor.session_file = Some(session_file.clone()).filter(|file| !file.is_empty());
+            editor.restore_session();
+        }
         editor.refresh_status();
         Ok(editor)
     }
@@ -106,6 +304,7 @@ impl Editor {
             match read() {
                 Ok(event) => self.evaluate_event(event),
                 Err(err) => {
+                    debuglog::log(|| format!("could not read event: {err:?}"));
                     #[cfg(debug_assertions)]
                     {
                         panic!("Could not read event: {err:?}");
@@ -113,7 +312,7 @@ impl Editor {
                     #[cfg(not(debug_assertions))]
                     {
                         // 错误提示
-                        self.update_message("读取事件时发生错误，请重试。");
+                        self.update_message(messages::read_event_failed());
                     }
                 }
             }
@@ -121,75 +320,232 @@ impl Editor {
         }
     }
 
-    /// 刷新整个屏幕，包括各 UI 组件
+    /// 刷新整个屏幕，包括各 UI 组件。
+    /// 终端太矮时依次舍弃状态栏和底部栏，尽量保留编辑区：
+    /// 高度为 2 时只显示编辑区和底部栏，高度为 1 时只显示编辑区（提示模式下显示命令栏）
     fn refresh_screen(&mut self) {
-        if self.terminal_size.height == 0 || self.terminal_size.width == 0 {
+        let Size { height, width } = self.terminal_size;
+        if height == 0 || width == 0 {
             return;
         }
-        let bottom_bar_row = self.terminal_size.height.saturating_sub(1);
-        let _ = Terminal::hide_caret();
-        if self.in_prompt() {
-            self.command_bar.render(bottom_bar_row);
-        } else {
-            self.message_bar.render(bottom_bar_row);
+        let bottom_bar_row = height.saturating_sub(1);
+        let _ = self.screen.hide_caret();
+        let show_view = height > 1 || !self.in_prompt();
+        let show_bottom_bar = height > 1 || self.in_prompt();
+        if height == 1 {
+            // 编辑区和命令栏共用唯一的一行，每次都重绘当前显示的那个
+            self.view.set_needs_redraw(true);
+            self.command_bar.set_needs_redraw(true);
         }
-        if self.terminal_size.height > 1 {
-            self.status_bar
-                .render(self.terminal_size.height.saturating_sub(2));
+        if show_bottom_bar {
+            if self.in_prompt() {
+                self.command_bar.render(self.screen.as_mut(), bottom_bar_row);
+            } else {
+                self.message_bar.render(self.screen.as_mut(), bottom_bar_row);
+            }
         }
-        if self.terminal_size.height > 2 {
-            self.view.render(0);
+        if height > 2 {
+            self.status_bar.render(self.screen.as_mut(), height.saturating_sub(2));
         }
-        let new_caret_pos = if self.in_prompt() {
-            Position {
-                row: bottom_bar_row,
-                col: self.command_bar.caret_position_col(),
+        if show_view {
+            self.view.render(self.screen.as_mut(), 0);
+            if self.overlay.is_some() && height > 2 {
+                self.render_overlay();
             }
+        }
+        let (new_caret_pos, caret_style) = if self.in_prompt() {
+            (
+                Position {
+                    row: bottom_bar_row,
+                    col: self.command_bar.caret_position_col(),
+                },
+                SetCursorStyle::SteadyBar,
+            )
         } else {
-            self.view.caret_position()
+            (self.view.caret_position(), SetCursorStyle::SteadyBlock)
         };
-        debug_assert!(new_caret_pos.col <= self.terminal_size.width);
-        debug_assert!(new_caret_pos.row <= self.terminal_size.height);
+        // 行号栏等占满整个宽度时光标可能落在屏幕之外，校正到最后一列
+        let new_caret_pos = Position {
+            row: min(new_caret_pos.row, bottom_bar_row),
+            col: min(new_caret_pos.col, width.saturating_sub(1)),
+        };
+
+        let _ = self.screen.place_caret(new_caret_pos, caret_style);
+        // 用滚轮把光标所在行滚出编辑区后不显示光标，移动光标时再滚回来
+        if self.in_prompt() || self.view.is_caret_visible() {
+            let _ = self.screen.show_caret();
+        }
+        let _ = self.screen.execute();
+    }
 
-        let _ = Terminal::move_caret_to(new_caret_pos);
-        let _ = Terminal::show_caret();
-        let _ = Terminal::execute();
+    /// 在编辑区底部覆盖显示临时列表（消息记录、最近文件等）
+    fn render_overlay(&mut self) {
+        let Some((header, lines)) = &self.overlay else {
+            return;
+        };
+        let view_height = self.terminal_size.height.saturating_sub(2);
+        let visible_count = min(lines.len(), view_height.saturating_sub(1));
+        let header_row = view_height.saturating_sub(visible_count.saturating_add(1));
+        let _ = self.screen.print_inverted_row(header_row, header);
+        for (offset, line) in lines.iter().take(visible_count).enumerate() {
+            let _ = self.screen.print_row(header_row.saturating_add(offset).saturating_add(1), line);
+        }
     }
 
     /// 刷新状态栏内容和终端标题
     fn refresh_status(&mut self) {
-        let status = self.view.get_status();
-        let title = format!("{} - {NAME}", status.file_name);
+        let mut status = self.view.get_status();
+        status.recording_macro = self.recording_macro.is_some();
+        status.mode = self.mode;
+        (status.total_words, status.total_chars, status.total_bytes) = self.view.text_counts();
+        status.buffer_idx = self.buffer_list.active_idx();
+        status.buffer_count = self.buffer_list.count();
+        let title = format!("{} - {NAME}", status.file_name_to_string());
         self.status_bar.update_status(status);
-        if title != self.title && matches!(Terminal::set_title(&title), Ok(())) {
+        if title != self.title && matches!(self.screen.set_title(&title), Ok(())) {
             self.title = title;
         }
     }
 
     /// 处理输入事件，分发命令
     fn evaluate_event(&mut self, event: Event) {
+        if let Event::Paste(text) = &event {
+            self.handle_paste(text);
+            return;
+        }
         let should_process = match &event {
             Event::Key(KeyEvent { kind, .. }) => kind == &KeyEventKind::Press,
-            Event::Resize(_, _) => true,
+            Event::Resize(_, _) | Event::Mouse(_) => true,
             _ => false,
         };
+        if let (true, Event::Key(_)) = (should_process, &event) {
+            self.track_input_speed();
+        }
+        // 模态编辑时先按当前模式解释按键，普通模式下未处理的按键再按通常的方式映射
+        if let (true, Event::Key(key_event)) = (should_process, &event) {
+            if self.handle_modal_key(*key_event) {
+                return;
+            }
+        }
 
         if should_process {
-            if let Ok(command) = Command::try_from(event) {
+            if let Ok(command) = self.key_map.translate(event) {
                 self.process_command(command);
             }
         }
     }
 
+    /// 终端不支持括号粘贴时，粘贴的内容会作为一串按键快速送达。
+    /// 连续多次按键间隔都很短时暂停自动缩进，避免缩进逐行叠加；输入停顿后恢复
+    fn track_input_speed(&mut self) {
+        let now = Instant::now();
+        let is_fast = self
+            .last_key_time
+            .is_some_and(|last| now.duration_since(last) < self.paste_guard_threshold);
+        self.last_key_time = Some(now);
+        self.fast_key_streak = if is_fast {
+            self.fast_key_streak.saturating_add(1)
+        } else {
+            0
+        };
+        self.view.set_paste_guard(self.fast_key_streak >= PASTE_GUARD_STREAK);
+    }
+
+    /// 按模态编辑的当前模式处理按键，已处理时返回 true；提示模式和浮层显示时不处理
+    fn handle_modal_key(&mut self, event: KeyEvent) -> bool {
+        if self.in_prompt() || self.overlay.is_some() {
+            return false;
+        }
+        match self.mode {
+            Some(Mode::Normal) => self.handle_normal_mode_key(event),
+            Some(Mode::Insert) => {
+                if event.code == KeyCode::Esc && event.modifiers == KeyModifiers::NONE {
+                    // Esc 回到普通模式，之后仍按取消命令处理（如退出块选择）
+                    self.mode = Some(Mode::Normal);
+                }
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// 普通模式下处理字母和数字键；其他按键（方向键、Ctrl 组合键等）返回 false，
+    /// 交给通常的命令映射。未定义的字母在普通模式下忽略
+    fn handle_normal_mode_key(&mut self, event: KeyEvent) -> bool {
+        let KeyEvent {
+            code: KeyCode::Char(character),
+            modifiers,
+            ..
+        } = event
+        else {
+            self.pending_delete = false;
+            return false;
+        };
+        if modifiers != KeyModifiers::NONE && modifiers != KeyModifiers::SHIFT {
+            self.pending_delete = false;
+            return false;
+        }
+        let pending_delete = std::mem::take(&mut self.pending_delete);
+        match character {
+            'h' => self.process_command(Move(Left)),
+            'j' => self.process_command(Move(Down)),
+            'k' => self.process_command(Move(Up)),
+            'l' => self.process_command(Move(Right)),
+            'i' => self.mode = Some(Mode::Insert),
+            'x' => self.process_command(Edit(Delete)),
+            'u' => self.process_command(Edit(Undo)),
+            'd' if pending_delete => self.process_command(Edit(DeleteLine)),
+            'd' => self.pending_delete = true,
+            'o' => {
+                self.process_command(Move(EndOfLine));
+                self.process_command(Edit(InsertNewline));
+                self.mode = Some(Mode::Insert);
+            }
+            '0' if self.pending_count.is_none() => self.process_command(Move(StartOfLine)),
+            digit @ '0'..='9' => self.process_command(System(CountDigit(digit as u8 - b'0'))),
+            _ => {}
+        }
+        true
+    }
+
     /// 处理命令分发，根据当前模式调用不同处理逻辑
     fn process_command(&mut self, command: Command) {
+        debuglog::log(|| format!("command: {command:?}"));
+        if let Some(recording) = &mut self.recording_macro {
+            if !matches!(
+                command,
+                System(Resize(_) | Redraw | ForceQuit | ToggleMacroRecording | PlayMacro)
+            ) {
+                recording.push(command);
+            }
+        }
         match command {
             System(Resize(size)) => self.handle_resize_command(size),
+            System(ForceQuit) => self.handle_force_quit_command(),
+            System(Redraw) => self.handle_redraw_command(),
+            _ if self.overlay.is_some() && !self.in_prompt() => self.hide_overlay(),
+            System(Paste) => self.handle_paste_command(),
             _ => match self.prompt_type {
                 PromptType::Save => self.process_command_during_save(command),
+                PromptType::ConfirmOverwrite => self.process_command_during_overwrite_confirm(command),
+                PromptType::ConfirmCreateDir => self.process_command_during_create_dir_confirm(command),
+                PromptType::OpenRecent => self.process_command_during_open_recent(command),
+                PromptType::Open => self.process_command_during_open(command),
+                PromptType::InsertFile => self.process_command_during_insert_file(command),
+                PromptType::PipeCommand => self.process_command_during_pipe(command),
+                PromptType::Search => self.process_command_during_search(command),
+                PromptType::GoToLine => self.process_command_during_go_to_line(command),
+                PromptType::ReplaceSearch => self.process_command_during_replace_search(command),
+                PromptType::ReplaceWith => self.process_command_during_replace_with(command),
+                PromptType::ConfirmReplace => self.process_command_during_replace_confirm(command),
+                PromptType::SetMark | PromptType::JumpToMark => {
+                    self.process_command_during_mark(command);
+                }
                 PromptType::None => self.process_command_no_prompt(command),
             }
         }
+        // 一条命令中的所有修改合为一个撤销步骤，连续输入的字符再合并为一步
+        self.view.finish_undo_step(matches!(command, Edit(Insert(_))));
     }
 
     /// 非提示模式下的命令处理
@@ -199,22 +555,703 @@ impl Editor {
             return;
         }
         self.reset_quit_times(); // 重置退出计数
+        if let System(CountDigit(digit)) = command {
+            self.handle_count_digit(digit);
+            return;
+        }
+        if self.dir_listing.is_some() {
+            if let Edit(edit_command) = command {
+                // 目录列表只读：回车打开所选条目，其他编辑命令忽略
+                if matches!(edit_command, InsertNewline) {
+                    self.open_dir_listing_entry();
+                }
+                return;
+            }
+        }
+        // 只读时拒绝修改内容的命令，移动和滚动照常
+        if self.view.is_read_only() && Self::modifies_buffer(command) {
+            self.update_message(messages::buffer_read_only());
+            return;
+        }
+        // 重复次数只作用于下一条命令，非编辑、移动命令直接丢弃
+        let count = match self.pending_count.take() {
+            Some(count) => {
+                self.message_bar.clear();
+                count.max(1)
+            }
+            None => 1,
+        };
 
         match command {
-            System(Quit | Resize(_) | Dismiss) => {}, // 退出和调整大小已经在上面处理，其他不适用
+            System(Quit | ForceQuit | Redraw | Resize(_) | CountDigit(_) | Paste) => {},
+            System(Dismiss) => {
+                self.view.clear_block_selection();
+                self.view.clear_selection();
+            }
+            System(ToggleBlockSelection) => {
+                let enabled = self.view.toggle_block_selection();
+                self.update_message(messages::block_selection(enabled));
+            } // 退出和调整大小已经在上面处理，其他不适用
             System(Save) => self.handle_save_command(),
-            Edit(edit_command) => self.view.handle_edit_command(edit_command),
-            Move(move_command) => self.view.handle_move_command(move_command),
+            System(SaveAll) => self.handle_save_all_command(),
+            System(ShowMessageLog) => self.show_message_log(),
+            System(ShowRecentFiles) => self.handle_recent_files_command(),
+            System(Open) => self.set_prompt(PromptType::Open),
+            System(NextBuffer) => self.handle_switch_buffer_command(true),
+            System(PreviousBuffer) => self.handle_switch_buffer_command(false),
+            System(ToggleWhitespace) => {
+                let enabled = self.view.toggle_show_whitespace();
+                self.update_message(messages::show_whitespace(enabled));
+            }
+            System(ShowCharInfo) => self.handle_char_info_command(),
+            System(InsertFile) => self.set_prompt(PromptType::InsertFile),
+            System(PipeThroughCommand) => self.set_prompt(PromptType::PipeCommand),
+            System(ToggleMacroRecording) => self.handle_macro_recording_command(),
+            System(PlayMacro) => self.handle_play_macro_command(count),
+            System(SaveSession) => self.handle_save_session_command(),
+            System(Search) => self.set_prompt(PromptType::Search),
+            System(GoToLine) => self.set_prompt(PromptType::GoToLine),
+            System(Replace) => self.set_prompt(PromptType::ReplaceSearch),
+            System(SearchNext) => self.search_next(SearchDirection::Forward),
+            System(SearchPrevious) => self.search_next(SearchDirection::Backward),
+            System(ToggleSearchWrap) => {
+                let enabled = self.view.toggle_search_wrap();
+                self.update_message(messages::search_wrap_toggled(enabled));
+            }
+            System(ToggleWholeWordSearch) => {
+                let enabled = self.view.toggle_search_whole_word();
+                self.update_message(messages::search_whole_word_toggled(enabled));
+            }
+            System(ToggleDiffMarks) => self.handle_diff_marks_command(),
+            System(NextChange) => self.handle_jump_to_change_command(SearchDirection::Forward),
+            System(PreviousChange) => self.handle_jump_to_change_command(SearchDirection::Backward),
+            System(CheckIndentation) if self.view.is_large_file() => {
+                self.update_message(messages::large_file_unsupported());
+            }
+            System(CheckIndentation) => match self.view.find_mixed_indentation() {
+                Some(line_idx) => self.update_message(&messages::mixed_indentation(line_idx)),
+                None => self.update_message(messages::indentation_consistent()),
+            },
+            System(CycleLineNumbers) => {
+                let line_numbers = self.view.cycle_line_numbers();
+                self.update_message(messages::line_numbers(line_numbers));
+            }
+            System(SetMark) => self.set_prompt(PromptType::SetMark),
+            System(JumpToMark) => self.set_prompt(PromptType::JumpToMark),
+            System(ToggleReadOnly) if self.view.is_large_file() => {
+                self.update_message(messages::large_file_read_only());
+            }
+            System(ToggleReadOnly) => {
+                let read_only = self.view.toggle_read_only();
+                self.update_message(messages::read_only_toggled(read_only));
+            }
+            System(ToggleAutoIndent) => {
+                let enabled = self.view.toggle_auto_indent();
+                self.update_message(messages::auto_indent(enabled));
+            }
+            System(ToggleLineEnding) => {
+                let line_ending = self.view.toggle_line_ending();
+                self.update_message(&messages::line_ending_changed(line_ending));
+            }
+            System(ToggleTrailingWhitespace) => {
+                let enabled = self.view.toggle_highlight_trailing_whitespace();
+                self.update_message(messages::highlight_trailing_whitespace(enabled));
+            }
+            System(ShowWordCount) if self.view.is_large_file() => {
+                self.update_message(messages::large_file_unsupported());
+            }
+            System(ShowWordCount) => {
+                let (words, chars, _) = self.view.text_counts();
+                self.update_message(&messages::word_count(words, chars));
+            }
+            System(RepeatLastEdit) => self.handle_repeat_last_edit_command(count),
+            System(Copy) => self.handle_copy_command(false),
+            System(Cut) => self.handle_copy_command(true),
+            Edit(CompleteWord) => {
+                if !self.view.complete_word() {
+                    self.update_message(messages::no_completions());
+                }
+                self.record_edit(CompleteWord);
+                return;
+            }
+            Edit(Undo) => {
+                for _ in 0..count {
+                    if !self.view.undo() {
+                        self.update_message(messages::nothing_to_undo());
+                        break;
+                    }
+                }
+            }
+            Edit(Redo) => {
+                for _ in 0..count {
+                    if !self.view.redo() {
+                        self.update_message(messages::nothing_to_redo());
+                        break;
+                    }
+                }
+            }
+            Edit(edit_command) => {
+                for _ in 0..count {
+                    self.view.handle_edit_command(edit_command);
+                    self.record_edit(edit_command);
+                }
+                return;
+            }
+            Move(MatchingBracket) => {
+                if !self.view.move_to_matching_bracket() {
+                    self.update_message(messages::no_matching_bracket());
+                }
+            }
+            Move(JumpBack) => {
+                for _ in 0..count {
+                    if !self.view.jump_back() {
+                        self.update_message(messages::no_jump_history());
+                        break;
+                    }
+                }
+            }
+            Move(JumpForward) => {
+                for _ in 0..count {
+                    if !self.view.jump_forward() {
+                        self.update_message(messages::no_jump_history());
+                        break;
+                    }
+                }
+            }
+            Move(move_command) => {
+                for _ in 0..count {
+                    self.view.handle_move_command(move_command);
+                }
+            }
+            Select(move_command) => {
+                for _ in 0..count {
+                    self.view.handle_select_command(move_command);
+                }
+            }
+        }
+        self.in_insert_run = false;
+    }
+
+    /// 开始录制宏，或停止录制并保存录制的命令
+    fn handle_macro_recording_command(&mut self) {
+        if let Some(recorded) = self.recording_macro.take() {
+            let count = recorded.len();
+            self.recorded_macro = recorded;
+            self.update_message(&messages::macro_recorded(count));
+        } else {
+            self.recording_macro = Some(Vec::new());
+            self.update_message(messages::macro_recording_started());
+        }
+    }
+
+    /// 将录制的宏回放 count 次，每条命令按原样重新处理
+    fn handle_play_macro_command(&mut self, count: usize) {
+        if self.recording_macro.is_some() {
+            self.update_message(messages::macro_play_while_recording());
+            return;
+        }
+        if self.recorded_macro.is_empty() {
+            self.update_message(messages::no_macro());
+            return;
+        }
+        let commands = self.recorded_macro.clone();
+        for _ in 0..count {
+            for &command in &commands {
+                self.process_command(command);
+            }
+            if self.should_quit {
+                break;
+            }
+        }
+    }
+
+    /// 记录上一次编辑：连续输入的字符和换行合并为一段，其他编辑命令单独记录
+    fn record_edit(&mut self, command: EditCommand) {
+        let is_insert = matches!(command, Insert(_) | InsertNewline);
+        if !(is_insert && self.in_insert_run) {
+            self.last_edit.clear();
+        }
+        self.last_edit.push(command);
+        self.in_insert_run = is_insert;
+    }
+
+    /// 在光标处重新执行上一次编辑
+    fn handle_repeat_last_edit_command(&mut self, count: usize) {
+        if self.last_edit.is_empty() {
+            self.update_message(messages::nothing_to_repeat());
+            return;
+        }
+        for _ in 0..count {
+            for &command in &self.last_edit {
+                self.view.handle_edit_command(command);
+            }
+        }
+    }
+
+    /// 累加重复次数的一位数字，并在消息栏显示当前次数
+    fn handle_count_digit(&mut self, digit: u8) {
+        let count = self
+            .pending_count
+            .unwrap_or(0)
+            .saturating_mul(10)
+            .saturating_add(usize::from(digit));
+        let count = count.min(MAX_REPEAT_COUNT);
+        self.pending_count = Some(count);
+        self.update_message(&messages::repeat_count(count));
+    }
+
+    /// 判断命令是否会修改缓冲区的内容（只读时拒绝执行；粘贴在 handle_paste 中检查）
+    fn modifies_buffer(command: Command) -> bool {
+        matches!(
+            command,
+            Edit(_)
+                | System(Cut | InsertFile | PipeThroughCommand | RepeatLastEdit | Replace | ToggleLineEnding)
+        )
+    }
+
+    /// 处理括号粘贴的内容：编辑区原样插入，提示模式下只取第一行
+    fn handle_paste(&mut self, text: &str) {
+        if self.in_prompt() {
+            let first_line = text.lines().next().unwrap_or_default();
+            for character in first_line.chars() {
+                self.command_bar.handle_edit_command(Insert(character));
+            }
+        } else if self.view.is_read_only() {
+            if self.dir_listing.is_none() {
+                self.update_message(messages::buffer_read_only());
+            }
+        } else {
+            self.reset_quit_times();
+            self.in_insert_run = false;
+            self.view.insert_str(text);
+            self.view.finish_undo_step(false);
+        }
+    }
+
+    /// 复制或剪切到剪贴板：优先为 Shift 选中的内容，其次为块选择的内容，都没有时为当前行；目录列表中不能剪切
+    fn handle_copy_command(&mut self, cut: bool) {
+        let cut = cut && self.dir_listing.is_none();
+        let text = if cut {
+            self.in_insert_run = false;
+            self.view.cut_selected_text()
+        } else {
+            self.view.selected_text()
+        };
+        let Some(text) = text else {
+            self.update_message(messages::nothing_to_copy());
+            return;
+        };
+        if self.clipboard.copy(&text) {
+            self.update_message(&messages::copied(text.lines().count().max(1), cut));
+        } else {
+            self.update_message(messages::system_clipboard_unavailable());
+        }
+    }
+
+    /// 粘贴剪贴板中的文本，与终端的括号粘贴相同：提示中只插入第一行
+    fn handle_paste_command(&mut self) {
+        match self.clipboard.paste() {
+            Some(text) => self.handle_paste(&text),
+            None => self.update_message(messages::clipboard_empty()),
+        }
+    }
+
+    /// 在消息栏显示光标处字素的码位和宽度
+    fn handle_char_info_command(&mut self) {
+        let message = match self.view.grapheme_at_caret() {
+            Some((grapheme, width)) => messages::grapheme_info(&grapheme, width),
+            None => messages::no_grapheme_at_caret().to_string(),
+        };
+        self.update_message(&message);
+    }
+
+    /// 显示最近的消息记录
+    fn show_message_log(&mut self) {
+        let view_height = self.terminal_size.height.saturating_sub(2);
+        let lines = self
+            .message_bar
+            .recent_messages(view_height.saturating_sub(1))
+            .map(str::to_string)
+            .collect();
+        self.overlay = Some((messages::message_log_header().to_string(), lines));
+    }
+
+    /// 关闭临时列表，恢复编辑区显示
+    fn hide_overlay(&mut self) {
+        self.overlay = None;
+        self.view.set_needs_redraw(true);
+    }
+
+    /// 列出最近文件，并提示输入要打开的序号
+    fn handle_recent_files_command(&mut self) {
+        if self.recent_files.paths().is_empty() {
+            self.update_message(messages::no_recent_files());
+            return;
+        }
+        let lines = self
+            .recent_files
+            .paths()
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| format!("{:>2}  {}", idx.saturating_add(1), path.display()))
+            .collect();
+        self.overlay = Some((messages::recent_files_header().to_string(), lines));
+        self.set_prompt(PromptType::OpenRecent);
+    }
+
+    /// 最近文件提示下的命令处理：回车打开所选序号的文件，Esc 取消
+    fn process_command_during_open_recent(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => {
+                self.hide_overlay();
+                self.set_prompt(PromptType::None);
+            }
+            Edit(InsertNewline) => {
+                let selected = self
+                    .command_bar
+                    .value()
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .and_then(|idx| self.recent_files.paths().get(idx))
+                    .map(|path| path.to_string_lossy().to_string());
+                self.hide_overlay();
+                self.set_prompt(PromptType::None);
+                match selected {
+                    Some(file_name) => self.open_file(&file_name),
+                    None => self.update_message(messages::invalid_recent_file_number()),
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 打开文件提示下的命令处理：回车打开输入的文件（打开失败时保留当前内容），Esc 取消
+    fn process_command_during_open(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(InsertNewline) => {
+                let file_name = self.command_bar.value();
+                self.set_prompt(PromptType::None);
+                if !file_name.is_empty() {
+                    self.open_file(&file_name);
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 打开文件：已经打开时切换过去；当前是未修改的空白文档时在当前视图中打开，
+    /// 否则作为新的文件打开，当前文件保留在后台。打开失败时回到原来的文件
+    fn open_file(&mut self, file_name: &str) {
+        if self.view.is_current_file(file_name) {
+            return;
+        }
+        if let Some(idx) = self.buffer_list.find(file_name) {
+            self.switch_buffer(idx);
+            return;
+        }
+        let is_blank = !self.view.is_file_loaded()
+            && self.dir_listing.is_none()
+            && !self.view.get_status().is_modified;
+        if is_blank {
+            self.load_file(file_name);
+            return;
+        }
+        let slot = BufferSlot {
+            view: self.view.empty_like(),
+            dir_listing: None,
+        };
+        self.with_active_slot(|buffer_list, active| buffer_list.open(slot, active));
+        if !self.load_file(file_name) {
+            self.with_active_slot(BufferList::close_active);
+            self.view.set_needs_redraw(true);
+        }
+    }
+
+    /// 以当前文件的视图和目录列表调用 f，用于在打开的文件之间切换
+    fn with_active_slot<T>(&mut self, f: impl FnOnce(&mut BufferList, &mut BufferSlot) -> T) -> T {
+        let mut active = BufferSlot {
+            view: std::mem::take(&mut self.view),
+            dir_listing: self.dir_listing.take(),
+        };
+        let result = f(&mut self.buffer_list, &mut active);
+        self.view = active.view;
+        self.dir_listing = active.dir_listing;
+        result
+    }
+
+    /// 切换到第 idx 个打开的文件，光标和滚动位置保持离开时的样子
+    fn switch_buffer(&mut self, idx: usize) {
+        self.in_insert_run = false;
+        self.pending_delete = false;
+        if !self.with_active_slot(|buffer_list, active| buffer_list.switch_to(idx, active)) {
+            return;
+        }
+        self.view.set_needs_redraw(true);
+        let file_name = self.view.get_status().file_name_to_string();
+        self.update_message(&messages::buffer_switched(
+            self.buffer_list.active_idx(),
+            self.buffer_list.count(),
+            &file_name,
+        ));
+    }
+
+    /// 切换到下一个（或上一个）打开的文件，到达末尾时回到开头
+    fn handle_switch_buffer_command(&mut self, forward: bool) {
+        let count = self.buffer_list.count();
+        if count <= 1 {
+            self.update_message(messages::only_one_buffer());
+            return;
+        }
+        self.switch_buffer(self.buffer_list.adjacent_idx(forward));
+    }
+
+    /// 加载文件到视图，恢复上次的光标位置；文件不可写、混用换行符时在消息栏提示。
+    /// 指定的是目录时改为列出其中的条目。无法打开时保留当前内容并返回 false
+    fn load_file(&mut self, file_name: &str) -> bool {
+        if Path::new(file_name).is_dir() {
+            return self.list_directory(Path::new(file_name));
+        }
+        let is_new_file = !Path::new(file_name).exists();
+        self.remember_cursor_position();
+        if let Err(err) = self.view.load(file_name) {
+            self.update_persistent_message(&messages::open_failed(file_name, &err));
+            return false;
+        }
+        self.dir_listing = None;
+        if is_new_file {
+            // 文件要到保存后才存在，之后再记入最近文件
+            if self.read_only_mode {
+                self.view.set_read_only(true);
+            }
+            self.update_message(&messages::new_file(file_name));
+            return true;
+        }
+        self.restore_cursor_position();
+        self.remember_recent_file();
+        if self.read_only_mode {
+            self.view.set_read_only(true);
+        } else if self.view.is_large_file() {
+            self.update_message(messages::large_file_read_only());
+        } else if self.view.is_lossy() {
+            self.update_message(messages::opened_lossy());
+        } else if self.view.is_read_only() {
+            self.update_message(messages::opened_read_only());
+        } else if let Some(line_ending) = self.view.mixed_line_endings() {
+            self.update_message(&messages::mixed_line_endings(line_ending));
+        } else if let Some(line_idx) = self.view.find_mixed_indentation() {
+            self.update_message(&messages::mixed_indentation(line_idx));
+        }
+        true
+    }
+
+    /// 在编辑区以只读方式列出目录中的条目；无法读取时保留当前内容、提示并返回 false
+    fn list_directory(&mut self, dir: &Path) -> bool {
+        let listing = match DirListing::read(dir) {
+            Ok(listing) => listing,
+            Err(err) => {
+                self.update_persistent_message(&messages::dir_read_failed(dir, &err));
+                return false;
+            }
+        };
+        self.view.load_lines(listing.entries());
+        self.view.set_read_only(true);
+        if listing.is_empty() {
+            self.update_message(&messages::dir_empty(listing.dir()));
+        } else {
+            self.update_message(&messages::dir_listing(listing.dir()));
+        }
+        self.dir_listing = Some(listing);
+        true
+    }
+
+    /// 打开目录列表中光标所在行的条目：子目录继续列出，文件在编辑区打开
+    fn open_dir_listing_entry(&mut self) {
+        let path = self
+            .dir_listing
+            .as_ref()
+            .and_then(|listing| listing.path_at(self.view.text_location().line_idx));
+        if let Some(path) = path {
+            self.load_file(&path.to_string_lossy());
+        }
+    }
+
+    /// 会话文件的路径：启动时指定的文件，否则为配置目录下的默认位置
+    #[cfg(feature = "session")]
+    fn session_path(&self) -> Option<PathBuf> {
+        self.session_file
+            .as_ref()
+            .map(PathBuf::from)
+            .or_else(Session::default_path)
+    }
+
+    /// 保存当前会话：所有打开的文件及其光标、滚动位置，以及当前文件
+    #[cfg(feature = "session")]
+    fn handle_save_session_command(&mut self) {
+        let Some(path) = self.session_path() else {
+            return;
+        };
+        let mut files = Vec::new();
+        let mut active = 0;
+        for (idx, view) in self.buffer_list.views(&self.view).enumerate() {
+            // 未命名的文件不记入会话
+            let Some(file_path) = view.file_path() else {
+                continue;
+            };
+            if idx == self.buffer_list.active_idx() {
+                active = files.len();
+            }
+            files.push(SessionFile {
+                path: file_path.canonicalize().unwrap_or_else(|_| file_path.to_path_buf()),
+                location: view.text_location(),
+                scroll_offset: view.scroll_offset(),
+            });
+        }
+        let session = Session { files, active };
+        match session.save(&path) {
+            Ok(()) => self.update_message(&messages::session_saved(&path)),
+            Err(err) => self.update_persistent_message(&messages::session_save_failed(&err)),
+        }
+    }
+
+    /// 未启用会话功能时提示
+    #[cfg(not(feature = "session"))]
+    fn handle_save_session_command(&mut self) {
+        self.update_message(messages::session_unavailable());
+    }
+
+    /// 恢复会话：依次打开各文件并恢复其光标和滚动位置，最后切换到当时的当前文件；
+    /// 跳过已不存在的文件
+    #[cfg(feature = "session")]
+    fn restore_session(&mut self) {
+        let Some(path) = self.session_path() else {
+            return;
+        };
+        let session = match Session::load(&path) {
+            Ok(session) => session,
+            Err(err) => {
+                self.update_persistent_message(&messages::session_restore_failed(&err));
+                return;
+            }
+        };
+        let mut missing = Vec::new();
+        let mut active_idx = None;
+        for (idx, file) in session.files.iter().enumerate() {
+            if !file.path.exists() {
+                missing.push(file);
+                continue;
+            }
+            let file_name = file.path.to_string_lossy();
+            self.open_file(&file_name);
+            if !self.view.is_current_file(&file_name) {
+                continue;
+            }
+            self.view.restore_view_state(file.location, file.scroll_offset);
+            if idx == session.active {
+                active_idx = Some(self.buffer_list.active_idx());
+            }
+        }
+        if let Some(idx) = active_idx {
+            self.with_active_slot(|buffer_list, active| buffer_list.switch_to(idx, active));
+        }
+        if !missing.is_empty() {
+            let names: Vec<String> = missing
+                .iter()
+                .map(|file| file.path.display().to_string())
+                .collect();
+            self.update_persistent_message(&messages::session_files_missing(&names));
+        }
+    }
+
+    /// 未启用会话功能时提示
+    #[cfg(not(feature = "session"))]
+    fn restore_session(&mut self) {
+        self.update_message(messages::session_unavailable());
+    }
+
+    /// 显示或隐藏与磁盘版本比较的改动标记
+    #[cfg(feature = "diff")]
+    fn handle_diff_marks_command(&mut self) {
+        if !self.view.is_file_loaded() {
+            self.update_message(messages::diff_needs_file());
+            return;
+        }
+        if self.view.is_large_file() {
+            self.update_message(messages::large_file_unsupported());
+            return;
+        }
+        match self.view.toggle_diff_marks() {
+            Ok(enabled) => self.update_message(messages::diff_marks(enabled)),
+            Err(err) => self.update_persistent_message(&messages::diff_failed(&err)),
+        }
+    }
+
+    /// 未启用比较功能时提示
+    #[cfg(not(feature = "diff"))]
+    fn handle_diff_marks_command(&mut self) {
+        self.update_message(messages::diff_unavailable());
+    }
+
+    /// 跳到下一处（或上一处）改动
+    #[cfg(feature = "diff")]
+    fn handle_jump_to_change_command(&mut self, direction: SearchDirection) {
+        if !self.view.is_showing_diff_marks() {
+            self.update_message(messages::diff_marks_hidden());
+        } else if !self.view.jump_to_change(direction) {
+            self.update_message(messages::no_more_changes());
+        }
+    }
+
+    /// 未启用比较功能时提示
+    #[cfg(not(feature = "diff"))]
+    fn handle_jump_to_change_command(&mut self, _direction: SearchDirection) {
+        self.update_message(messages::diff_unavailable());
+    }
+
+    /// 记录当前文件的光标位置
+    fn remember_cursor_position(&mut self) {
+        if let Some(path) = self.view.file_path() {
+            self.cursor_positions.set(path, self.view.text_location());
+        }
+    }
+
+    /// 恢复当前文件上次的光标位置
+    fn restore_cursor_position(&mut self) {
+        let location = self
+            .view
+            .file_path()
+            .and_then(|path| self.cursor_positions.get(path));
+        if let Some(location) = location {
+            self.view.restore_text_location(location);
+        }
+    }
+
+    /// 将当前文件记入最近文件列表
+    fn remember_recent_file(&mut self) {
+        if let Some(path) = self.view.file_path() {
+            self.recent_files.add(path);
         }
     }
 
     /// 处理调整终端大小命令
     fn handle_resize_command(&mut self, size: Size) {
         self.terminal_size = size;
-        self.view.resize(Size {
-            height: size.height.saturating_sub(2),
+        // 与 refresh_screen 的布局一致：高度不足 3 行时编辑区至少保留一行
+        let view_height = match size.height {
+            0..=2 => min(size.height, 1),
+            height => height.saturating_sub(2),
+        };
+        let view_size = Size {
+            height: view_height,
             width: size.width,
-        });
+        };
+        for view in std::iter::once(&mut self.view).chain(self.buffer_list.other_views_mut()) {
+            view.resize(view_size);
+        }
         let bar_size = Size {
             height: 1,
             width: size.width,
@@ -222,55 +1259,512 @@ impl Editor {
         self.message_bar.resize(bar_size);
         self.status_bar.resize(bar_size);
         self.command_bar.resize(bar_size);
+        // 连续调整大小时终端可能残留旧布局的内容（如缩小前的状态栏），清屏后全部重绘
+        self.view.clamp_caret();
+        self.handle_redraw_command();
+    }
+
+    /// 处理重绘命令：清屏并标记所有组件重绘，下一次刷新时重新绘制整个界面和光标
+    fn handle_redraw_command(&mut self) {
+        let _ = self.screen.clear_screen();
+        self.view.set_needs_redraw(true);
+        self.status_bar.set_needs_redraw(true);
+        self.message_bar.set_needs_redraw(true);
+        self.command_bar.set_needs_redraw(true);
+        self.title.clear(); // 让终端标题也重新设置
     }
 
     /// 处理退出命令，支持多次确认
     fn handle_quit_command(&mut self) {
-        if !self.view.get_status().is_modified || self.quit_times + 1 == QUIT_TIMES {
+        let modified_files = std::iter::once(&mut self.view)
+            .chain(self.buffer_list.other_views_mut())
+            .filter(|view| view.get_status().is_modified)
+            .count();
+        if modified_files == 0 || self.quit_times.saturating_add(1) >= self.required_quit_times {
+            self.remember_all_cursor_positions();
             self.should_quit = true;
-        } else if self.view.get_status().is_modified {
-            self.update_message(&format!(
-                "WARNING! 文件有未保存的更改。再按 Ctrl-Q {} 次以退出。",
-                QUIT_TIMES - self.quit_times - 1
+        } else {
+            // 确认期间提示不自动消失，直到退出计数被重置
+            self.message_bar.update_message_sticky(&messages::unsaved_quit_warning(
+                self.required_quit_times - self.quit_times - 1,
+                modified_files,
             ));
 
             self.quit_times += 1;
         }
     }
+    /// 处理强制退出命令，不检查未保存的更改
+    fn handle_force_quit_command(&mut self) {
+        self.remember_all_cursor_positions();
+        self.should_quit = true;
+    }
+    /// 退出前记录所有打开的文件的光标位置
+    fn remember_all_cursor_positions(&mut self) {
+        for view in std::iter::once(&mut self.view).chain(self.buffer_list.other_views_mut()) {
+            if let Some(path) = view.file_path() {
+                self.cursor_positions.set(path, view.text_location());
+            }
+        }
+    }
     /// 重置退出计数
     fn reset_quit_times(&mut self) {
         if self.quit_times > 0 {
             self.quit_times = 0;
-            self.update_message("");
+            self.message_bar.clear();
         }
     }
     
     /// 处理保存命令
     fn handle_save_command(&mut self) {
-        if self.view.is_file_loaded() {
+        if self.view.is_read_only() {
+            self.update_message(messages::save_read_only());
+        } else if self.view.is_file_loaded() {
             self.save(None);
         } else {
             self.set_prompt(PromptType::Save);
         }
     }
 
+    /// 处理全部保存命令：保存所有有路径的已修改文件，遇到错误继续保存其余文件，
+    /// 未命名的文件弹出另存为提示
+    fn handle_save_all_command(&mut self) {
+        let mut saved: usize = 0;
+        let mut failures = Vec::new();
+        let mut needs_name = false;
+        let views = std::iter::once(&mut self.view).chain(self.buffer_list.other_views_mut());
+        for (view_idx, view) in views.enumerate() {
+            if !view.get_status().is_modified {
+                continue;
+            }
+            // 未命名的文件只能为当前文件询问文件名
+            if !view.is_file_loaded() {
+                if view_idx == 0 {
+                    needs_name = true;
+                } else {
+                    failures.push(format!(
+                        "{}: {}",
+                        view.get_status().file_name,
+                        messages::unnamed_buffer_not_saved()
+                    ));
+                }
+                continue;
+            }
+            if view.is_read_only() {
+                failures.push(format!(
+                    "{}: {}",
+                    view.get_status().file_name,
+                    messages::save_read_only()
+                ));
+                continue;
+            }
+            let result = view.save();
+            debuglog::log(|| format!("save {}: {result:?}", view.get_status().file_name));
+            match result {
+                Ok(()) => {
+                    saved = saved.saturating_add(1);
+                    if let Some(path) = view.file_path() {
+                        self.recent_files.add(path);
+                        self.cursor_positions.set(path, view.text_location());
+                    }
+                }
+                Err(err) => failures.push(messages::save_file_failed(
+                    &view.get_status().file_name,
+                    &err,
+                )),
+            }
+        }
+        if failures.is_empty() {
+            self.update_message(&messages::saved_files(saved));
+        } else {
+            self.update_persistent_message(&messages::save_all_failed(saved, &failures));
+        }
+        if needs_name {
+            self.set_prompt(PromptType::Save);
+        }
+    }
+
     /// 保存模式下的命令处理
     fn process_command_during_save(&mut self, command: Command) {
         match command {
-            System(Quit | Resize(_) | Save) | Move(_) => {}, // 保存过程中不适用，调整大小已经在此阶段处理
             System(Dismiss) => {
                 self.set_prompt(PromptType::None);
-                self.update_message("保存已取消。");
+                self.update_message(messages::save_cancelled());
+            }
+            Edit(InsertNewline) => {
+                let file_name = self.command_bar.value();
+                if missing_parent_dir(&file_name).is_some() {
+                    self.pending_save_as = Some(file_name);
+                    self.set_prompt(PromptType::ConfirmCreateDir);
+                } else if Path::new(&file_name).exists() && !self.view.is_current_file(&file_name) {
+                    self.pending_save_as = Some(file_name);
+                    self.set_prompt(PromptType::ConfirmOverwrite);
+                } else {
+                    self.save(Some(&file_name));
+                    self.set_prompt(PromptType::None);
+                }
             }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {} // 保存过程中不适用，调整大小已经在此阶段处理
+        }
+    }
+
+    /// 插入文件模式下的命令处理：回车读取文件并插入到光标处，Esc 取消
+    fn process_command_during_insert_file(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
             Edit(InsertNewline) => {
                 let file_name = self.command_bar.value();
-                self.save(Some(&file_name));
                 self.set_prompt(PromptType::None);
+                self.insert_file(&file_name);
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 书签模式下的命令处理：输入一个字母后立即设置或跳转，Esc 取消
+    fn process_command_during_mark(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(Insert(name)) if name.is_ascii_alphabetic() => {
+                let message = if self.prompt_type == PromptType::SetMark {
+                    self.view.set_mark(name);
+                    messages::mark_set(name)
+                } else if self.view.jump_to_mark(name) {
+                    messages::mark_jumped(name)
+                } else {
+                    messages::mark_not_set(name)
+                };
+                self.set_prompt(PromptType::None);
+                self.update_message(&message);
+            }
+            Edit(_) | System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 搜索模式下的命令处理：输入时从进入提示的位置开始增量搜索，
+    /// 方向键查找下一个/上一个匹配，回车停在当前匹配处，Esc 回到原位置
+    fn process_command_during_search(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => {
+                if let Some((location, scroll_offset)) = self.search_origin.take() {
+                    self.view.restore_view_state(location, scroll_offset);
+                }
+                self.set_prompt(PromptType::None);
+            }
+            Edit(InsertNewline) => {
+                if let Some((location, _)) = self.search_origin.take() {
+                    if location != self.view.text_location() {
+                        self.view.record_jump_from(location);
+                    }
+                }
+                self.set_prompt(PromptType::None);
+            }
+            Edit(edit_command) => {
+                self.command_bar.handle_edit_command(edit_command);
+                self.search_query = self.command_bar.value();
+                self.search_incrementally();
+            }
+            Move(Down | Right) | System(SearchNext) => self.search_next(SearchDirection::Forward),
+            Move(Up | Left) | System(SearchPrevious) => self.search_next(SearchDirection::Backward),
+            System(ToggleWholeWordSearch) => {
+                self.view.toggle_search_whole_word();
+                self.search_incrementally();
+            }
+            System(_) | Move(_) | Select(_) => {}
+        }
+        // 搜索提示打开期间高亮所有匹配
+        let highlight = (self.prompt_type == PromptType::Search).then_some(self.search_query.as_str());
+        self.view.set_search_highlight(highlight);
+    }
+
+    /// 替换提示下的命令处理：回车确认要替换的内容（不能为空）并询问替换为什么，Esc 取消
+    fn process_command_during_replace_search(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(InsertNewline) => {
+                let query = self.command_bar.value();
+                if query.is_empty() {
+                    self.command_bar
+                        .set_prompt(&messages::replace_prompt(Some(messages::empty_replace_query())));
+                    return;
+                }
+                self.search_query = query;
+                self.set_prompt(PromptType::ReplaceWith);
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 替换为提示下的命令处理：回车后从光标处开始查找第一个匹配并逐个确认，Esc 取消
+    fn process_command_during_replace_with(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(InsertNewline) => {
+                let replacement = self.command_bar.value();
+                let origin = self.view.text_location();
+                let mut session = ReplaceSession::new(&self.search_query, &replacement, origin);
+                let Some(location) = session.next_match(&self.view, origin) else {
+                    self.set_prompt(PromptType::None);
+                    self.update_message(&messages::search_not_found(&self.search_query));
+                    return;
+                };
+                self.view.show_match(location);
+                if location != origin {
+                    self.view.record_jump_from(origin);
+                }
+                self.view.set_search_highlight(Some(session.query()));
+                self.replace_session = Some(session);
+                self.set_prompt(PromptType::ConfirmReplace);
             }
             Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 确认替换下的命令处理：y 替换当前匹配，n 跳过，a 替换其余所有匹配，q 或 Esc 结束。
+    /// 光标始终停在当前匹配处；到达文件末尾后回绕，回到起点时结束
+    fn process_command_during_replace_confirm(&mut self, command: Command) {
+        let Some(mut session) = self.replace_session.take() else {
+            self.set_prompt(PromptType::None);
+            return;
+        };
+        let at = self.view.text_location();
+        let next = match command {
+            Edit(Insert('y' | 'Y')) => {
+                let after = session.replace(&mut self.view, at);
+                session.next_match(&self.view, after)
+            }
+            Edit(Insert('n' | 'N')) => {
+                let from = Location {
+                    line_idx: at.line_idx,
+                    grapheme_idx: at.grapheme_idx.saturating_add(1),
+                };
+                session.next_match(&self.view, from)
+            }
+            Edit(Insert('a' | 'A')) => {
+                // 在同一条命令中全部替换，撤销时作为一步
+                let mut next = Some(at);
+                while let Some(location) = next {
+                    let after = session.replace(&mut self.view, location);
+                    next = session.next_match(&self.view, after);
+                }
+                None
+            }
+            Edit(Insert('q' | 'Q')) | System(Dismiss) => None,
+            _ => {
+                self.replace_session = Some(session);
+                return;
+            }
+        };
+        match next {
+            Some(location) => {
+                self.view.show_match(location);
+                self.replace_session = Some(session);
+            }
+            None => {
+                self.view.set_search_highlight(None);
+                self.set_prompt(PromptType::None);
+                self.update_message(&messages::replaced_count(session.replaced()));
+            }
+        }
+    }
+
+    /// 从进入搜索提示的位置开始查找当前输入，输入清空时回到原位置
+    fn search_incrementally(&mut self) {
+        let Some((origin, scroll_offset)) = self.search_origin else {
+            return;
+        };
+        if self.search_query.is_empty() {
+            self.view.restore_view_state(origin, scroll_offset);
+            self.update_search_prompt(None);
+            return;
+        }
+        let outcome = self
+            .view
+            .search_from(origin, &self.search_query, SearchDirection::Forward);
+        self.report_search_outcome(outcome, SearchDirection::Forward);
+    }
+
+    /// 从光标处查找上一次搜索内容的下一个（或上一个）匹配；
+    /// 在提示外查找时记入跳转记录
+    fn search_next(&mut self, direction: SearchDirection) {
+        if self.search_query.is_empty() {
+            if !self.in_prompt() {
+                self.update_message(messages::no_search_query());
+            }
+            return;
+        }
+        let before = self.view.text_location();
+        let outcome = self.view.search_next(&self.search_query, direction);
+        if !self.in_prompt() && self.view.text_location() != before {
+            self.view.record_jump_from(before);
+        }
+        self.report_search_outcome(outcome, direction);
+    }
+
+    /// 显示搜索结果的提示（匹配序号和总数、回绕等）：提示模式下显示在提示符中，否则显示在消息栏。
+    /// 按需读取的大文件不统计匹配总数
+    fn report_search_outcome(&mut self, outcome: SearchOutcome, direction: SearchDirection) {
+        let notice = if outcome == SearchOutcome::NotFound {
+            Some(messages::search_not_found(&self.search_query))
+        } else {
+            let extra = match outcome {
+                SearchOutcome::Wrapped => Some(messages::search_wrapped(direction)),
+                SearchOutcome::ReachedEnd => Some(messages::search_reached_end(direction)),
+                SearchOutcome::Found | SearchOutcome::NotFound => None,
+            };
+            if self.view.is_large_file() {
+                extra.map(ToString::to_string)
+            } else {
+                let (ordinal, total) = self.view.search_match_count(&self.search_query);
+                Some(messages::search_match_count(ordinal, total, extra))
+            }
+        };
+        if self.in_prompt() {
+            self.update_search_prompt(notice.as_deref());
+        } else {
+            self.update_message(&notice.unwrap_or_default());
+        }
+    }
+
+    /// 更新搜索提示符：显示整词模式和上一次搜索的提示
+    fn update_search_prompt(&mut self, notice: Option<&str>) {
+        let prompt = messages::search_prompt(self.view.is_search_whole_word(), notice);
+        self.command_bar.set_prompt(&prompt);
+    }
+
+    /// 跳转到行模式下的命令处理：回车跳转到输入的行号（超出末尾时停在最后一行），
+    /// 输入不是有效行号时保留提示并显示原因，Esc 取消且不移动光标
+    fn process_command_during_go_to_line(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(InsertNewline) => {
+                let Some(line_idx) = self
+                    .command_bar
+                    .value()
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                else {
+                    let prompt = messages::go_to_line_prompt(Some(messages::invalid_line_number()));
+                    self.command_bar.set_prompt(&prompt);
+                    return;
+                };
+                self.set_prompt(PromptType::None);
+                let reached = self.view.go_to_line(line_idx);
+                if reached < line_idx {
+                    self.update_message(&messages::line_number_clamped(reached));
+                }
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 过滤命令模式下的命令处理：回车执行命令并用其输出替换选中的内容或全文，Esc 取消
+    fn process_command_during_pipe(&mut self, command: Command) {
+        match command {
+            System(Dismiss) => self.set_prompt(PromptType::None),
+            Edit(InsertNewline) => {
+                let shell_command = self.command_bar.value();
+                self.set_prompt(PromptType::None);
+                self.pipe_through(&shell_command);
+            }
+            Edit(edit_command) => self.command_bar.handle_edit_command(edit_command),
+            System(_) | Move(_) | Select(_) => {}
+        }
+    }
+
+    /// 将选中的内容（没有选择时为全文）交给 shell 命令处理，成功时用其标准输出替换，
+    /// 失败时不修改缓冲区
+    fn pipe_through(&mut self, shell_command: &str) {
+        if shell_command.trim().is_empty() {
+            return;
+        }
+        let selection = self.view.selection_text();
+        let input = selection.clone().unwrap_or_else(|| self.view.text());
+        match shellfilter::run(shell_command, &input) {
+            Ok(output) => {
+                self.reset_quit_times();
+                self.in_insert_run = false;
+                if selection.is_some() {
+                    self.view.replace_selection(&output);
+                } else {
+                    self.view.replace_text(&output);
+                }
+                self.update_message(&messages::pipe_succeeded(shell_command));
+            }
+            Err(err) => self.update_persistent_message(&messages::pipe_failed(&err)),
+        }
+    }
+
+    /// 读取文件并插入到光标处；读取失败（文件不存在、不是 UTF-8 等）时不修改缓冲区
+    fn insert_file(&mut self, file_name: &str) {
+        match read_to_string(file_name) {
+            Ok(contents) => {
+                self.reset_quit_times();
+                self.in_insert_run = false;
+                self.view.insert_str(&contents);
+                self.update_message(&messages::file_inserted(file_name));
+            }
+            Err(err) => {
+                self.update_persistent_message(&messages::insert_file_failed(file_name, &err));
+            }
         }
     }
     
+    /// 覆盖确认模式下的命令处理：y 覆盖保存，n 返回文件名输入，Esc 取消保存
+    fn process_command_during_overwrite_confirm(&mut self, command: Command) {
+        match command {
+            Edit(Insert('y' | 'Y')) => {
+                if let Some(file_name) = self.pending_save_as.take() {
+                    self.save(Some(&file_name));
+                }
+                self.set_prompt(PromptType::None);
+            }
+            Edit(Insert('n' | 'N')) => {
+                self.pending_save_as = None;
+                self.set_prompt(PromptType::Save);
+            }
+            System(Dismiss) => {
+                self.pending_save_as = None;
+                self.set_prompt(PromptType::None);
+                self.update_message(messages::save_cancelled());
+            }
+            _ => {}
+        }
+    }
+
+    /// 确认创建目录模式下的命令处理：y 创建缺少的目录后保存，n 重新输入文件名，Esc 取消
+    fn process_command_during_create_dir_confirm(&mut self, command: Command) {
+        match command {
+            Edit(Insert('y' | 'Y')) => {
+                self.set_prompt(PromptType::None);
+                let Some(file_name) = self.pending_save_as.take() else {
+                    return;
+                };
+                let created = missing_parent_dir(&file_name).map_or(Ok(()), create_dir_all);
+                match created {
+                    Ok(()) => self.save(Some(&file_name)),
+                    Err(err) => self.update_persistent_message(&messages::save_failed(&err)),
+                }
+            }
+            Edit(Insert('n' | 'N')) => {
+                self.pending_save_as = None;
+                self.set_prompt(PromptType::Save);
+            }
+            System(Dismiss) => {
+                self.pending_save_as = None;
+                self.set_prompt(PromptType::None);
+                self.update_message(messages::save_cancelled());
+            }
+            _ => {}
+        }
+    }
+
     /// 保存文件，支持另存为
     fn save(&mut self, file_name: Option<&str>) {
         let result = if let Some(name) = file_name {
@@ -278,24 +1772,31 @@ impl Editor {
         } else {
             self.view.save()
         };
-        if result.is_ok() {
-            self.update_message("文件保存成功！");
-        } else {
-            self.update_message("文件写入失败！");
+        debuglog::log(|| {
+            let target = file_name.map_or_else(|| self.view.get_status().file_name, str::to_string);
+            format!("save {target}: {result:?}")
+        });
+        match result {
+            Ok(()) => {
+                self.remember_cursor_position();
+                self.remember_recent_file();
+                self.update_message(messages::save_succeeded());
+            }
+            Err(err) => self.update_persistent_message(&messages::save_failed(&err)),
         }
     }
 
-    /// 查找模式下的命令处理（已禁用，直接退出）
-    fn process_command_during_search(&mut self, _command: Command) {
-        // 纯文本编辑器不再支持查找，直接退出查找模式
-        self.set_prompt(PromptType::None);
-    }
-
     /// 更新消息栏内容
     fn update_message(&mut self, new_message: &str) {
         self.message_bar.update_message(new_message);
     }
 
+    /// 更新消息栏内容，消息不会自动过期（用于需要用户注意的错误）
+    fn update_persistent_message(&mut self, new_message: &str) {
+        debuglog::log(|| format!("error: {new_message}"));
+        self.message_bar.update_message_sticky(new_message);
+    }
+
     /// 判断当前是否为提示模式
     fn in_prompt(&self) -> bool {
         self.prompt_type.is_prompt()
@@ -305,19 +1806,222 @@ impl Editor {
     fn set_prompt(&mut self, prompt_type: PromptType) {
         match prompt_type {
             PromptType::None => self.message_bar.set_needs_redraw(true), // 确保消息栏在下一个重绘周期中正确绘制
-            PromptType::Save => self.command_bar.set_prompt("保存为（Esc 取消）: "),
+            PromptType::Save => self.command_bar.set_prompt(messages::save_as_prompt()),
+            PromptType::OpenRecent => self.command_bar.set_prompt(messages::open_recent_prompt()),
+            PromptType::Open => self.command_bar.set_prompt(messages::open_prompt()),
+            PromptType::InsertFile => self.command_bar.set_prompt(messages::insert_file_prompt()),
+            PromptType::PipeCommand => self.command_bar.set_prompt(messages::pipe_prompt()),
+            PromptType::SetMark => self.command_bar.set_prompt(messages::set_mark_prompt()),
+            PromptType::JumpToMark => self.command_bar.set_prompt(messages::jump_to_mark_prompt()),
+            PromptType::GoToLine => self.command_bar.set_prompt(&messages::go_to_line_prompt(None)),
+            PromptType::ReplaceSearch => self.command_bar.set_prompt(&messages::replace_prompt(None)),
+            PromptType::ReplaceWith => {
+                self.command_bar
+                    .set_prompt(&messages::replace_with_prompt(&self.search_query));
+            }
+            PromptType::ConfirmReplace => self.command_bar.set_prompt(messages::confirm_replace_prompt()),
+            PromptType::Search => {
+                self.search_origin = Some((self.view.text_location(), self.view.scroll_offset()));
+                self.update_search_prompt(None);
+            }
+            PromptType::ConfirmOverwrite => {
+                let file_name = self.pending_save_as.as_deref().unwrap_or_default();
+                self.command_bar
+                    .set_prompt(&messages::confirm_overwrite_prompt(file_name));
+            }
+            PromptType::ConfirmCreateDir => {
+                let file_name = self.pending_save_as.as_deref().unwrap_or_default();
+                let dir = missing_parent_dir(file_name)
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default();
+                self.command_bar
+                    .set_prompt(&messages::confirm_create_dir_prompt(&dir));
+            }
         }
         self.command_bar.clear_value();
         self.prompt_type = prompt_type;
     }
 }
 
+// 不连接终端的编辑接口：载入若干行文本，执行一系列命令，再读回内容和光标位置。
+// 编辑器本身不调用，供 Document、测试和脚本驱动编辑逻辑
+impl Editor {
+    /// 创建不连接终端的编辑器，编辑区为给定尺寸（含状态栏和消息栏），内容为给定的若干行
+    pub fn headless(lines: &[String], size: Size) -> Self {
+        let mut editor = Self::default();
+        editor.headless = true;
+        editor.required_quit_times = Config::default().quit_times;
+        editor.command_bar.set_tab_width(Config::default().tab_width);
+        editor.handle_resize_command(size);
+        editor.view.set_config(&Config::default());
+        editor.view.load_lines(lines);
+        editor
+    }
+
+    /// 依次执行命令，与按键触发时的处理完全相同，每条命令后都像主循环一样刷新界面；
+    /// 编辑器退出后忽略其余命令
+    pub fn apply(&mut self, commands: impl IntoIterator<Item = Command>) {
+        for command in commands {
+            if self.should_quit {
+                break;
+            }
+            self.process_command(command);
+            self.refresh_screen();
+        }
+    }
+
+    /// 返回缓冲区的所有行
+    pub fn lines(&self) -> Vec<String> {
+        self.view.lines()
+    }
+
+    /// 返回光标的文本位置（行、字素）
+    pub const fn text_location(&self) -> Location {
+        self.view.text_location()
+    }
+
+    /// 判断编辑器是否已收到退出命令
+    pub const fn should_quit(&self) -> bool {
+        self.should_quit
+    }
+}
+
+/// 文件所在的目录不存在时返回该目录（文件名不含目录时视为当前目录，总是存在）
+fn missing_parent_dir(file_name: &str) -> Option<&Path> {
+    Path::new(file_name)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+}
+
 impl Drop for Editor {
     /// 退出时终端清理
     fn drop(&mut self) {
+        if self.headless {
+            return;
+        }
         let _ = Terminal::terminate();
         if self.should_quit {
-            let _ = Terminal::print("欢迎下次使用。\r\n");
+            let _ = Terminal::print(messages::farewell());
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+    use terminal::RecordingScreen;
+
+    /// 创建 80×24 的无终端编辑器，内容为给定的若干行
+    fn editor(lines: &[&str]) -> Editor {
+        let lines: Vec<String> = lines.iter().map(|line| (*line).to_string()).collect();
+        Editor::headless(&lines, Size { height: 24, width: 80 })
+    }
+
+    #[test]
+    fn toggle_line_ending_is_rejected_when_read_only() {
+        let mut editor = editor(&["a", "b"]);
+        editor.view.set_read_only(true);
+        editor.apply([System(ToggleLineEnding)]);
+        let status = editor.view.get_status();
+        assert_eq!(status.line_ending, LineEnding::default());
+        assert!(!status.is_modified);
+    }
+
+    /// 依次输入文本中的各个字符
+    fn typed(text: &str) -> Vec<Command> {
+        text.chars().map(|character| Edit(Insert(character))).collect()
+    }
+
+    /// 打开过滤命令提示，输入命令并回车
+    fn pipe(editor: &mut Editor, shell_command: &str) {
+        editor.apply([System(PipeThroughCommand)]);
+        editor.apply(typed(shell_command));
+        editor.apply([Edit(InsertNewline)]);
+    }
+
+    #[test]
+    fn pipe_through_filters_only_the_selection() {
+        let mut editor = editor(&["c", "b", "a", "z", "y"]);
+        editor.apply([Select(Down), Select(Down), Select(Down)]);
+        pipe(&mut editor, "sort");
+        assert_eq!(editor.lines(), ["a", "b", "c", "z", "y"]);
+        assert_eq!(editor.text_location(), Location { line_idx: 3, grapheme_idx: 0 });
+        editor.apply([Edit(Undo)]);
+        assert_eq!(editor.lines(), ["c", "b", "a", "z", "y"]);
+    }
+
+    #[test]
+    fn move_line_keeps_selection_for_repeated_moves() {
+        let mut editor = editor(&["a", "b", "c", "d"]);
+        editor.apply([Select(Down), Select(Right)]);
+        editor.apply([Edit(EditCommand::MoveLineDown), Edit(EditCommand::MoveLineDown)]);
+        assert_eq!(editor.lines(), ["c", "d", "a", "b"]);
+        editor.apply([Edit(Undo)]);
+        assert_eq!(editor.lines(), ["c", "a", "b", "d"]);
+    }
+
+    #[test]
+    fn edits_are_rendered_through_the_screen() {
+        let mut editor = editor(&["hello", "world"]);
+        let rows = Rc::default();
+        editor.screen = Box::new(RecordingScreen { rows: Rc::clone(&rows) });
+        editor.apply([System(Redraw)]);
+        assert!(rows.borrow()[0].ends_with("hello"));
+        editor.apply([Move(EndOfLine), Edit(InsertNewline)]);
+        editor.apply(typed("字"));
+        editor.apply([Edit(EditCommand::DeleteBackward), Edit(Insert('!'))]);
+        let rows = rows.borrow();
+        assert!(rows[0].ends_with("hello"));
+        assert!(rows[1].ends_with('!'));
+        assert!(rows[2].ends_with("world"));
+    }
+
+    #[test]
+    fn pipe_through_filters_the_whole_buffer_without_selection() {
+        let mut editor = editor(&["c", "b", "a"]);
+        pipe(&mut editor, "sort");
+        assert_eq!(editor.lines(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn shrinking_the_terminal_keeps_the_caret_on_screen() {
+        let lines: Vec<String> = (0..100).map(|idx| format!("{idx:03}{}", "x".repeat(57))).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut editor = editor(&lines);
+        let rows = Rc::default();
+        editor.screen = Box::new(RecordingScreen { rows: Rc::clone(&rows) });
+        editor.apply(std::iter::repeat_n(Move(Down), 40));
+        editor.apply([Move(EndOfLine)]);
+        assert_eq!(editor.text_location(), Location { line_idx: 40, grapheme_idx: 60 });
+        editor.apply([System(Resize(Size { height: 10, width: 20 }))]);
+        assert_eq!(editor.text_location(), Location { line_idx: 40, grapheme_idx: 60 });
+        assert!(editor.view.is_caret_visible());
+        let caret = editor.view.caret_position();
+        assert!(caret.row < 8 && caret.col < 20, "{caret:?}");
+        // 清屏后所有组件都重绘：编辑区 8 行、状态栏和消息栏
+        let rows = rows.borrow();
+        assert_eq!(rows.len(), 10);
+        assert!(rows[caret.row].starts_with(" 41 "));
+        drop(rows);
+        // 只剩一行时光标仍在编辑区内
+        editor.apply([System(Resize(Size { height: 1, width: 5 }))]);
+        let caret = editor.view.caret_position();
+        assert!(caret.row < 1 && caret.col < 5, "{caret:?}");
+    }
+
+    #[test]
+    fn typing_in_read_only_mode_does_not_modify_the_buffer() {
+        let mut editor = editor(&["abc"]);
+        editor.apply([System(ToggleReadOnly)]);
+        editor.apply(typed("xyz"));
+        editor.apply([Edit(InsertNewline), Edit(Delete)]);
+        assert_eq!(editor.lines(), ["abc"]);
+        assert!(!editor.view.get_status().is_modified);
+        // 关闭只读后可以正常编辑
+        editor.apply([System(ToggleReadOnly)]);
+        editor.apply([Edit(Insert('x'))]);
+        assert_eq!(editor.lines(), ["xabc"]);
+        assert!(editor.view.get_status().is_modified);
+    }
+}
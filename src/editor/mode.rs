@@ -0,0 +1,9 @@
+// Mode 表示可选的 vi 风格模态编辑：普通模式下字母键解释为移动和编辑命令，插入模式下按键照常输入。
+
+/// 编辑模式
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+pub enum Mode {
+    Normal, // 普通模式：h/j/k/l 移动，i 进入插入模式，x、dd、o 编辑
+    #[default]
+    Insert, // 插入模式：按键直接输入文字，Esc 回到普通模式
+}
@@ -0,0 +1,69 @@
+// RecentFiles 记录最近打开或保存过的文件，持久化到配置目录下的纯文本文件（每行一个路径）。
+
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    io::Error,
+    path::{Path, PathBuf},
+};
+
+use super::configdir;
+
+/// 保存最近文件列表的文件名
+const FILE_NAME: &str = "recent_files";
+/// 最多保留的文件数
+const LIMIT: usize = 20;
+
+/// 最近文件列表（最近使用的在前）
+#[derive(Default)]
+pub struct RecentFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// 从配置目录读取列表，文件不存在或无法读取时返回空列表
+    pub fn load() -> Self {
+        let paths = configdir::config_file(FILE_NAME)
+            .and_then(|file| read_to_string(file).ok())
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(PathBuf::from)
+                    .take(LIMIT)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { paths }
+    }
+
+    /// 将文件移到列表最前面（去重并限制数量），并写回磁盘
+    pub fn add(&mut self, path: &Path) {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        self.paths.retain(|existing| existing != &path);
+        self.paths.insert(0, path);
+        self.paths.truncate(LIMIT);
+        // 列表只是便利功能，写入失败不影响编辑
+        let _ = self.save();
+    }
+
+    /// 获取所有路径
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// 将列表写入配置目录
+    fn save(&self) -> Result<(), Error> {
+        let Some(file) = configdir::config_file(FILE_NAME) else {
+            return Ok(());
+        };
+        if let Some(dir) = file.parent() {
+            create_dir_all(dir)?;
+        }
+        let contents: String = self
+            .paths
+            .iter()
+            .map(|path| format!("{}\n", path.display()))
+            .collect();
+        write(file, contents)
+    }
+}
@@ -0,0 +1,74 @@
+// ReplaceSession 记录一次逐个确认的替换：从光标处向后替换到文件末尾，再从开头回绕到起点为止。
+// 每次替换后从替换内容之后继续查找，替换内容本身包含搜索内容时也不会重复匹配。
+
+use crate::prelude::*;
+
+use super::uicomponents::View;
+
+/// 替换的起点。回绕前的替换都在起点之后，不影响它在行中的索引；回绕后的替换都在起点之前，
+/// 不影响它到行尾的距离，因此回绕时改为按到行尾的字素数记录
+#[derive(Clone, Copy)]
+enum Origin {
+    Ahead(Location),               // 尚未回绕：起点位置
+    Wrapped(LineIdx, GraphemeIdx), // 已回绕：起点所在行、起点到行尾的字素数
+}
+
+/// 逐个确认的替换
+pub struct ReplaceSession {
+    query: String,       // 搜索内容
+    replacement: String, // 替换内容
+    origin: Origin,      // 开始替换的位置
+    replaced: usize,     // 已替换的次数
+}
+
+impl ReplaceSession {
+    /// 从 origin 开始替换
+    pub fn new(query: &str, replacement: &str, origin: Location) -> Self {
+        Self {
+            query: query.to_string(),
+            replacement: replacement.to_string(),
+            origin: Origin::Ahead(origin),
+            replaced: 0,
+        }
+    }
+
+    /// 返回搜索内容
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// 返回已替换的次数
+    pub const fn replaced(&self) -> usize {
+        self.replaced
+    }
+
+    /// 从 from 开始查找下一个要确认的匹配；到达文件末尾时回绕，回到起点时返回 None
+    pub fn next_match(&mut self, view: &View, from: Location) -> Option<Location> {
+        let from = match self.origin {
+            Origin::Ahead(origin) => {
+                if let Some(location) = view.find_match_from(from, &self.query) {
+                    return Some(location);
+                }
+                let from_end = view.grapheme_count(origin.line_idx).saturating_sub(origin.grapheme_idx);
+                self.origin = Origin::Wrapped(origin.line_idx, from_end);
+                Location::default()
+            }
+            Origin::Wrapped(..) => from,
+        };
+        let Origin::Wrapped(line_idx, from_end) = self.origin else {
+            return None;
+        };
+        let origin = Location {
+            line_idx,
+            grapheme_idx: view.grapheme_count(line_idx).saturating_sub(from_end),
+        };
+        view.find_match_from(from, &self.query)
+            .filter(|&location| location < origin)
+    }
+
+    /// 替换 at 处的匹配，返回替换内容之后的位置
+    pub fn replace(&mut self, view: &mut View, at: Location) -> Location {
+        self.replaced = self.replaced.saturating_add(1);
+        view.replace_match(at, &self.query, &self.replacement)
+    }
+}
@@ -0,0 +1,53 @@
+// Session 将打开的文件及其光标、滚动位置保存为 JSON 文件，并在启动时恢复。
+
+use std::{
+    fs,
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::configdir;
+use crate::prelude::*;
+
+/// 默认会话文件名（位于配置目录）
+const FILE_NAME: &str = "session.json";
+
+/// 会话：打开的文件及当前活动的文件
+#[derive(Serialize, Deserialize, Default)]
+pub struct Session {
+    pub files: Vec<SessionFile>,
+    pub active: usize,
+}
+
+/// 会话中的单个文件
+#[derive(Serialize, Deserialize)]
+pub struct SessionFile {
+    pub path: PathBuf,
+    pub location: Location,
+    pub scroll_offset: Position,
+}
+
+impl Session {
+    /// 默认会话文件的路径
+    pub fn default_path() -> Option<PathBuf> {
+        configdir::config_file(FILE_NAME)
+    }
+
+    /// 从文件读取会话
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)?;
+        serde_json::from_str(&contents).map_err(|err| Error::new(ErrorKind::InvalidData, err))
+    }
+
+    /// 将会话写入文件
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let contents =
+            serde_json::to_string_pretty(self).map_err(|err| Error::new(ErrorKind::InvalidData, err))?;
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,60 @@
+// shellfilter 将文本作为标准输入交给外部 shell 命令（如 sort、fmt、jq），并取回其标准输出。
+
+use std::{
+    io::{self, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+/// 外部命令执行失败的原因
+pub enum FilterError {
+    Spawn(io::Error),   // 无法启动命令
+    Failed(String),     // 命令以非零状态退出，附带标准错误输出或退出状态
+    InvalidOutput,      // 标准输出不是 UTF-8 文本
+}
+
+/// 通过 shell 执行命令，将 input 写入其标准输入，返回标准输出
+pub fn run(command: &str, input: &str) -> Result<String, FilterError> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(FilterError::Spawn)?;
+    // 在单独的线程中写入标准输入，避免输出填满管道时双方互相等待
+    let stdin = child.stdin.take();
+    let input = input.to_string();
+    let writer = thread::spawn(move || {
+        if let Some(mut stdin) = stdin {
+            // 命令可能不读取全部输入就退出，此时写入失败可以忽略
+            let _ = stdin.write_all(input.as_bytes());
+        }
+    });
+    let output = child.wait_with_output().map_err(FilterError::Spawn)?;
+    let _ = writer.join();
+    if !output.status.success() {
+        // 消息栏只有一行，将多行错误输出合并显示；没有错误输出时显示退出状态
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr = stderr.split_whitespace().collect::<Vec<_>>().join(" ");
+        let reason = if stderr.is_empty() {
+            output.status.to_string()
+        } else {
+            stderr
+        };
+        return Err(FilterError::Failed(reason));
+    }
+    String::from_utf8(output.stdout).map_err(|_| FilterError::InvalidOutput)
+}
+
+/// 构造在当前平台 shell 中执行命令的进程
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut process = Command::new("cmd");
+        process.args(["/C", command]);
+        process
+    } else {
+        let mut process = Command::new("sh");
+        process.args(["-c", command]);
+        process
+    }
+}
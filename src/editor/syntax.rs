@@ -0,0 +1,201 @@
+// Syntax 为少数内置的语言（Rust、JSON）提供简单的语法高亮：按行识别关键字、字符串、数字和注释。
+// 每行单独分析，不跨行跟踪状态（如多行的块注释），只为屏幕上可见的行计算，开销很小。
+
+use std::ops::Range;
+
+use crate::prelude::*;
+
+use super::{AnnotationType, FileType};
+
+/// 一种语言的词法规则
+struct Syntax {
+    keywords: &'static [&'static str],                      // 关键字
+    line_comment: Option<&'static str>,                     // 行注释的开头
+    block_comment: Option<(&'static str, &'static str)>,    // 块注释的开头和结尾
+    char_literals: bool,                                    // 是否有 'a' 这样的字符字面量
+    negative_numbers: bool,                                 // 数字前的负号是否算作数字的一部分
+}
+
+const RUST: Syntax = Syntax {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    char_literals: true,
+    negative_numbers: false,
+};
+
+const JSON: Syntax = Syntax {
+    keywords: &["true", "false", "null"],
+    line_comment: None,
+    block_comment: None,
+    char_literals: false,
+    negative_numbers: true,
+};
+
+impl Syntax {
+    /// 返回文件类型对应的词法规则，不支持的类型返回 None
+    const fn for_file_type(file_type: FileType) -> Option<&'static Self> {
+        match file_type {
+            FileType::Rust => Some(&RUST),
+            FileType::Json => Some(&JSON),
+            _ => None,
+        }
+    }
+}
+
+/// 返回一行文本中需要着色的片段（字节范围及标注类型），按出现顺序排列；
+/// 不支持的文件类型返回空列表
+pub fn highlight(file_type: FileType, text: &str) -> Vec<(Range<ByteIdx>, AnnotationType)> {
+    let Some(syntax) = Syntax::for_file_type(file_type) else {
+        return Vec::new();
+    };
+    let mut spans = Vec::new();
+    let mut idx = 0;
+    while let Some(character) = text[idx..].chars().next() {
+        let rest = &text[idx..];
+        let (len, annotation_type) = if syntax.line_comment.is_some_and(|start| rest.starts_with(start)) {
+            (rest.len(), Some(AnnotationType::Comment))
+        } else if let Some((start, end)) = syntax.block_comment.filter(|(start, _)| rest.starts_with(start)) {
+            let len = rest[start.len()..]
+                .find(end)
+                .map_or(rest.len(), |end_idx| start.len() + end_idx + end.len());
+            (len, Some(AnnotationType::Comment))
+        } else if character == '"' {
+            (string_len(rest, '"'), Some(AnnotationType::StringLiteral))
+        } else if let Some(len) = char_literal_len(rest).filter(|_| syntax.char_literals) {
+            (len, Some(AnnotationType::StringLiteral))
+        } else if let Some(len) = number_len(rest, syntax.negative_numbers)
+            .filter(|_| !text[..idx].ends_with(is_identifier_char))
+        {
+            (len, Some(AnnotationType::Number))
+        } else if is_identifier_char(character) {
+            let len = rest.find(|c| !is_identifier_char(c)).unwrap_or(rest.len());
+            let is_keyword = syntax.keywords.contains(&&rest[..len]);
+            (len, is_keyword.then_some(AnnotationType::Keyword))
+        } else {
+            (character.len_utf8(), None)
+        };
+        if let Some(annotation_type) = annotation_type {
+            spans.push((idx..idx + len, annotation_type));
+        }
+        idx += len;
+    }
+    spans
+}
+
+/// 判断是否为标识符中的字符
+fn is_identifier_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+/// 以 quote 开头的字符串的字节长度（含两端的引号），没有结尾的引号时到行尾为止
+fn string_len(text: &str, quote: char) -> ByteIdx {
+    let mut escaped = false;
+    for (idx, character) in text.char_indices().skip(1) {
+        match character {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if character == quote => return idx + character.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// 'a'、'\n' 这样的字符字面量的字节长度；'a 这样的生命周期不算，返回 None
+fn char_literal_len(text: &str) -> Option<ByteIdx> {
+    let mut chars = text.char_indices();
+    chars.next().filter(|&(_, character)| character == '\'')?;
+    match chars.next()? {
+        (_, '\\') => {
+            let len = string_len(text, '\'');
+            (len < text.len() || text.ends_with('\'')).then_some(len)
+        }
+        (_, '\'') => None,
+        _ => chars
+            .next()
+            .filter(|&(_, character)| character == '\'')
+            .map(|(idx, _)| idx + 1),
+    }
+}
+
+/// 以数字开头（allow_negative 时也可以是负号加数字）的数字字面量的字节长度，
+/// 包括小数点、指数和 u8 这样的后缀；不是数字时返回 None
+fn number_len(text: &str, allow_negative: bool) -> Option<ByteIdx> {
+    let sign_len = usize::from(allow_negative && text.starts_with('-'));
+    let digits = &text[sign_len..];
+    if !digits.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let mut len = 0;
+    let mut previous = '0';
+    for character in digits.chars() {
+        let part_of_number = character.is_ascii_alphanumeric()
+            || character == '_'
+            // 小数点后须是数字，避免把 0..10 中的 .. 算进来
+            || (character == '.' && digits[len + 1..].starts_with(|c: char| c.is_ascii_digit()))
+            // 指数的符号，如 1e-5
+            || (matches!(character, '+' | '-') && matches!(previous, 'e' | 'E') && !digits.starts_with("0x"));
+        if !part_of_number {
+            break;
+        }
+        len += character.len_utf8();
+        previous = character;
+    }
+    Some(sign_len + len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use AnnotationType::{Comment, Keyword, Number, StringLiteral};
+
+    /// 返回各个着色片段的文本和标注类型
+    fn spans(file_type: FileType, text: &str) -> Vec<(&str, AnnotationType)> {
+        highlight(file_type, text)
+            .into_iter()
+            .map(|(range, annotation_type)| (&text[range], annotation_type))
+            .collect()
+    }
+
+    #[test]
+    fn rust_keyword_and_string_literal_get_distinct_spans() {
+        assert_eq!(
+            spans(FileType::Rust, r#"let s = "fn \"x\"";"#),
+            [("let", Keyword), (r#""fn \"x\"""#, StringLiteral)]
+        );
+    }
+
+    #[test]
+    fn rust_numbers_chars_and_comments() {
+        assert_eq!(
+            spans(FileType::Rust, "for i in 0..10u8 { x('a', 1e-5) } // 完"),
+            [
+                ("for", Keyword),
+                ("in", Keyword),
+                ("0", Number),
+                ("10u8", Number),
+                ("'a'", StringLiteral),
+                ("1e-5", Number),
+                ("// 完", Comment),
+            ]
+        );
+        // 生命周期不是字符字面量，标识符中的数字不是数字
+        assert_eq!(spans(FileType::Rust, "fn f<'a>(x2: &'a str)"), [("fn", Keyword)]);
+        assert_eq!(spans(FileType::Rust, "/* a */ b /* c"), [("/* a */", Comment), ("/* c", Comment)]);
+    }
+
+    #[test]
+    fn json_and_unsupported_file_types() {
+        assert_eq!(
+            spans(FileType::Json, r#"{"n": -1.5, "ok": true}"#),
+            [(r#""n""#, StringLiteral), ("-1.5", Number), (r#""ok""#, StringLiteral), ("true", Keyword)]
+        );
+        assert!(highlight(FileType::Markdown, "let x = 1;").is_empty());
+    }
+}
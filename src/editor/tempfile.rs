@@ -0,0 +1,52 @@
+// 测试用的临时文件：每个放在系统临时目录下单独的子目录中，离开作用域时连同子目录一起删除。
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// 本进程已建立的测试目录数，用于区分同名的测试文件
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// 临时目录中的测试文件，离开作用域时删除
+pub struct TempFile {
+    dir: PathBuf,  // 只放这个文件（以及被测代码在旁边建立的文件）的目录
+    path: PathBuf, // 文件路径，尚未写入时文件不存在
+}
+
+impl TempFile {
+    /// 新建空的测试目录，返回其中名为 name 的文件，文件本身尚未创建
+    pub fn new(name: &str) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let dir = env::temp_dir().join(format!("text-editor-{}-{id}", process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        Self { dir, path }
+    }
+
+    /// 新建内容为 contents 的测试文件
+    pub fn with_contents(name: &str, contents: impl AsRef<[u8]>) -> Self {
+        let file = Self::new(name);
+        fs::write(&file.path, contents).unwrap();
+        file
+    }
+
+    /// 文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 文件路径的字符串形式，用于以文件名打开的接口
+    pub fn name(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
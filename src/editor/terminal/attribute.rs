@@ -0,0 +1,99 @@
+// Attribute 将标注类型映射为终端的前景色和背景色，具体颜色取决于配色方案。
+
+use crossterm::style::Color;
+
+use super::super::AnnotationType;
+
+/// 配色方案
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Theme {
+    #[default]
+    Dark,  // 适合深色背景的终端
+    Light, // 适合浅色背景的终端
+}
+
+impl Theme {
+    /// 通过名称（dark、light）解析配色方案
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "dark" => Some(Self::Dark),
+            "light" => Some(Self::Light),
+            _ => None,
+        }
+    }
+}
+
+/// 终端文本样式
+pub struct Attribute {
+    pub foreground: Option<Color>, // 前景色
+    pub background: Option<Color>, // 背景色
+}
+
+impl Attribute {
+    /// 按配色方案返回标注类型对应的样式
+    pub const fn new(annotation_type: AnnotationType, theme: Theme) -> Self {
+        let light = matches!(theme, Theme::Light);
+        match annotation_type {
+            AnnotationType::Ruler => Self {
+                foreground: None,
+                background: Some(if light { Color::Grey } else { Color::DarkGrey }),
+            },
+            AnnotationType::OverLimit => Self {
+                foreground: Some(if light { Color::DarkRed } else { Color::Red }),
+                background: None,
+            },
+            AnnotationType::TrailingWhitespace => Self {
+                foreground: None,
+                background: Some(if light { Color::DarkRed } else { Color::Red }),
+            },
+            AnnotationType::Selection => Self {
+                foreground: Some(Color::White),
+                background: Some(if light { Color::DarkBlue } else { Color::Blue }),
+            },
+            AnnotationType::SearchMatch => Self {
+                foreground: Some(Color::Black),
+                background: Some(if light { Color::DarkYellow } else { Color::Yellow }),
+            },
+            AnnotationType::LineNumber | AnnotationType::OverflowMarker => Self {
+                foreground: Some(if light { Color::Grey } else { Color::DarkGrey }),
+                background: None,
+            },
+            AnnotationType::Keyword => Self {
+                foreground: Some(if light { Color::DarkMagenta } else { Color::Magenta }),
+                background: None,
+            },
+            AnnotationType::StringLiteral => Self {
+                foreground: Some(if light { Color::DarkGreen } else { Color::Green }),
+                background: None,
+            },
+            AnnotationType::Number => Self {
+                foreground: Some(if light { Color::DarkCyan } else { Color::Cyan }),
+                background: None,
+            },
+            AnnotationType::Comment => Self {
+                foreground: Some(if light { Color::Grey } else { Color::DarkGrey }),
+                background: None,
+            },
+            #[cfg(feature = "diff")]
+            AnnotationType::DiffAdded => Self {
+                foreground: Some(if light { Color::DarkGreen } else { Color::Green }),
+                background: None,
+            },
+            #[cfg(feature = "diff")]
+            AnnotationType::DiffModified => Self {
+                foreground: Some(if light { Color::DarkYellow } else { Color::Yellow }),
+                background: None,
+            },
+            #[cfg(feature = "diff")]
+            AnnotationType::DiffRemoved => Self {
+                foreground: Some(if light { Color::DarkRed } else { Color::Red }),
+                background: None,
+            },
+        }
+    }
+}
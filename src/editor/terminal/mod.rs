@@ -2,10 +2,11 @@
 
 
 use crossterm::{
-    cursor::{Hide, MoveTo, Show},
+    cursor::{Hide, MoveTo, SetCursorStyle, Show},
+    event::{DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture},
     style::{
         Attribute::{Reset, Reverse},
-        Print,
+        Print, ResetColor, SetBackgroundColor, SetForegroundColor,
     },
     terminal::{
         disable_raw_mode, enable_raw_mode, size, Clear, ClearType, DisableLineWrap, EnableLineWrap,
@@ -13,33 +14,218 @@ use crossterm::{
     },
     queue, Command,
 };
-use std::io::{stdout, Error, Write};
+use std::{
+    io::{stdout, Error, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        OnceLock,
+    },
+};
 use crate::prelude::*;
 
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{AnnotatedString, AnnotationType};
+
+mod attribute;
+use attribute::Attribute;
+pub use attribute::Theme;
+
+/// 当前使用的配色方案，启动时设置一次
+static THEME: OnceLock<Theme> = OnceLock::new();
+/// 是否以纯文本输出（NO_COLOR 或 --no-color），此时不输出任何颜色和反色属性
+static PLAIN: AtomicBool = AtomicBool::new(false);
 
 pub struct Terminal;
 
+/// 界面绘制用到的输出操作。编辑器通过它输出各 UI 组件，不直接调用 Terminal，
+/// 不连接终端时（脚本和测试）换成什么也不输出的 NullTerminal
+pub trait Screen {
+    /// 在指定行输出一行文本
+    fn print_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error>;
+    /// 在指定行输出带标注的文本
+    fn print_annotated_row(&mut self, row: RowIdx, annotated_string: &AnnotatedString) -> Result<(), Error>;
+    /// 在指定行输出反色文本
+    fn print_inverted_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error>;
+    /// 清空整个屏幕
+    fn clear_screen(&mut self) -> Result<(), Error>;
+    /// 隐藏光标
+    fn hide_caret(&mut self) -> Result<(), Error>;
+    /// 显示光标
+    fn show_caret(&mut self) -> Result<(), Error>;
+    /// 将光标移到指定位置并设置形状
+    fn place_caret(&mut self, position: Position, style: SetCursorStyle) -> Result<(), Error>;
+    /// 设置终端标题
+    fn set_title(&mut self, title: &str) -> Result<(), Error>;
+    /// 刷新输出缓冲区
+    fn execute(&mut self) -> Result<(), Error>;
+}
+
+impl Screen for Terminal {
+    fn print_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error> {
+        Self::print_row(row, line_text)
+    }
+    fn print_annotated_row(&mut self, row: RowIdx, annotated_string: &AnnotatedString) -> Result<(), Error> {
+        Self::print_annotated_row(row, annotated_string)
+    }
+    fn print_inverted_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error> {
+        Self::print_inverted_row(row, line_text)
+    }
+    fn clear_screen(&mut self) -> Result<(), Error> {
+        Self::clear_screen()
+    }
+    fn hide_caret(&mut self) -> Result<(), Error> {
+        Self::hide_caret()
+    }
+    fn show_caret(&mut self) -> Result<(), Error> {
+        Self::show_caret()
+    }
+    fn place_caret(&mut self, position: Position, style: SetCursorStyle) -> Result<(), Error> {
+        Self::move_caret_to(position)?;
+        Self::set_cursor_style(style)
+    }
+    fn set_title(&mut self, title: &str) -> Result<(), Error> {
+        Self::set_title(title)
+    }
+    fn execute(&mut self) -> Result<(), Error> {
+        Self::execute()
+    }
+}
+
+/// 什么也不输出的屏幕，所有操作都直接成功
+pub struct NullTerminal;
+
+impl Screen for NullTerminal {
+    fn print_row(&mut self, _row: RowIdx, _line_text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn print_annotated_row(&mut self, _row: RowIdx, _annotated_string: &AnnotatedString) -> Result<(), Error> {
+        Ok(())
+    }
+    fn print_inverted_row(&mut self, _row: RowIdx, _line_text: &str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn clear_screen(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn hide_caret(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn show_caret(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn place_caret(&mut self, _position: Position, _style: SetCursorStyle) -> Result<(), Error> {
+        Ok(())
+    }
+    fn set_title(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn execute(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// 记录各行最后一次输出内容的屏幕
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingScreen {
+    pub rows: std::rc::Rc<std::cell::RefCell<Vec<String>>>,
+}
+
+#[cfg(test)]
+impl RecordingScreen {
+    fn set_row(&self, row: RowIdx, text: String) {
+        let mut rows = self.rows.borrow_mut();
+        if rows.len() <= row {
+            rows.resize(row + 1, String::new());
+        }
+        rows[row] = text;
+    }
+}
+
+#[cfg(test)]
+impl Screen for RecordingScreen {
+    fn print_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error> {
+        self.set_row(row, line_text.to_string());
+        Ok(())
+    }
+    fn print_annotated_row(&mut self, row: RowIdx, annotated_string: &AnnotatedString) -> Result<(), Error> {
+        self.set_row(row, annotated_string.to_string());
+        Ok(())
+    }
+    fn print_inverted_row(&mut self, row: RowIdx, line_text: &str) -> Result<(), Error> {
+        self.set_row(row, line_text.to_string());
+        Ok(())
+    }
+    fn clear_screen(&mut self) -> Result<(), Error> {
+        self.rows.borrow_mut().clear();
+        Ok(())
+    }
+    fn hide_caret(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn show_caret(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+    fn place_caret(&mut self, _position: Position, _style: SetCursorStyle) -> Result<(), Error> {
+        Ok(())
+    }
+    fn set_title(&mut self, _title: &str) -> Result<(), Error> {
+        Ok(())
+    }
+    fn execute(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// 默认不输出，连接终端时再换成 Terminal
+impl Default for Box<dyn Screen> {
+    fn default() -> Self {
+        Box::new(NullTerminal)
+    }
+}
+
 impl Terminal {
     /// 终端清理与退出，恢复原始状态
     pub fn terminate() -> Result<(), Error> {
+        Self::disable_bracketed_paste()?;
+        Self::disable_mouse_capture()?;
         Self::leave_alternate_screen()?;
         Self::enable_line_wrap()?;
+        Self::set_cursor_style(SetCursorStyle::DefaultUserShape)?;
         Self::show_caret()?;
         Self::execute()?;
         disable_raw_mode()?;
         Ok(())
     }
     
-    /// 初始化终端，进入备用屏幕、禁用自动换行、清屏
+    /// 初始化终端，进入备用屏幕、启用括号粘贴、禁用自动换行、清屏
     pub fn initialize() -> Result<(), Error> {
         enable_raw_mode()?;
         Self::enter_alternate_screen()?;
+        Self::enable_bracketed_paste()?;
+        Self::enable_mouse_capture()?;
         Self::disable_line_wrap()?;
         Self::clear_screen()?;
         Self::execute()?;
         Ok(())
     }
 
+    /// 设置配色方案，只在启动时生效一次
+    pub fn set_theme(theme: Theme) {
+        let _ = THEME.set(theme);
+    }
+
+    /// 设置是否以纯文本输出，可在运行时切换
+    pub fn set_plain(plain: bool) {
+        PLAIN.store(plain, Ordering::Relaxed);
+    }
+
+    /// 判断是否以纯文本输出，状态栏和各种高亮都以此为准
+    pub fn is_plain() -> bool {
+        PLAIN.load(Ordering::Relaxed)
+    }
+
     /// 清空整个屏幕
     pub fn clear_screen() -> Result<(), Error> {
         Self::queue_command(Clear(ClearType::All))?;
@@ -71,6 +257,30 @@ impl Terminal {
         Ok(())
     }
 
+    /// 启用括号粘贴模式，粘贴内容将作为整体事件送达
+    pub fn enable_bracketed_paste() -> Result<(), Error> {
+        Self::queue_command(EnableBracketedPaste)?;
+        Ok(())
+    }
+
+    /// 禁用括号粘贴模式
+    pub fn disable_bracketed_paste() -> Result<(), Error> {
+        Self::queue_command(DisableBracketedPaste)?;
+        Ok(())
+    }
+
+    /// 启用鼠标捕获，接收点击和滚轮事件
+    pub fn enable_mouse_capture() -> Result<(), Error> {
+        Self::queue_command(EnableMouseCapture)?;
+        Ok(())
+    }
+
+    /// 禁用鼠标捕获
+    pub fn disable_mouse_capture() -> Result<(), Error> {
+        Self::queue_command(DisableMouseCapture)?;
+        Ok(())
+    }
+
     /// 隐藏光标
     pub fn hide_caret() -> Result<(), Error> {
         Self::queue_command(Hide)?;
@@ -83,6 +293,12 @@ impl Terminal {
         Ok(())
     }
 
+    /// 设置光标形状（块状、竖线等）
+    pub fn set_cursor_style(style: SetCursorStyle) -> Result<(), Error> {
+        Self::queue_command(style)?;
+        Ok(())
+    }
+
     /// 禁用自动换行
     pub fn disable_line_wrap() -> Result<(), Error> {
         Self::queue_command(DisableLineWrap)?;
@@ -115,10 +331,67 @@ impl Terminal {
         Ok(())
     }
 
+    /// 在指定行输出带标注的文本，各标注段按其类型着色
+    pub fn print_annotated_row(row: RowIdx, annotated_string: &AnnotatedString) -> Result<(), Error> {
+        let theme = THEME.get().copied().unwrap_or_default();
+        Self::print_row(row, &Self::annotated_text(annotated_string, theme, Self::is_plain()))
+    }
+
+    /// 将带标注的文本转换为输出的字符串，各标注段按配色方案加上颜色。纯文本输出时不含任何控制序列，
+    /// 选中的文本和搜索匹配改为在每个字素之后附加下划线组合字符，不占额外的列
+    fn annotated_text(annotated_string: &AnnotatedString, theme: Theme, plain: bool) -> String {
+        let mut result = String::new();
+        for (text, annotation_type) in annotated_string.segments() {
+            match annotation_type {
+                Some(annotation_type) if plain => match Self::plain_marker(annotation_type) {
+                    Some(marker) => {
+                        for grapheme in text.graphemes(true) {
+                            result.push_str(grapheme);
+                            result.push(marker);
+                        }
+                    }
+                    None => result.push_str(text),
+                },
+                Some(annotation_type) => {
+                    let attribute = Attribute::new(annotation_type, theme);
+                    // 写入 String 不会失败
+                    if let Some(color) = attribute.foreground {
+                        let _ = SetForegroundColor(color).write_ansi(&mut result);
+                    }
+                    if let Some(color) = attribute.background {
+                        let _ = SetBackgroundColor(color).write_ansi(&mut result);
+                    }
+                    result.push_str(text);
+                    let _ = ResetColor.write_ansi(&mut result);
+                }
+                None => result.push_str(text),
+            }
+        }
+        result
+    }
+
+    /// 纯文本输出时标出选中的文本（单下划线）和搜索匹配（双下划线）的组合字符，其他标注不标出
+    const fn plain_marker(annotation_type: AnnotationType) -> Option<char> {
+        match annotation_type {
+            AnnotationType::Selection => Some('\u{332}'),
+            AnnotationType::SearchMatch => Some('\u{333}'),
+            _ => None,
+        }
+    }
+
     /// 在指定行输出反色文本（用于状态栏等）
     pub fn print_inverted_row(row: RowIdx, line_text: &str) -> Result<(), Error> {
         let width = Self::size()?.width;
-        Self::print_row(row, &format!("{Reverse}{line_text:width$.width$}{Reset}"))
+        Self::print_row(row, &Self::inverted_text(line_text, width, Self::is_plain()))
+    }
+
+    /// 将文本补齐到 width 列并加上反色属性；纯文本输出时不加属性，改用 - 补齐以便与其他行区分
+    fn inverted_text(line_text: &str, width: usize, plain: bool) -> String {
+        if plain {
+            format!("{line_text:-<width$.width$}")
+        } else {
+            format!("{Reverse}{line_text:width$.width$}{Reset}")
+        }
     }
 
     /// 获取当前终端尺寸（行数和列数）
@@ -139,4 +412,43 @@ impl Terminal {
         queue!(stdout(), command)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> AnnotatedString {
+        let mut text = AnnotatedString::default();
+        text.push_str("let ", Some(AnnotationType::Keyword));
+        text.push_str("ab", Some(AnnotationType::Selection));
+        text.push_str(" = ", None);
+        text.push_str("字", Some(AnnotationType::SearchMatch));
+        text
+    }
+
+    #[test]
+    fn plain_output_has_no_escape_sequences() {
+        let text = Terminal::annotated_text(&sample(), Theme::Dark, true);
+        assert!(!text.contains('\x1b'));
+        assert_eq!(text, "let a\u{332}b\u{332} = 字\u{333}");
+        let inverted = Terminal::inverted_text("status", 10, true);
+        assert!(!inverted.contains('\x1b'));
+        assert_eq!(inverted, "status----");
+    }
+
+    #[test]
+    fn plain_markers_keep_column_widths() {
+        use unicode_width::UnicodeWidthStr;
+        let text = Terminal::annotated_text(&sample(), Theme::Light, true);
+        assert_eq!(text.width(), sample().to_string().width());
+    }
+
+    #[test]
+    fn colored_output_uses_escape_sequences() {
+        let text = Terminal::annotated_text(&sample(), Theme::Dark, false);
+        assert!(text.contains('\x1b'));
+        assert!(!text.contains('\u{332}'));
+        assert!(Terminal::inverted_text("status", 10, false).contains('\x1b'));
+    }
+}
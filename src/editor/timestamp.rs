@@ -0,0 +1,57 @@
+// timestamp 生成插入到文本中的日期时间字符串。
+// 启用 local-time 特性时使用本地时区（chrono），否则使用 UTC 并手动格式化。
+
+/// 时间戳格式
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TimestampFormat {
+    Iso8601,   // 2024-05-01T13:45:30+08:00（UTC 时以 Z 结尾）
+    ShortDate, // 2024-05-01
+}
+
+/// 按指定格式返回当前时间
+#[cfg(feature = "local-time")]
+pub fn now(format: TimestampFormat) -> String {
+    let now = chrono::Local::now();
+    match format {
+        TimestampFormat::Iso8601 => now.format("%Y-%m-%dT%H:%M:%S%:z").to_string(),
+        TimestampFormat::ShortDate => now.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// 按指定格式返回当前时间（UTC）
+#[cfg(not(feature = "local-time"))]
+pub fn now(format: TimestampFormat) -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let (year, month, day) = civil_from_days(seconds / 86_400);
+    let seconds_of_day = seconds % 86_400;
+    let (hour, minute, second) = (
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60,
+    );
+    match format {
+        TimestampFormat::Iso8601 => {
+            format!("{year:04}-{month:02}-{day:02}T{hour:02}:{minute:02}:{second:02}Z")
+        }
+        TimestampFormat::ShortDate => format!("{year:04}-{month:02}-{day:02}"),
+    }
+}
+
+/// 将自 1970-01-01 起的天数转换为（年、月、日），算法来自 Howard Hinnant 的 civil_from_days
+#[cfg(not(feature = "local-time"))]
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -5,7 +5,7 @@ use unicode_width::UnicodeWidthStr;
 
 use crate::prelude::*;
 
-use super::super::{command::Edit, Line, Terminal};
+use super::super::{command::Edit, Line, Screen};
 use super::UIComponent;
 
 /// 处理底部命令输入（如保存文件名）
@@ -15,15 +15,39 @@ pub struct CommandBar {
     value: Line,       // 用户输入内容
     needs_redraw: bool,// 是否需要重绘
     size: Size,        // 组件尺寸
+    tab_width: ColIdx, // 制表符宽度
 }
 
 impl CommandBar {
+    /// 设置输入内容中制表符的宽度
+    pub fn set_tab_width(&mut self, tab_width: ColIdx) {
+        self.tab_width = tab_width;
+        self.set_needs_redraw(true);
+    }
+
     /// 处理编辑命令（插入、删除等）
     pub fn handle_edit_command(&mut self, command: Edit) {
         match command {
             Edit::Insert(character) => self.value.append_char(character),
-            Edit::Delete | Edit::InsertNewline => {}
+            Edit::Delete
+            | Edit::InsertNewline
+            | Edit::MoveLineUp
+            | Edit::MoveLineDown
+            | Edit::InsertTimestamp(_)
+            | Edit::Indent
+            | Edit::Dedent
+            | Edit::ExpandTabs
+            | Edit::UnexpandTabs
+            | Edit::DeleteLine
+            | Edit::DuplicateLine
+            | Edit::CompleteWord
+            | Edit::Undo
+            | Edit::Redo => {}
             Edit::DeleteBackward => self.value.delete_last(),
+            Edit::DeleteBackwardCodepoint => self
+                .value
+                .delete_last_codepoint(self.value.grapheme_count().saturating_sub(1)),
+            Edit::DeleteToStartOfLine => self.value.remove_range(0..self.value.grapheme_count()),
         }
         self.set_needs_redraw(true);
     }
@@ -34,8 +58,8 @@ impl CommandBar {
         let value_width = UnicodeWidthStr::width(self.value.to_string().as_str());
         // 计算提示符和输入值的实际显示宽度
         let max_width = prompt_width + value_width;
-        // 限制光标位置在可显示宽度范围内
-        min(max_width, self.size.width)
+        // 限制光标位置在可显示宽度范围内（最后一列的下标为 width - 1）
+        min(max_width, self.size.width.saturating_sub(1))
     }
 
     /// 获取当前输入的字符串
@@ -70,12 +94,12 @@ impl UIComponent for CommandBar {
         self.size = size;
     }
     /// 绘制命令栏内容
-    fn draw(&mut self, origin: RowIdx) -> Result<(), Error> {
+    fn draw(&mut self, screen: &mut dyn Screen, origin: RowIdx) -> Result<(), Error> {
         let area_for_value = self.size.width.saturating_sub(self.prompt.len()); 
-        let value_end = self.value.width(); 
+        let value_end = self.value.width(self.tab_width); 
         let value_start = value_end.saturating_sub(area_for_value); 
     
-        let visible_value = self.value.get_visible_graphemes(value_start..value_end);
+        let visible_value = self.value.get_visible_graphemes(value_start..value_end, false, self.tab_width);
     
         let message = format!("{}{}", self.prompt, visible_value);
         let to_print = if message.len() <= self.size.width {
@@ -85,6 +109,6 @@ impl UIComponent for CommandBar {
             format!("{}{}", self.prompt, &visible_value)
         };
     
-        Terminal::print_row(origin, &to_print)
+        screen.print_row(origin, &to_print)
     }    
 }
@@ -1,27 +1,32 @@
 // MessageBar 用于在编辑器底部显示临时提示信息。
 
 use std::{
+    collections::VecDeque,
     io::Error,
     time::{Duration, Instant},
 };
 
 use crate::prelude::*;
-use super::super::Terminal;
+use super::super::Screen;
 use super::UIComponent;
 
 /// 默认消息显示时长（ 10秒）
 const DEFAULT_DURATION: Duration = Duration::new(10, 0);
+/// 消息记录最多保留的条数
+const HISTORY_LIMIT: usize = 50;
 
 
 struct Message {
-    text: String,   // 消息内容
-    time: Instant,  // 消息生成时间
+    text: String,               // 消息内容
+    time: Instant,              // 消息生成时间
+    duration: Option<Duration>, // 显示时长，None 表示不会自动过期
 }
 impl Default for Message {
     fn default() -> Self {
         Self {
             text: String::new(),
             time: Instant::now(),
+            duration: Some(DEFAULT_DURATION),
         }
     }
 }
@@ -29,28 +34,83 @@ impl Default for Message {
 impl Message {
     /// 判断消息是否已过期
     fn is_expired(&self) -> bool {
-        Instant::now().duration_since(self.time) > DEFAULT_DURATION
+        self.duration
+            .is_some_and(|duration| Instant::now().duration_since(self.time) > duration)
     }
 }
 
 /// 消息栏组件
-#[derive(Default)]
 pub struct MessageBar {
     current_message: Message,   // 当前显示的消息
+    default_duration: Duration, // 普通消息的显示时长
+    history: VecDeque<String>,  // 最近显示过的消息（旧的在前）
     needs_redraw: bool,        // 是否需要重绘
     cleared_after_expiry: bool, // 确保过期消息被正确清除
 }
 
+impl Default for MessageBar {
+    fn default() -> Self {
+        Self {
+            current_message: Message::default(),
+            default_duration: DEFAULT_DURATION,
+            history: VecDeque::new(),
+            needs_redraw: false,
+            cleared_after_expiry: false,
+        }
+    }
+}
+
 impl MessageBar {
-    /// 更新消息栏内容，并重置计时
+    /// 设置普通消息的默认显示时长
+    pub fn set_default_duration(&mut self, duration: Duration) {
+        self.default_duration = duration;
+    }
+
+    /// 更新消息栏内容，并重置计时（使用默认显示时长）
     pub fn update_message(&mut self, new_message: &str) {
+        self.update_message_with_duration(new_message, Some(self.default_duration));
+    }
+
+    /// 更新消息栏内容并指定显示时长，None 表示消息一直保留直到被替换
+    pub fn update_message_with_duration(&mut self, new_message: &str, duration: Option<Duration>) {
+        if !new_message.is_empty() {
+            if self.history.len() == HISTORY_LIMIT {
+                self.history.pop_front();
+            }
+            self.history.push_back(new_message.to_string());
+        }
         self.current_message = Message {
             text: new_message.to_string(),
             time: Instant::now(),
+            duration,
         };
         self.cleared_after_expiry = false;
         self.set_needs_redraw(true);
     }
+
+    /// 更新消息栏内容，消息一直保留直到被替换或清除（用于需要用户注意的提示）
+    pub fn update_message_sticky(&mut self, new_message: &str) {
+        self.update_message_with_duration(new_message, None);
+    }
+
+    /// 立即清空消息栏，不记入消息记录；清空后只重绘一次
+    pub fn clear(&mut self) {
+        self.current_message = Message {
+            text: String::new(),
+            time: Instant::now(),
+            duration: None,
+        };
+        self.cleared_after_expiry = true;
+        self.set_needs_redraw(true);
+    }
+
+    /// 返回最近的至多 count 条消息（旧的在前）
+    pub fn recent_messages(&self, count: usize) -> impl Iterator<Item = &str> {
+        self.history
+            .iter()
+            .skip(self.history.len().saturating_sub(count))
+            .map(String::as_str)
+    }
 }
 
 impl UIComponent for MessageBar {
@@ -65,7 +125,7 @@ impl UIComponent for MessageBar {
     /// 消息栏无需调整尺寸，空实现
     fn set_size(&mut self, _size: Size) {}
     /// 绘制消息栏内容，过期时清空
-    fn draw(&mut self, origin: RowIdx) -> Result<(), Error> {
+    fn draw(&mut self, screen: &mut dyn Screen, origin: RowIdx) -> Result<(), Error> {
         if self.current_message.is_expired() {
             self.cleared_after_expiry = true; // 过期时，写出 "" 一次以清除消息
         }
@@ -75,6 +135,55 @@ impl UIComponent for MessageBar {
             &self.current_message.text
         };
 
-        Terminal::print_row(origin, message)
+        screen.print_row(origin, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::terminal::RecordingScreen;
+
+    /// 让当前消息看起来是 age 之前生成的
+    fn age(message_bar: &mut MessageBar, age: Duration) {
+        message_bar.current_message.time = Instant::now().checked_sub(age).unwrap();
+    }
+
+    /// 渲染一次，返回这次输出的内容；没有重绘时返回 None
+    fn render(message_bar: &mut MessageBar) -> Option<String> {
+        let mut screen = RecordingScreen::default();
+        message_bar.render(&mut screen, 0);
+        let row = screen.rows.borrow().first().cloned();
+        row
+    }
+
+    #[test]
+    fn sticky_message_survives_past_the_default_duration() {
+        let mut message_bar = MessageBar::default();
+        message_bar.update_message_sticky("read-only");
+        assert_eq!(render(&mut message_bar).as_deref(), Some("read-only"));
+        age(&mut message_bar, DEFAULT_DURATION + Duration::from_secs(60));
+        assert!(!message_bar.needs_redraw());
+        message_bar.set_needs_redraw(true);
+        assert_eq!(render(&mut message_bar).as_deref(), Some("read-only"));
+        message_bar.clear();
+        assert_eq!(render(&mut message_bar).as_deref(), Some(""));
+        assert_eq!(render(&mut message_bar), None);
+    }
+
+    #[test]
+    fn timed_message_clears_exactly_once() {
+        let mut message_bar = MessageBar::default();
+        message_bar.update_message("saved");
+        assert_eq!(render(&mut message_bar).as_deref(), Some("saved"));
+        age(&mut message_bar, Duration::from_secs(9));
+        assert_eq!(render(&mut message_bar), None);
+        age(&mut message_bar, DEFAULT_DURATION + Duration::from_secs(1));
+        assert!(message_bar.needs_redraw());
+        assert_eq!(render(&mut message_bar).as_deref(), Some(""));
+        assert!(!message_bar.needs_redraw());
+        assert_eq!(render(&mut message_bar), None);
+        // 过期后仍可在消息记录中看到
+        assert_eq!(message_bar.recent_messages(5).collect::<Vec<_>>(), ["saved"]);
     }
 }
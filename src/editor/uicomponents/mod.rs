@@ -2,7 +2,7 @@
 
 mod view;
 /// 编辑区主视图组件
-pub use view::View;
+pub use view::{Buffer, LineNumbers, LineRef, SearchDirection, SearchOutcome, View};
 
 mod commandbar;
 /// 命令栏组件（显示快捷键信息）
@@ -14,7 +14,7 @@ pub use messagebar::MessageBar;
 
 mod statusbar;
 /// 状态栏组件（显示文件名、行数、光标位置等）
-pub use statusbar::StatusBar;
+pub use statusbar::{StatusBar, StatusTemplate};
 
 mod uicomponent;
 /// UI 组件通用 trait
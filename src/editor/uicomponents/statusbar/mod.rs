@@ -0,0 +1,151 @@
+// StatusBar 显示文件名、行数、修改状态和光标位置等信息。
+
+use std::io::Error;
+use unicode_width::UnicodeWidthStr;
+use crate::prelude::*;
+use super::super::{DocumentStatus, Screen, Terminal};
+use super::UIComponent;
+
+mod template;
+pub use template::StatusTemplate;
+
+/// 状态栏组件，负责显示文档状态信息
+pub struct StatusBar {
+    current_status: DocumentStatus, // 当前文档状态
+    needs_redraw: bool,            // 是否需要重绘
+    size: Size,                    // 状态栏尺寸
+    left_template: StatusTemplate,  // 左对齐部分的模板
+    right_template: StatusTemplate, // 右对齐部分的模板
+}
+
+impl Default for StatusBar {
+    fn default() -> Self {
+        Self {
+            current_status: DocumentStatus::default(),
+            needs_redraw: false,
+            size: Size::default(),
+            left_template: StatusTemplate::default_left(),
+            right_template: StatusTemplate::default_right(),
+        }
+    }
+}
+
+impl StatusBar {
+    /// 设置左右两部分的模板
+    pub fn set_templates(&mut self, left: StatusTemplate, right: StatusTemplate) {
+        self.left_template = left;
+        self.right_template = right;
+        self.set_needs_redraw(true);
+    }
+
+
+    /// 更新状态栏显示的文档状态
+    pub fn update_status(&mut self, new_status: DocumentStatus) {
+        if new_status != self.current_status {
+            self.current_status = new_status;
+            self.set_needs_redraw(true);
+        }
+    }
+
+    /// 各部分之间 len 列宽的空位；纯文本输出时没有反色背景，中间用 - 填充，两端各留一个空格
+    fn padding(len: usize) -> String {
+        if Terminal::is_plain() && len > 2 {
+            format!(" {} ", "-".repeat(len - 2))
+        } else {
+            " ".repeat(len)
+        }
+    }
+}
+
+impl UIComponent for StatusBar {
+    /// 设置是否需要重绘
+    fn set_needs_redraw(&mut self, value: bool) {
+        self.needs_redraw = value;
+    }
+    /// 判断是否需要重绘
+    fn needs_redraw(&self) -> bool {
+        self.needs_redraw
+    }
+    /// 设置状态栏尺寸
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+    }
+    /// 绘制状态栏内容
+    fn draw(&mut self, screen: &mut dyn Screen, origin_row: RowIdx) -> Result<(), Error> {
+        let beginning = self.left_template.render(&self.current_status);
+        let back_part = self.right_template.render(&self.current_status);
+
+        // 组装整个状态栏，右半部分靠右对齐
+        let padding = self
+            .size
+            .width
+            .saturating_sub(beginning.width())
+            .saturating_sub(back_part.width());
+        // 全文统计放在中间，两侧至少各留一个空格；放不下时依次省略字节数、词数，最后整个省略
+        let middle = self
+            .current_status
+            .counts_to_strings()
+            .into_iter()
+            .find(|middle| middle.width().saturating_add(2) <= padding)
+            .unwrap_or_default();
+        let left_padding = if middle.is_empty() {
+            padding
+        } else {
+            // 尽量居中，但不挤掉两侧的空格
+            let centered_start = self.size.width.saturating_sub(middle.width()) / 2;
+            let max_padding = padding.saturating_sub(middle.width()).saturating_sub(1);
+            centered_start.saturating_sub(beginning.width()).clamp(1, max_padding)
+        };
+        let right_padding = padding
+            .saturating_sub(middle.width())
+            .saturating_sub(left_padding);
+        let status = format!(
+            "{beginning}{}{middle}{}{back_part}",
+            Self::padding(left_padding),
+            Self::padding(right_padding)
+        );
+
+        // 仅在状态适合时打印状态。否则写出一个空字符串以确保清除行。
+        let to_print = if status.width() <= self.size.width {
+            status
+        } else {
+            String::new()
+        };
+        screen.print_inverted_row(origin_row, &to_print)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::{command::Move, terminal::RecordingScreen};
+    use super::super::View;
+
+    /// 把视图的状态画到 80 列的状态栏上，返回输出的文本
+    fn rendered(view: &View) -> String {
+        let mut status_bar = StatusBar::default();
+        status_bar.resize(Size { height: 1, width: 80 });
+        status_bar.update_status(view.get_status());
+        let mut screen = RecordingScreen::default();
+        status_bar.draw(&mut screen, 0).unwrap();
+        let row = screen.rows.borrow()[0].clone();
+        row
+    }
+
+    #[test]
+    fn column_counts_display_width_after_cjk() {
+        let mut view = View::default();
+        view.resize(Size { height: 10, width: 80 });
+        view.load_lines(&["字a".to_string()]);
+        view.handle_move_command(Move::Right);
+        let status = view.get_status();
+        assert_eq!(status.current_col_idx, 1);
+        assert_eq!(status.current_col, 2);
+        assert!(rendered(&view).contains("1/1 : 3"));
+        view.handle_move_command(Move::Right);
+        assert_eq!(view.get_status().current_col, 3);
+        assert!(rendered(&view).contains("1/1 : 4"));
+    }
+}
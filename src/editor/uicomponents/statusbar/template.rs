@@ -0,0 +1,245 @@
+// StatusTemplate 解析状态栏的格式模板（如 "{file} - {lines}"），并按文档状态填入各占位符。
+// 模板只在设置时解析一次，绘制时直接按解析结果拼接。
+
+use super::super::super::DocumentStatus;
+
+/// 左半部分的默认模板
+const DEFAULT_LEFT: &str = "{buffers}{file} [{filetype}] - {lines} {modified}{readonly}{lossy}";
+/// 右半部分的默认模板
+const DEFAULT_RIGHT: &str = "{mode}{recording}{eol} {position} {percent}";
+
+/// 模板中可用的占位符
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum Field {
+    File,       // {file}：文件名，已修改时前面带 *
+    FileType,   // {filetype}：文件类型
+    Lines,      // {lines}：“N 行”
+    Modified,   // {modified}：已修改标记
+    ReadOnly,   // {readonly}：只读标记 [RO]
+    Lossy,      // {lossy}：有损解码标记 [lossy]
+    Mode,       // {mode}：模态编辑的当前模式
+    Recording,  // {recording}：宏录制标记
+    LineEnding, // {eol}：换行符
+    Line,       // {line}：光标所在行号（从 1 开始）
+    Col,        // {col}：光标所在列号（按字素，从 1 开始）
+    Total,      // {total}：总行数
+    Position,   // {position}：“当前行/总行 : 显示列”
+    Percent,    // {percent}：视口位置（All、Top、Bot 或 NN%）
+    Words,      // {words}：全文词数
+    Chars,      // {chars}：全文字符数（按字素计）
+    Bytes,      // {bytes}：保存时的字节数
+    Buffers,    // {buffers}：打开了多个文件时为“[当前序号/文件数] ”
+}
+
+impl Field {
+    /// 通过占位符名称解析
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "file" => Some(Self::File),
+            "filetype" => Some(Self::FileType),
+            "lines" => Some(Self::Lines),
+            "modified" => Some(Self::Modified),
+            "readonly" => Some(Self::ReadOnly),
+            "lossy" => Some(Self::Lossy),
+            "mode" => Some(Self::Mode),
+            "recording" => Some(Self::Recording),
+            "eol" => Some(Self::LineEnding),
+            "line" => Some(Self::Line),
+            "col" => Some(Self::Col),
+            "total" => Some(Self::Total),
+            "position" => Some(Self::Position),
+            "percent" => Some(Self::Percent),
+            "words" => Some(Self::Words),
+            "chars" => Some(Self::Chars),
+            "bytes" => Some(Self::Bytes),
+            "buffers" => Some(Self::Buffers),
+            _ => None,
+        }
+    }
+
+    /// 按文档状态返回占位符的内容
+    fn render(self, status: &DocumentStatus) -> String {
+        match self {
+            Self::File => status.file_name_to_string(),
+            Self::FileType => status.file_type.to_string(),
+            Self::Lines => status.line_count_to_string(),
+            Self::Modified => status.modified_indicator_to_string(),
+            Self::ReadOnly => status.read_only_indicator_to_string(),
+            Self::Lossy => status.lossy_indicator_to_string(),
+            Self::Mode => status.mode_indicator_to_string(),
+            Self::Recording => status.recording_indicator_to_string(),
+            Self::LineEnding => status.line_ending.to_string(),
+            Self::Line => status.current_line_idx.saturating_add(1).to_string(),
+            Self::Col => status.current_col_idx.saturating_add(1).to_string(),
+            Self::Total => status.total_lines.to_string(),
+            Self::Position => status.position_indicator_to_string(),
+            Self::Percent => status.scroll_indicator_to_string(),
+            Self::Words => status.total_words.to_string(),
+            Self::Chars => status.total_chars.to_string(),
+            Self::Bytes => status.total_bytes.to_string(),
+            Self::Buffers => status.buffer_indicator_to_string(),
+        }
+    }
+}
+
+/// 模板的组成部分：原样输出的文字或占位符
+#[derive(Clone, Eq, PartialEq, Debug)]
+enum Segment {
+    Text(String),
+    Field(Field),
+}
+
+/// 解析后的状态栏模板。`{{` 和 `}}` 表示花括号本身
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "config", derive(serde::Deserialize), serde(try_from = "String"))]
+pub struct StatusTemplate {
+    segments: Vec<Segment>,
+}
+
+impl StatusTemplate {
+    /// 解析模板；遇到未知的占位符或未闭合的花括号时返回出错的片段
+    pub fn parse(template: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+        while let Some(character) = chars.next() {
+            match character {
+                '{' => {
+                    let rest = chars.as_str();
+                    if let Some(after) = rest.strip_prefix('{') {
+                        text.push('{');
+                        chars = after.chars();
+                        continue;
+                    }
+                    let Some((name, after)) = rest.split_once('}') else {
+                        return Err(format!("{{{rest}"));
+                    };
+                    let field = Field::from_name(name).ok_or_else(|| format!("{{{name}}}"))?;
+                    if !text.is_empty() {
+                        segments.push(Segment::Text(std::mem::take(&mut text)));
+                    }
+                    segments.push(Segment::Field(field));
+                    chars = after.chars();
+                }
+                '}' => {
+                    let rest = chars.as_str();
+                    let Some(after) = rest.strip_prefix('}') else {
+                        return Err("}".to_string());
+                    };
+                    text.push('}');
+                    chars = after.chars();
+                }
+                _ => text.push(character),
+            }
+        }
+        if !text.is_empty() {
+            segments.push(Segment::Text(text));
+        }
+        Ok(Self { segments })
+    }
+
+    /// 左半部分的默认模板
+    pub fn default_left() -> Self {
+        Self::parse(DEFAULT_LEFT).unwrap_or_default()
+    }
+
+    /// 右半部分的默认模板
+    pub fn default_right() -> Self {
+        Self::parse(DEFAULT_RIGHT).unwrap_or_default()
+    }
+
+    /// 按文档状态填入占位符
+    pub fn render(&self, status: &DocumentStatus) -> String {
+        self.segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Text(text) => text.clone(),
+                Segment::Field(field) => field.render(status),
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<String> for StatusTemplate {
+    type Error = String;
+
+    fn try_from(template: String) -> Result<Self, Self::Error> {
+        Self::parse(&template).map_err(|part| format!("status template: {part}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 文件名为 a.rs、光标在第 3 行第 5 列、共 10 行的状态
+    fn status() -> DocumentStatus {
+        DocumentStatus {
+            file_name: "a.rs".to_string(),
+            total_lines: 10,
+            current_line_idx: 2,
+            current_col_idx: 4,
+            ..DocumentStatus::default()
+        }
+    }
+
+    #[test]
+    fn parse_renders_text_escapes_and_fields() {
+        let cases = [
+            ("", ""),
+            ("plain", "plain"),
+            ("{file}", "a.rs"),
+            ("{line}:{col}/{total}", "3:5/10"),
+            ("{{file}}", "{file}"),
+            ("{{{file}}}", "{a.rs}"),
+            ("}}{{", "}{"),
+            ("[{file}] {{x}}", "[a.rs] {x}"),
+        ];
+        for (template, expected) in cases {
+            let parsed = StatusTemplate::parse(template).unwrap();
+            assert_eq!(parsed.render(&status()), expected, "{template:?}");
+        }
+    }
+
+    #[test]
+    fn parse_reports_the_offending_part() {
+        let cases = [
+            // 未知的占位符名称
+            ("{name}", "{name}"),
+            ("{file} {FILE}", "{FILE}"),
+            ("{}", "{}"),
+            // 未闭合的 {
+            ("{file", "{file"),
+            ("a {line} {col", "{col"),
+            ("{", "{"),
+            // 单独的 }
+            ("}", "}"),
+            ("a}b", "}"),
+            ("{file}}", "}"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(StatusTemplate::parse(template), Err(expected.to_string()), "{template:?}");
+        }
+    }
+
+    #[test]
+    fn invalid_template_names_the_part_in_config_errors() {
+        assert_eq!(
+            StatusTemplate::try_from("{oops".to_string()),
+            Err("status template: {oops".to_string())
+        );
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn invalid_template_in_config_falls_back_to_the_default() {
+        use crate::editor::config::Config;
+        let parsed: Config = toml::from_str("status-left = \"{line}:{col}\"").unwrap();
+        assert_eq!(parsed.status_left.render(&status()), "3:5");
+        // 无效的模板使整份配置被拒绝，编辑器改用默认设置，状态栏为默认模板
+        let err = toml::from_str::<Config>("status-left = \"{oops\"").err().unwrap();
+        assert!(err.message().contains("status template: {oops"), "{}", err.message());
+        assert_eq!(Config::default().status_left, StatusTemplate::default_left());
+        assert!(Config::default().status_left.render(&status()).starts_with("a.rs ["));
+    }
+}
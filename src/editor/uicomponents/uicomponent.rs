@@ -3,6 +3,8 @@
 use crate::prelude::*;
 use std::io::Error;
 
+use super::super::Screen;
+
 pub trait UIComponent {
     // 标记此 UI 组件需要重绘（或不需要）
     fn set_needs_redraw(&mut self, value: bool);
@@ -18,9 +20,9 @@ pub trait UIComponent {
     fn set_size(&mut self, size: Size);
 
     // 如果组件可见且需要重绘，则绘制此组件
-    fn render(&mut self, origin_row: RowIdx) {
+    fn render(&mut self, screen: &mut dyn Screen, origin_row: RowIdx) {
         if self.needs_redraw() {
-            if let Err(err) = self.draw(origin_row) {
+            if let Err(err) = self.draw(screen, origin_row) {
                 #[cfg(debug_assertions)]
                 {
                     panic!("无法渲染组件: {err:?}");
@@ -35,6 +37,6 @@ pub trait UIComponent {
         }
     }
     // 实际绘制组件的方法，必须由每个组件实现
-    fn draw(&mut self, origin_row: RowIdx) -> Result<(), Error>;
+    fn draw(&mut self, screen: &mut dyn Screen, origin_row: RowIdx) -> Result<(), Error>;
 }
 
@@ -2,11 +2,84 @@
 
 
 use super::FileInfo;
-use super::Line;
+use super::history::{Change, History, InLineChange, LinesChange};
+use super::lazylines::{LazyLines, LineRef};
+use super::safewrite;
+use super::{Line, LineEnding};
 use crate::prelude::*;
-use std::fs::{read_to_string, File};
-use std::io::Error;
-use std::io::Write;
+use std::cmp::min;
+use std::collections::HashSet;
+use std::fs::{metadata, read, File, OpenOptions};
+use std::io::{Error, ErrorKind, Read};
+use std::ops::{Range, RangeInclusive};
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 判断是否为二进制文件时检查的开头字节数
+const BINARY_SAMPLE_LEN: usize = 8192;
+/// 开头的字节中 NUL 超过这一比例（1/N）时视为二进制文件
+const BINARY_NUL_RATIO: usize = 10;
+
+/// 文件达到这一字节数时按需读取各行（只读打开），不把整个文件读进内存
+const LARGE_FILE_THRESHOLD: u64 = 64 << 20;
+
+/// 括号：种类（以对应的左括号表示）及是否为左括号
+#[derive(Copy, Clone)]
+struct Bracket {
+    kind: char,
+    is_open: bool,
+}
+
+impl Bracket {
+    /// 识别字素是否为括号
+    fn from_grapheme(grapheme: &str) -> Option<Self> {
+        let (kind, is_open) = match grapheme {
+            "(" => ('(', true),
+            ")" => ('(', false),
+            "[" => ('[', true),
+            "]" => ('[', false),
+            "{" => ('{', true),
+            "}" => ('{', false),
+            _ => return None,
+        };
+        Some(Self { kind, is_open })
+    }
+}
+
+/// 若干行的词数、字符数（按字素计）和字节数（不含换行符）之和
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+struct LineCounts {
+    words: usize,
+    chars: usize,
+    bytes: usize,
+}
+
+impl LineCounts {
+    /// 统计给定各行
+    fn of(lines: &[Line]) -> Self {
+        lines.iter().fold(Self::default(), |counts, line| Self {
+            words: counts.words.saturating_add(line.word_count()),
+            chars: counts.chars.saturating_add(line.grapheme_count()),
+            bytes: counts.bytes.saturating_add(line.len()),
+        })
+    }
+    /// 统计一行中 window 范围内的词数，以及整行的字符数和字节数
+    fn around(line: &Line, window: Range<ByteIdx>) -> Self {
+        Self {
+            words: line[window].unicode_words().count(),
+            chars: line.grapheme_count(),
+            bytes: line.len(),
+        }
+    }
+    /// 把一段行的统计从 removed 换成 inserted
+    fn replace(self, removed: Self, inserted: Self) -> Self {
+        Self {
+            words: self.words.saturating_sub(removed.words).saturating_add(inserted.words),
+            chars: self.chars.saturating_sub(removed.chars).saturating_add(inserted.chars),
+            bytes: self.bytes.saturating_sub(removed.bytes).saturating_add(inserted.bytes),
+        }
+    }
+}
 
 /// 文本缓冲区，管理所有文本行、文件信息和脏标记
 #[derive(Default)]
@@ -14,6 +87,15 @@ pub struct Buffer {
     lines: Vec<Line>,      // 文本行集合
     file_info: FileInfo,   // 文件信息
     dirty: bool,           // 是否有未保存修改
+    line_ending: LineEnding, // 保存时使用的换行符
+    mixed_line_endings: bool, // 加载的文件混用了 LF 和 CRLF，保存时统一为 line_ending
+    read_only: bool,       // 是否只读（文件不可写）
+    lossy: bool,           // 文件不是有效的 UTF-8，无法解码的字节已替换为 U+FFFD
+    lazy: Option<LazyLines>, // 很大的文件按需读取各行，此时 lines 为空且只读
+    missing_final_newline: bool, // 加载的文件末尾没有换行符，保存时同样不写
+    revision: usize,       // 内容每次修改时递增，用于判断缓存是否过期
+    history: History,      // 撤销和重做记录
+    counts: Option<LineCounts>, // 全文统计，首次使用时计算，之后只按修改的行增量更新
 }
 
 impl Buffer {
@@ -21,40 +103,261 @@ impl Buffer {
     pub const fn is_dirty(&self) -> bool {
         self.dirty
     }
+    /// 标记内容已修改
+    fn mark_modified(&mut self) {
+        self.dirty = true;
+        self.revision = self.revision.wrapping_add(1);
+    }
+    /// 修改从 lines.start 开始的一段行：change 可以修改这些行，也可以在其中插入或删除行，
+    /// 但不能改动这段之外的行。修改前后这段的内容记入撤销记录，内容有变化时标记为已修改
+    fn change_lines(&mut self, lines: Range<LineIdx>, change: impl FnOnce(&mut Vec<Line>)) {
+        // 按需读取的大文件只读，lines 中没有内容
+        if self.lazy.is_some() {
+            return;
+        }
+        let height = self.height();
+        let start = min(lines.start, height);
+        let end = lines.end.clamp(start, height);
+        let removed: Vec<String> = self.lines[start..end].iter().map(ToString::to_string).collect();
+        let removed_counts = self.counts.map(|_| LineCounts::of(&self.lines[start..end]));
+        change(&mut self.lines);
+        let inserted_count = removed.len().saturating_add(self.height()).saturating_sub(height);
+        let inserted_lines = start..start.saturating_add(inserted_count);
+        let inserted: Vec<String> = self.lines[inserted_lines.clone()]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        if let (Some(counts), Some(removed_counts)) = (self.counts, removed_counts) {
+            let inserted_counts = LineCounts::of(&self.lines[inserted_lines]);
+            self.counts = Some(counts.replace(removed_counts, inserted_counts));
+        }
+        if removed != inserted {
+            self.history.record(Change::Lines(LinesChange {
+                line_idx: start,
+                removed,
+                inserted,
+            }));
+            self.mark_modified();
+        }
+    }
+    /// 将第 line_idx 行中字节范围 range 内的内容替换为 text，行数不变。
+    /// 撤销记录只保存被替换的一段，不像 change_lines 那样复制整行
+    fn change_in_line(&mut self, line_idx: LineIdx, range: Range<ByteIdx>, text: &str) {
+        if self.lazy.is_some() {
+            return;
+        }
+        let Some(line) = self.lines.get(line_idx) else {
+            return;
+        };
+        let removed = line[range.clone()].to_string();
+        if removed == text {
+            return;
+        }
+        self.replace_in_line(line_idx, range.clone(), text);
+        self.history.record(Change::InLine(InLineChange {
+            line_idx,
+            start: range.start,
+            removed,
+            inserted: text.to_string(),
+        }));
+        self.mark_modified();
+    }
+    /// 将上一次结束以来的修改合为一个撤销步骤，coalesce 为真时允许与上一步连续输入的字符合并
+    pub fn finish_undo_step(&mut self, caret_before: Location, caret_after: Location, coalesce: bool) {
+        self.history.finish_step(caret_before, caret_after, coalesce);
+    }
+    /// 撤销最近一步，返回这一步之前的光标位置；没有可撤销的步骤时返回 None
+    pub fn undo(&mut self) -> Option<Location> {
+        let step = self.history.pop_undo()?;
+        for change in step.changes.iter().rev() {
+            match change {
+                Change::Lines(change) => {
+                    self.replace_lines(change.line_idx, change.inserted.len(), &change.removed);
+                }
+                Change::InLine(change) => {
+                    let end = change.start.saturating_add(change.inserted.len());
+                    self.replace_in_line(change.line_idx, change.start..end, &change.removed);
+                }
+            }
+        }
+        let caret = step.caret_before;
+        self.history.push_redo(step);
+        self.after_history_move();
+        Some(caret)
+    }
+    /// 重做最近撤销的一步，返回这一步之后的光标位置；没有可重做的步骤时返回 None
+    pub fn redo(&mut self) -> Option<Location> {
+        let step = self.history.pop_redo()?;
+        for change in &step.changes {
+            match change {
+                Change::Lines(change) => {
+                    self.replace_lines(change.line_idx, change.removed.len(), &change.inserted);
+                }
+                Change::InLine(change) => {
+                    let end = change.start.saturating_add(change.removed.len());
+                    self.replace_in_line(change.line_idx, change.start..end, &change.inserted);
+                }
+            }
+        }
+        let caret = step.caret_after;
+        self.history.push_undo(step);
+        self.after_history_move();
+        Some(caret)
+    }
+    /// 用 replacement 替换从 line_idx 开始的 count 行
+    fn replace_lines(&mut self, line_idx: LineIdx, count: usize, replacement: &[String]) {
+        let end = min(line_idx.saturating_add(count), self.lines.len());
+        let replacement: Vec<Line> = replacement.iter().map(|line| Line::from(line)).collect();
+        if let Some(counts) = self.counts {
+            let removed_counts = LineCounts::of(&self.lines[line_idx..end]);
+            self.counts = Some(counts.replace(removed_counts, LineCounts::of(&replacement)));
+        }
+        self.lines.splice(line_idx..end, replacement);
+    }
+    /// 将第 line_idx 行中字节范围 range 内的内容替换为 text，不记入撤销记录
+    fn replace_in_line(&mut self, line_idx: LineIdx, range: Range<ByteIdx>, text: &str) {
+        let Some(line) = self.lines.get_mut(line_idx) else {
+            return;
+        };
+        let Some(counts) = self.counts else {
+            line.replace_bytes(range, text);
+            return;
+        };
+        // 词不会跨过空白：只统计修改处向两侧扩展到空白为止的一段，长行中输入时不必重新统计整行
+        let window_start = line[..range.start].rfind([' ', '\t']).unwrap_or(0);
+        let window_end = line[range.end..]
+            .find([' ', '\t'])
+            .map_or(line.len(), |idx| range.end.saturating_add(idx));
+        let removed_counts = LineCounts::around(line, window_start..window_end);
+        let window_end = window_end.saturating_sub(range.len()).saturating_add(text.len());
+        line.replace_bytes(range, text);
+        let inserted_counts = LineCounts::around(line, window_start..window_end);
+        self.counts = Some(counts.replace(removed_counts, inserted_counts));
+    }
+    /// 撤销或重做之后更新修改次数，回到保存时的内容时清除未保存标记
+    fn after_history_move(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+        self.dirty = !self.history.is_at_saved();
+    }
+    /// 获取内容的修改次数，内容相同的两次调用之间返回值不变
+    pub const fn revision(&self) -> usize {
+        self.revision
+    }
     /// 获取文件信息
     pub const fn get_file_info(&self) -> &FileInfo {
         &self.file_info
     }
     /// 获取指定行的字素数
     pub fn grapheme_count(&self, idx: LineIdx) -> GraphemeIdx {
-        self.lines.get(idx).map_or(0, Line::grapheme_count)
+        self.get_line(idx).map_or(0, |line| line.grapheme_count())
     }
     /// 获取指定行到某字素的宽度
-    pub fn width_until(&self, idx: LineIdx, until: GraphemeIdx) -> GraphemeIdx {
-        self.lines
-            .get(idx)
-            .map_or(0, |line| line.width_until(until))
+    pub fn width_until(&self, idx: LineIdx, until: GraphemeIdx, tab_width: ColIdx) -> ColIdx {
+        self.get_line(idx)
+            .map_or(0, |line| line.width_until(until, tab_width))
+    }
+    /// 获取指定行中覆盖某列的字素索引
+    pub fn grapheme_idx_at_col(&self, idx: LineIdx, col: ColIdx, tab_width: ColIdx) -> GraphemeIdx {
+        self.get_line(idx)
+            .map_or(0, |line| line.grapheme_idx_at_col(col, tab_width))
     }
-    /// 加载文件内容到缓冲区
+    /// 加载文件内容到缓冲区；文件不存在时返回关联该文件名的空缓冲区，保存时直接写入该文件
     pub fn load(file_name: &str) -> Result<Self, Error> {
-        let contents = read_to_string(file_name)?;
+        if metadata(file_name).is_ok_and(|metadata| metadata.len() >= LARGE_FILE_THRESHOLD) {
+            return Self::load_large(file_name);
+        }
+        let bytes = match read(file_name) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == ErrorKind::NotFound => {
+                return Ok(Self {
+                    file_info: FileInfo::from(file_name),
+                    ..Self::default()
+                });
+            }
+            Err(err) => return Err(err),
+        };
+        if Self::is_binary(&bytes) {
+            return Err(Error::new(ErrorKind::InvalidData, "binary file"));
+        }
+        // 不是有效的 UTF-8 时按有损方式解码，并以只读打开，避免保存时悄悄替换原有的字节
+        let (contents, lossy) = match String::from_utf8(bytes) {
+            Ok(contents) => (contents, false),
+            Err(err) => (String::from_utf8_lossy(err.as_bytes()).into_owned(), true),
+        };
         let mut lines = Vec::new();
         for value in contents.lines() {
             lines.push(Line::from(value));
         }
+        let (line_ending, mixed_line_endings) = LineEnding::detect(&contents);
         Ok(Self {
             lines,
             file_info: FileInfo::from(file_name),
             dirty: false,
+            line_ending,
+            mixed_line_endings,
+            read_only: lossy || !Self::is_writable(file_name),
+            lossy,
+            lazy: None,
+            missing_final_newline: !contents.is_empty() && !contents.ends_with('\n'),
+            revision: 0,
+            history: History::default(),
+            counts: None,
         })
     }
+    /// 只读打开很大的文件：只建立行的索引，各行在显示或查找时才读取。
+    /// 二进制判断和换行符检测只看文件开头的一段
+    fn load_large(file_name: &str) -> Result<Self, Error> {
+        let mut sample = Vec::new();
+        File::open(file_name)?
+            .take(BINARY_SAMPLE_LEN as u64)
+            .read_to_end(&mut sample)?;
+        if Self::is_binary(&sample) {
+            return Err(Error::new(ErrorKind::InvalidData, "binary file"));
+        }
+        let (line_ending, mixed_line_endings) = LineEnding::detect(&String::from_utf8_lossy(&sample));
+        Ok(Self {
+            file_info: FileInfo::from(file_name),
+            line_ending,
+            mixed_line_endings,
+            read_only: true,
+            lazy: Some(LazyLines::open(Path::new(file_name))?),
+            ..Self::default()
+        })
+    }
+    /// 判断是否为按需读取的大文件
+    pub const fn is_large_file(&self) -> bool {
+        self.lazy.is_some()
+    }
+    /// 由若干行文本构建未关联文件的缓冲区
+    pub fn from_lines(lines: &[String]) -> Self {
+        Self {
+            lines: lines.iter().map(|line| Line::from(line)).collect(),
+            ..Self::default()
+        }
+    }
+    /// 开头的字节中 NUL 较多时视为二进制文件
+    fn is_binary(bytes: &[u8]) -> bool {
+        let sample = &bytes[..min(bytes.len(), BINARY_SAMPLE_LEN)];
+        let nul_count = sample.iter().filter(|&&byte| byte == 0).count();
+        nul_count > 0 && nul_count.saturating_mul(BINARY_NUL_RATIO) > sample.len()
+    }
+    /// 检查文件是否可写：以追加方式打开不会修改文件内容
+    fn is_writable(file_name: &str) -> bool {
+        OpenOptions::new().append(true).open(file_name).is_ok()
+    }
     /// 保存内容到指定文件
-    fn save_to_file(&self, file_info: &FileInfo) -> Result<(), Error> {
+    fn save_to_file(&self, file_info: &FileInfo, backup: bool) -> Result<(), Error> {
         if let Some(file_path) = &file_info.get_path() {
-            let mut file = File::create(file_path)?;
-            for line in &self.lines {
-                writeln!(file, "{line}")?;
+            let line_ending = self.line_ending.as_str();
+            let last_idx = self.lines.len().saturating_sub(1);
+            let mut contents = String::new();
+            for (line_idx, line) in self.lines.iter().enumerate() {
+                contents.push_str(&line.to_string());
+                if line_idx != last_idx || !self.missing_final_newline {
+                    contents.push_str(line_ending);
+                }
             }
+            safewrite::write_file(file_path, contents.as_bytes(), backup)?;
         } else {
             #[cfg(debug_assertions)]
             {
@@ -63,23 +366,57 @@ impl Buffer {
         }
         Ok(())
     }
-    /// 另存为新文件
-    pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
+    /// 另存为新文件，backup 为真时先把目标文件原有的内容保存为 .bak
+    pub fn save_as(&mut self, file_name: &str, backup: bool) -> Result<(), Error> {
         let file_info = FileInfo::from(file_name);
-        self.save_to_file(&file_info)?;
+        self.save_to_file(&file_info, backup)?;
         self.file_info = file_info;
         self.dirty = false;
+        self.history.mark_saved();
+        self.read_only = false;
+        self.lossy = false;
         Ok(())
     }
-    /// 保存到当前文件
-    pub fn save(&mut self) -> Result<(), Error> {
-        self.save_to_file(&self.file_info)?;
+    /// 保存到当前文件，backup 为真时先把原有的内容保存为 .bak
+    pub fn save(&mut self, backup: bool) -> Result<(), Error> {
+        self.save_to_file(&self.file_info, backup)?;
         self.dirty = false;
+        self.history.mark_saved();
+        self.lossy = false;
         Ok(())
     }
-    /// 判断缓冲区是否为空
-    pub fn is_empty(&self) -> bool {
-        self.lines.is_empty()
+    /// 之后保存时在最后一行末尾写入换行符，即使加载的文件原本没有
+    pub fn add_final_newline(&mut self) {
+        self.missing_final_newline = false;
+    }
+    /// 获取保存时使用的换行符
+    pub const fn line_ending(&self) -> LineEnding {
+        self.line_ending
+    }
+    /// 判断加载的文件是否混用了两种换行符
+    pub const fn has_mixed_line_endings(&self) -> bool {
+        self.mixed_line_endings
+    }
+    /// 更改保存时使用的换行符，标记为已修改
+    pub fn set_line_ending(&mut self, line_ending: LineEnding) {
+        if self.line_ending != line_ending {
+            self.line_ending = line_ending;
+            self.history.forget_saved();
+            self.mark_modified();
+        }
+    }
+    /// 判断是否只读
+    pub const fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    /// 判断文件是否按有损方式解码（不是有效的 UTF-8）
+    pub const fn is_lossy(&self) -> bool {
+        self.lossy
+    }
+    /// 设置是否只读
+    pub fn set_read_only(&mut self, read_only: bool) {
+        // 按需读取的大文件始终只读
+        self.read_only = read_only || self.lazy.is_some();
     }
     /// 判断是否已加载文件
     pub const fn is_file_loaded(&self) -> bool {
@@ -87,47 +424,832 @@ impl Buffer {
     }
     /// 获取文本行数
     pub fn height(&self) -> LineIdx {
-        self.lines.len()
+        self.lazy.as_ref().map_or(self.lines.len(), LazyLines::height)
+    }
+    /// 按出现顺序返回全文中以 prefix 开头（且不等于 prefix）的不重复单词，
+    /// 单词由字母、数字和下划线组成
+    pub fn words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut words = Vec::new();
+        for line in &self.lines {
+            let text: &str = line;
+            for word in text.split(|character: char| !is_word_char(character)) {
+                if word.len() > prefix.len() && word.starts_with(prefix) && seen.insert(word) {
+                    words.push(word.to_string());
+                }
+            }
+        }
+        words
+    }
+    /// 全文各行的统计：第一次调用时扫描全文，之后由修改时的增量更新维护
+    fn line_counts(&mut self) -> LineCounts {
+        *self.counts.get_or_insert_with(|| LineCounts::of(&self.lines))
+    }
+    /// 统计全文的词数
+    pub fn word_count(&mut self) -> usize {
+        self.line_counts().words
+    }
+    /// 查找缩进中混用制表符和空格的情况：返回能确定混用的第一行，
+    /// 即同一行缩进中两者都有，或与前面某行使用的缩进字符不同的行；没有混用时返回 None
+    pub fn find_mixed_indentation(&self) -> Option<LineIdx> {
+        let mut seen_tab = false;
+        let mut seen_space = false;
+        self.lines.iter().position(|line| {
+            let indentation = line.indentation();
+            seen_tab |= indentation.contains('\t');
+            seen_space |= indentation.contains(' ');
+            seen_tab && seen_space
+        })
+    }
+    /// 统计全文的字符数（按字素计，不含换行）
+    pub fn char_count(&mut self) -> usize {
+        self.line_counts().chars
+    }
+    /// 统计保存时写入的字节数（UTF-8 编码，含换行符）
+    pub fn byte_count(&mut self) -> usize {
+        if let Some(lazy) = &self.lazy {
+            return usize::try_from(lazy.len()).unwrap_or(usize::MAX);
+        }
+        let line_endings = if self.missing_final_newline {
+            self.height().saturating_sub(1)
+        } else {
+            self.height()
+        };
+        self.line_counts()
+            .bytes
+            .saturating_add(line_endings.saturating_mul(self.line_ending.as_str().len()))
     }
     /// 在指定位置插入字符
     pub fn insert_char(&mut self, character: char, at: Location) {
         debug_assert!(at.line_idx <= self.height());
-        if at.line_idx == self.height() {
-            self.lines.push(Line::from(&character.to_string()));
-            self.dirty = true;
-        } else if let Some(line) = self.lines.get_mut(at.line_idx) {
-            line.insert_char(character, at.grapheme_idx);
-            self.dirty = true;
+        if let Some(line) = self.lines.get(at.line_idx) {
+            let start = line.text_range(0..at.grapheme_idx).len();
+            self.change_in_line(at.line_idx, start..start, character.encode_utf8(&mut [0; 4]));
+            return;
         }
+        self.change_lines(at.line_idx..at.line_idx.saturating_add(1), |lines| {
+            if at.line_idx == lines.len() {
+                lines.push(Line::from(&character.to_string()));
+            }
+        });
+    }
+    /// 在若干行的同一列插入字符，行宽不足该列时先用空格补齐
+    pub fn insert_in_column(
+        &mut self,
+        lines: RangeInclusive<LineIdx>,
+        col: ColIdx,
+        character: char,
+        tab_width: ColIdx,
+    ) {
+        let range = *lines.start()..lines.end().saturating_add(1);
+        self.change_lines(range, |all_lines| {
+            for line_idx in lines {
+                let Some(line) = all_lines.get_mut(line_idx) else {
+                    break;
+                };
+                for _ in line.width(tab_width)..col {
+                    line.append_char(' ');
+                }
+                let at = line.grapheme_idx_at_col(col, tab_width);
+                line.insert_char(character, at);
+            }
+        });
+    }
+    /// 删除若干行中位于 [from_col, to_col) 列范围内的字素
+    pub fn delete_in_columns(&mut self, lines: RangeInclusive<LineIdx>, cols: Range<ColIdx>, tab_width: ColIdx) {
+        for line_idx in lines {
+            let Some(line) = self.lines.get(line_idx) else {
+                break;
+            };
+            let start = line.grapheme_idx_at_col(cols.start, tab_width);
+            let end = line.grapheme_idx_at_col(cols.end, tab_width);
+            self.delete_range(
+                Location { line_idx, grapheme_idx: start },
+                Location { line_idx, grapheme_idx: end },
+            );
+        }
+    }
+    /// 返回若干行中位于 [from_col, to_col) 列范围内的文本，各行之间以换行符分隔
+    pub fn text_in_columns(&self, lines: RangeInclusive<LineIdx>, cols: Range<ColIdx>, tab_width: ColIdx) -> String {
+        lines
+            .filter_map(|line_idx| self.get_line(line_idx))
+            .map(|line| {
+                let start = line.grapheme_idx_at_col(cols.start, tab_width);
+                let end = line.grapheme_idx_at_col(cols.end, tab_width);
+                line.text_range(start..end).to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// 返回 start 与 end 之间的文本（不分先后），各行之间以换行符分隔
+    pub fn text_between(&self, start: Location, end: Location) -> String {
+        let (start, end) = start.ordered(end);
+        (start.line_idx..=end.line_idx)
+            .filter_map(|line_idx| {
+                let line = self.get_line(line_idx)?;
+                let from = if line_idx == start.line_idx { start.grapheme_idx } else { 0 };
+                let to = if line_idx == end.line_idx {
+                    end.grapheme_idx
+                } else {
+                    line.grapheme_count()
+                };
+                Some(line.text_range(from..to).to_string())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// 返回全文，每行以换行符结尾
+    pub fn text(&self) -> String {
+        self.lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+    /// 用给定文本替换全文，内容有变化时标记为已修改
+    pub fn replace_text(&mut self, text: &str) {
+        let new_lines: Vec<Line> = text.lines().map(Line::from).collect();
+        self.change_lines(0..self.height(), |lines| *lines = new_lines);
+    }
+    /// 在指定位置原样插入一段文本（可包含换行），返回插入内容末尾的位置
+    pub fn insert_str(&mut self, text: &str, at: Location) -> Location {
+        debug_assert!(at.line_idx <= self.height());
+        if text.is_empty() {
+            return at;
+        }
+        let normalized = text.replace("\r\n", "\n").replace('\r', "\n");
+        let mut end = at;
+        self.change_lines(at.line_idx..at.line_idx.saturating_add(1), |lines| {
+            if at.line_idx == lines.len() {
+                lines.push(Line::default());
+            }
+            let mut segments = normalized.split('\n');
+            let mut line_idx = at.line_idx;
+            let Some(line) = lines.get_mut(line_idx) else {
+                return;
+            };
+            let tail = line.split(at.grapheme_idx);
+            if let Some(first) = segments.next() {
+                line.append(&Line::from(first));
+            }
+            for segment in segments {
+                line_idx = line_idx.saturating_add(1);
+                lines.insert(line_idx, Line::from(segment));
+            }
+            let last_line = &mut lines[line_idx];
+            end = Location {
+                grapheme_idx: last_line.grapheme_count(),
+                line_idx,
+            };
+            last_line.append(&tail);
+        });
+        end
+    }
+    /// 删除指定位置字素的最后一个码位
+    pub fn delete_last_codepoint(&mut self, at: Location) {
+        let Some(line) = self.lines.get(at.line_idx) else {
+            return;
+        };
+        let Some((grapheme, _)) = line.grapheme_at(at.grapheme_idx) else {
+            return;
+        };
+        let end = line.text_range(0..at.grapheme_idx).len().saturating_add(grapheme.len());
+        let last_len = grapheme.chars().last().map_or(0, char::len_utf8);
+        self.change_in_line(at.line_idx, end.saturating_sub(last_len)..end, "");
     }
     /// 在指定位置删除字符或合并行
     pub fn delete(&mut self, at: Location) {
+        // 行内删除一个字素时只记录这个字素，在行尾删除时与下一行合并
         if let Some(line) = self.lines.get(at.line_idx) {
-            if at.grapheme_idx >= line.grapheme_count()
-                && self.height() > at.line_idx.saturating_add(1)
-            {
-                let next_line = self.lines.remove(at.line_idx.saturating_add(1));
-                self.lines[at.line_idx].append(&next_line);
-                self.dirty = true;
-            } else if at.grapheme_idx < line.grapheme_count() {
-                self.lines[at.line_idx].delete(at.grapheme_idx);
-                self.dirty = true;
+            if let Some((grapheme, _)) = line.grapheme_at(at.grapheme_idx) {
+                let start = line.text_range(0..at.grapheme_idx).len();
+                let end = start.saturating_add(grapheme.len());
+                self.change_in_line(at.line_idx, start..end, "");
+                return;
+            }
+        }
+        self.change_lines(at.line_idx..at.line_idx.saturating_add(2), |lines| {
+            if at.line_idx.saturating_add(1) < lines.len() {
+                let next_line = lines.remove(at.line_idx.saturating_add(1));
+                lines[at.line_idx].append(&next_line);
             }
+        });
+    }
+    /// 删除 start 与 end 之间的内容（两者先后不限）。跨行时首行在 start 之前的部分
+    /// 与末行在 end 之后的部分拼接为一行，每个受影响的行只重建一次分片。
+    /// 返回删除后光标应在的位置，即范围的起点
+    pub fn delete_range(&mut self, start: Location, end: Location) -> Location {
+        let (start, end) = start.ordered(end);
+        let Some(start_line) = self.lines.get(start.line_idx) else {
+            return start;
+        };
+        let start = Location {
+            line_idx: start.line_idx,
+            grapheme_idx: min(start.grapheme_idx, start_line.grapheme_count()),
+        };
+        // 终点超出文件末尾时截到最后一行的行尾
+        let last_line_idx = self.height().saturating_sub(1);
+        let end = if end.line_idx > last_line_idx {
+            Location {
+                line_idx: last_line_idx,
+                grapheme_idx: self.grapheme_count(last_line_idx),
+            }
+        } else {
+            Location {
+                line_idx: end.line_idx,
+                grapheme_idx: min(end.grapheme_idx, self.grapheme_count(end.line_idx)),
+            }
+        };
+        if start >= end {
+            return start;
+        }
+        if start.line_idx == end.line_idx {
+            let line = &self.lines[start.line_idx];
+            let start_byte = line.text_range(0..start.grapheme_idx).len();
+            let end_byte = start_byte.saturating_add(line.text_range(start.grapheme_idx..end.grapheme_idx).len());
+            self.change_in_line(start.line_idx, start_byte..end_byte, "");
+            return start;
         }
+        self.change_lines(start.line_idx..end.line_idx.saturating_add(1), |lines| {
+            let tail = lines[end.line_idx].split(end.grapheme_idx);
+            lines.drain(start.line_idx.saturating_add(1)..=end.line_idx);
+            let line = &mut lines[start.line_idx];
+            line.remove_range(start.grapheme_idx..line.grapheme_count());
+            line.append(&tail);
+        });
+        start
+    }
+    /// 删除整行；只剩一行时清空该行
+    pub fn delete_line(&mut self, line_idx: LineIdx) {
+        if line_idx >= self.height() {
+            return;
+        }
+        self.change_lines(line_idx..line_idx.saturating_add(1), |lines| {
+            if lines.len() > 1 {
+                lines.remove(line_idx);
+            } else {
+                lines[line_idx] = Line::default();
+            }
+        });
+    }
+    /// 将 at 处的 query 替换为 replacement（at 须是 query 的一个匹配），返回替换内容之后的位置
+    pub fn replace_match(&mut self, at: Location, query: &str, replacement: &str) -> Location {
+        let Some(line) = self.lines.get(at.line_idx) else {
+            return at;
+        };
+        let start = line.text_range(0..at.grapheme_idx).len();
+        let end = line.text_range(0..line.grapheme_idx_at_byte(start.saturating_add(query.len()))).len();
+        self.change_in_line(at.line_idx, start..end, replacement);
+        let grapheme_idx = self.lines[at.line_idx].grapheme_idx_at_byte(start.saturating_add(replacement.len()));
+        Location { grapheme_idx, ..at }
+    }
+    /// 在指定行下方插入该行的副本
+    pub fn duplicate_line(&mut self, line_idx: LineIdx) {
+        if line_idx >= self.height() {
+            return;
+        }
+        self.change_lines(line_idx..line_idx.saturating_add(1), |lines| {
+            let copy = lines[line_idx].clone();
+            lines.insert(line_idx.saturating_add(1), copy);
+        });
     }
     /// 在指定位置插入换行
     pub fn insert_newline(&mut self, at: Location) {
-        if at.line_idx == self.height() {
-            self.lines.push(Line::default());
-            self.dirty = true;
-        } else if let Some(line) = self.lines.get_mut(at.line_idx) {
-            let new = line.split(at.grapheme_idx);
-            self.lines.insert(at.line_idx.saturating_add(1), new);
-            self.dirty = true;
-        }
-    }
-    /// 获取指定行的引用
-    pub fn get_line(&self, idx: usize) -> Option<&Line> {
-        self.lines.get(idx)
+        self.change_lines(at.line_idx..at.line_idx.saturating_add(1), |lines| {
+            // 空缓冲区中光标所在的第 0 行尚不存在，先补上再拆分
+            if at.line_idx == lines.len() {
+                lines.push(Line::default());
+            }
+            if let Some(line) = lines.get_mut(at.line_idx) {
+                let new = line.split(at.grapheme_idx);
+                lines.insert(at.line_idx.saturating_add(1), new);
+            }
+        });
+    }
+    /// 在指定行的行首插入 indent，返回插入的字素数
+    pub fn indent_line(&mut self, idx: LineIdx, indent: &str) -> GraphemeIdx {
+        let mut inserted = 0;
+        self.change_lines(idx..idx.saturating_add(1), |lines| {
+            if let Some(line) = lines.get_mut(idx) {
+                inserted = line.replace_range(0..0, indent);
+            }
+        });
+        inserted
+    }
+    /// 删除指定行行首的一个制表符或至多 tab_width 个空格，返回删除的字素数；没有缩进时不做修改
+    pub fn dedent_line(&mut self, idx: LineIdx, tab_width: usize) -> GraphemeIdx {
+        let Some(removed) = self.get_line(idx).map(|line| {
+            if line.starts_with('\t') {
+                1
+            } else {
+                line.chars().take(tab_width).take_while(|&character| character == ' ').count()
+            }
+        }) else {
+            return 0;
+        };
+        if removed > 0 {
+            self.change_lines(idx..idx.saturating_add(1), |lines| {
+                if let Some(line) = lines.get_mut(idx) {
+                    line.remove_range(0..removed);
+                }
+            });
+        }
+        removed
+    }
+    /// 将所有制表符替换为 tab_width 个空格，返回转换后 caret 所在字符的新位置
+    pub fn expand_tabs(&mut self, tab_width: usize, caret: Location) -> Location {
+        let spaces = " ".repeat(tab_width);
+        let mut new_caret = caret;
+        self.change_lines(0..self.height(), |lines| {
+            for (line_idx, line) in lines.iter_mut().enumerate() {
+                if !line.contains('\t') {
+                    continue;
+                }
+                if line_idx == caret.line_idx {
+                    let tabs_before_caret = (0..caret.grapheme_idx)
+                        .filter(|&idx| line.grapheme_at(idx).is_some_and(|(g, _)| g == "\t"))
+                        .count();
+                    new_caret.grapheme_idx = caret.grapheme_idx.saturating_add(
+                        tabs_before_caret.saturating_mul(tab_width.saturating_sub(1)),
+                    );
+                }
+                *line = Line::from(&line.replace('\t', &spaces));
+            }
+        });
+        new_caret
+    }
+    /// 将行首每 tab_width 个连续空格替换为一个制表符，返回转换后 caret 所在字符的新位置
+    pub fn unexpand_tabs(&mut self, tab_width: usize, caret: Location) -> Location {
+        let mut new_caret = caret;
+        self.change_lines(0..self.height(), |lines| {
+            for (line_idx, line) in lines.iter_mut().enumerate() {
+                let leading_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+                let (new_leading, mapping) = Self::unexpand_leading(&line[..leading_len], tab_width);
+                if new_leading == line[..leading_len] {
+                    continue;
+                }
+                if line_idx == caret.line_idx {
+                    // 行首空白的长度按字素计，光标在行首空白之后时随其长度变化平移
+                    let old_width = line[..leading_len].graphemes(true).count();
+                    let new_width = new_leading.graphemes(true).count();
+                    new_caret.grapheme_idx = mapping.get(caret.grapheme_idx).copied().unwrap_or_else(|| {
+                        caret.grapheme_idx.saturating_sub(old_width).saturating_add(new_width)
+                    });
+                }
+                *line = Line::from(&format!("{new_leading}{}", &line[leading_len..]));
+            }
+        });
+        new_caret
+    }
+    /// 转换行首空白：返回新的行首空白，以及旧索引（含末尾）到新索引的映射
+    fn unexpand_leading(leading: &str, tab_width: usize) -> (String, Vec<GraphemeIdx>) {
+        let mut result = String::new();
+        let mut mapping = Vec::with_capacity(leading.len().saturating_add(1));
+        let mut run_len: usize = 0;
+        // 末尾追加一个哨兵字符，保证最后一段空格也被处理
+        for character in leading.chars().chain(Some('\0')) {
+            if character == ' ' {
+                run_len = run_len.saturating_add(1);
+                continue;
+            }
+            let base = result.len();
+            let tabs = run_len / tab_width;
+            let converted = tabs.saturating_mul(tab_width);
+            for idx in 0..run_len {
+                mapping.push(if idx < converted {
+                    base + idx / tab_width
+                } else {
+                    base + tabs + idx - converted
+                });
+            }
+            result.push_str(&"\t".repeat(tabs));
+            result.push_str(&" ".repeat(run_len - converted));
+            run_len = 0;
+            if character == '\t' {
+                mapping.push(result.len());
+                result.push('\t');
+            }
+        }
+        mapping.push(result.len());
+        (result, mapping)
+    }
+    /// 将若干行整体上移一行，原来在它们上面的一行移到下面；已到文件开头时不动
+    pub fn move_lines_up(&mut self, lines: RangeInclusive<LineIdx>) {
+        let (first, last) = (*lines.start(), *lines.end());
+        if first > 0 && first <= last && last < self.height() {
+            self.change_lines(first - 1..last + 1, |all_lines| all_lines[first - 1..=last].rotate_left(1));
+        }
+    }
+    /// 将若干行整体下移一行，原来在它们下面的一行移到上面；已到文件末尾时不动
+    pub fn move_lines_down(&mut self, lines: RangeInclusive<LineIdx>) {
+        let (first, last) = (*lines.start(), *lines.end());
+        if first <= last && last.saturating_add(1) < self.height() {
+            self.change_lines(first..last + 2, |all_lines| all_lines[first..=last + 1].rotate_right(1));
+        }
+    }
+    /// 查找与 at 处括号匹配的括号位置。
+    /// 若 at 处不是括号，则先在当前行向后查找下一个括号（与 vim 的 % 一致）。
+    pub fn find_matching_bracket(&self, at: Location) -> Option<Location> {
+        let line = self.get_line(at.line_idx)?;
+        let (bracket_idx, bracket) = (at.grapheme_idx..line.grapheme_count()).find_map(|idx| {
+            line.grapheme_at(idx)
+                .and_then(|(grapheme, _)| Bracket::from_grapheme(grapheme))
+                .map(|bracket| (idx, bracket))
+        })?;
+        let start = Location {
+            grapheme_idx: bracket_idx,
+            line_idx: at.line_idx,
+        };
+        self.scan_for_partner(start, bracket, bracket.is_open)
+    }
+    /// 从 start 开始向前或向后扫描，按嵌套深度寻找配对括号
+    fn scan_for_partner(&self, start: Location, bracket: Bracket, forward: bool) -> Option<Location> {
+        let mut depth: usize = 0;
+        let mut line_idx = start.line_idx;
+        let mut next_idx = Some(start.grapheme_idx);
+        loop {
+            let line = self.get_line(line_idx)?;
+            let indices: Box<dyn Iterator<Item = GraphemeIdx>> = match (next_idx, forward) {
+                (Some(idx), true) => Box::new(idx..line.grapheme_count()),
+                (None, true) => Box::new(0..line.grapheme_count()),
+                (Some(idx), false) => Box::new((0..=idx).rev()),
+                (None, false) => Box::new((0..line.grapheme_count()).rev()),
+            };
+            for grapheme_idx in indices {
+                let Some(other) = line
+                    .grapheme_at(grapheme_idx)
+                    .and_then(|(grapheme, _)| Bracket::from_grapheme(grapheme))
+                else {
+                    continue;
+                };
+                if other.kind != bracket.kind {
+                    continue;
+                }
+                if other.is_open == bracket.is_open {
+                    depth = depth.saturating_add(1);
+                } else {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return Some(Location {
+                            grapheme_idx,
+                            line_idx,
+                        });
+                    }
+                }
+            }
+            next_idx = None;
+            line_idx = if forward {
+                line_idx.saturating_add(1)
+            } else {
+                line_idx.checked_sub(1)?
+            };
+        }
+    }
+    /// 返回一行中所有匹配 query 的位置，按出现顺序排列
+    fn matches_in(line: &Line, line_idx: LineIdx, query: &str) -> impl DoubleEndedIterator<Item = Location> {
+        line.match_grapheme_indices(query)
+            .into_iter()
+            .map(move |grapheme_idx| Location {
+                grapheme_idx,
+                line_idx,
+            })
+    }
+    /// 查找 from 处或其后第一个匹配 query 的位置，不回绕；按需读取的大文件顺序读取文件，
+    /// 只为包含 query 的行构建 Line
+    pub fn search_forward(&self, from: Location, query: &str) -> Option<Location> {
+        if let Some(lazy) = &self.lazy {
+            return lazy.lines_containing(from.line_idx, query).find_map(|line_idx| {
+                Self::matches_in(&*self.get_line(line_idx)?, line_idx, query)
+                    .find(|&location| location >= from)
+            });
+        }
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(from.line_idx)
+            .find_map(|(line_idx, line)| {
+                Self::matches_in(line, line_idx, query).find(|&location| location >= from)
+            })
+    }
+    /// 返回全文中所有匹配 query 的位置，按出现顺序排列；按需读取的大文件不统计，返回空列表
+    pub fn find_all(&self, query: &str) -> Vec<Location> {
+        self.lines
+            .iter()
+            .enumerate()
+            .flat_map(|(line_idx, line)| Self::matches_in(line, line_idx, query))
+            .collect()
+    }
+    /// 查找 from 之前最后一个匹配 query 的位置，不回绕
+    pub fn search_backward(&self, from: Location, query: &str) -> Option<Location> {
+        if let Some(lazy) = &self.lazy {
+            return lazy.lines_containing_rev(from.line_idx, query).find_map(|line_idx| {
+                Self::matches_in(&*self.get_line(line_idx)?, line_idx, query)
+                    .rev()
+                    .find(|&location| location < from)
+            });
+        }
+        self.lines
+            .iter()
+            .enumerate()
+            .take(from.line_idx.saturating_add(1))
+            .rev()
+            .find_map(|(line_idx, line)| {
+                Self::matches_in(line, line_idx, query)
+                    .rev()
+                    .find(|&location| location < from)
+            })
+    }
+    /// 获取指定行；按需读取的大文件从缓存或文件中取出该行
+    pub fn get_line(&self, idx: usize) -> Option<LineRef<'_>> {
+        match &self.lazy {
+            Some(lazy) => lazy.get(idx).map(LineRef::Cached),
+            None => self.lines.get(idx).map(LineRef::Loaded),
+        }
+    }
+}
+
+/// 判断字符是否属于单词（字母、数字或下划线）
+pub fn is_word_char(character: char) -> bool {
+    character.is_alphanumeric() || character == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::tempfile::TempFile;
+
+    fn buffer(lines: &[&str]) -> Buffer {
+        let lines: Vec<String> = lines.iter().map(|line| (*line).to_string()).collect();
+        Buffer::from_lines(&lines)
+    }
+
+    fn lines(buffer: &Buffer) -> Vec<String> {
+        (0..buffer.height())
+            .filter_map(|idx| buffer.get_line(idx).map(|line| line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn unexpand_tabs_shifts_caret_after_leading_whitespace() {
+        let mut buffer = buffer(&["        let 字 = 1;"]);
+        let caret = buffer.unexpand_tabs(4, Location { line_idx: 0, grapheme_idx: 13 });
+        assert_eq!(lines(&buffer), ["\t\tlet 字 = 1;"]);
+        assert_eq!(caret, Location { line_idx: 0, grapheme_idx: 7 });
+    }
+
+    #[test]
+    fn unexpand_tabs_maps_caret_inside_leading_whitespace() {
+        let mut buffer = buffer(&["      x"]);
+        let caret = buffer.unexpand_tabs(4, Location { line_idx: 0, grapheme_idx: 5 });
+        assert_eq!(lines(&buffer), ["\t  x"]);
+        assert_eq!(caret, Location { line_idx: 0, grapheme_idx: 2 });
+    }
+
+    #[test]
+    fn unexpand_tabs_leaves_caret_on_other_lines() {
+        let mut buffer = buffer(&["    a", "b"]);
+        let caret = buffer.unexpand_tabs(4, Location { line_idx: 1, grapheme_idx: 1 });
+        assert_eq!(lines(&buffer), ["\ta", "b"]);
+        assert_eq!(caret, Location { line_idx: 1, grapheme_idx: 1 });
+    }
+
+    #[test]
+    fn large_file_reads_lines_on_demand() {
+        let file = TempFile::new("large");
+        let path = file.name();
+        let contents: String = (0..50_000).map(|idx| format!("{idx}\tvalue\n")).collect();
+        std::fs::write(path, &contents).unwrap();
+        let buffer = Buffer::load_large(path).unwrap();
+        assert!(buffer.is_large_file() && buffer.is_read_only());
+        assert!(buffer.lines.is_empty());
+        assert_eq!(buffer.height(), 50_000);
+        assert_eq!(buffer.get_line(42_424).map(|line| line.to_string()).as_deref(), Some("42424\tvalue"));
+        assert_eq!(buffer.search_forward(at(0, 0), "49999"), Some(at(49_999, 0)));
+    }
+
+    fn at(line_idx: LineIdx, grapheme_idx: GraphemeIdx) -> Location {
+        Location { line_idx, grapheme_idx }
+    }
+
+    #[test]
+    fn undo_of_coalesced_typing_restores_lines_and_clears_dirty() {
+        let mut buffer = buffer(&["ab", "cd"]);
+        for (grapheme_idx, character) in "xyz".chars().enumerate() {
+            buffer.insert_char(character, at(0, grapheme_idx + 1));
+            buffer.finish_undo_step(at(0, grapheme_idx + 1), at(0, grapheme_idx + 2), true);
+        }
+        buffer.insert_newline(at(0, 4));
+        buffer.insert_char('!', at(1, 0));
+        buffer.finish_undo_step(at(0, 4), at(1, 1), false);
+        assert_eq!(lines(&buffer), ["axyz", "!b", "cd"]);
+        assert!(buffer.is_dirty());
+        assert_eq!(buffer.undo(), Some(at(0, 4)));
+        assert_eq!(lines(&buffer), ["axyzb", "cd"]);
+        assert!(buffer.is_dirty());
+        assert_eq!(buffer.undo(), Some(at(0, 1)));
+        assert_eq!(lines(&buffer), ["ab", "cd"]);
+        assert!(!buffer.is_dirty());
+        assert_eq!(buffer.undo(), None);
+        assert_eq!(buffer.redo(), Some(at(0, 4)));
+        assert_eq!(lines(&buffer), ["axyzb", "cd"]);
+        assert!(buffer.is_dirty());
+    }
+
+    #[test]
+    fn undo_of_in_line_edits_restores_combined_graphemes() {
+        let mut buffer = buffer(&["cafe!", "x"]);
+        // 组合重音符与前一个字素合并，行的字素数不变
+        buffer.insert_char('\u{301}', at(0, 4));
+        buffer.finish_undo_step(at(0, 4), at(0, 4), false);
+        buffer.delete(at(0, 3));
+        buffer.delete_last_codepoint(at(0, 0));
+        buffer.finish_undo_step(at(0, 3), at(0, 0), false);
+        buffer.replace_match(at(0, 0), "af", "AF");
+        buffer.finish_undo_step(at(0, 0), at(0, 2), false);
+        assert_eq!(lines(&buffer), ["AF!", "x"]);
+        buffer.undo();
+        assert_eq!(lines(&buffer), ["af!", "x"]);
+        buffer.undo();
+        assert_eq!(lines(&buffer), ["cafe\u{301}!", "x"]);
+        buffer.undo();
+        assert_eq!(lines(&buffer), ["cafe!", "x"]);
+        buffer.redo();
+        buffer.redo();
+        buffer.redo();
+        assert_eq!(lines(&buffer), ["AF!", "x"]);
+    }
+
+    #[test]
+    fn missing_final_newline_survives_edit_and_save() {
+        let file = TempFile::with_contents("no-final-newline", "first\nlast");
+        let path = file.name();
+        let mut buffer = Buffer::load(path).unwrap();
+        assert_eq!(lines(&buffer), ["first", "last"]);
+        buffer.insert_char('!', at(1, 4));
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"first\nlast!");
+        // 再次载入、不修改直接保存，内容逐字节不变
+        let mut buffer = Buffer::load(path).unwrap();
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"first\nlast!");
+    }
+
+    #[test]
+    fn final_newline_is_kept_or_added() {
+        let file = TempFile::with_contents("final-newline", "a\nb\n");
+        let path = file.name();
+        let mut buffer = Buffer::load(path).unwrap();
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"a\nb\n");
+        // 新建的缓冲区保存时以换行符结尾
+        buffer = self::buffer(&["x"]);
+        buffer.save_as(path, false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"x\n");
+        // 原本没有换行符的文件在要求后补上
+        std::fs::write(path, "a").unwrap();
+        let mut buffer = Buffer::load(path).unwrap();
+        buffer.add_final_newline();
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"a\n");
+    }
+
+    #[test]
+    fn word_and_char_counts_sum_over_lines() {
+        let mut buffer = buffer(&["Hello 世界", "🦀 ok", ""]);
+        // Hello、世、界、ok；字符按字素计，不含换行
+        assert_eq!(buffer.word_count(), 4);
+        assert_eq!(buffer.char_count(), 12);
+    }
+
+    #[test]
+    fn counts_after_single_char_edit_do_not_rescan_other_lines() {
+        let mut buffer = buffer(&["one two", "three"]);
+        assert_eq!(buffer.word_count(), 3);
+        // 绕过 change_lines 直接改第一行：如果编辑后重新扫描全文，词数会把这里的变化算进去
+        buffer.lines[0] = Line::from("one two four five");
+        buffer.insert_char(' ', at(1, 5));
+        buffer.insert_char('x', at(1, 6));
+        assert_eq!(buffer.word_count(), 4);
+        assert_eq!(buffer.char_count(), 7 + 7);
+    }
+
+    #[test]
+    fn incremental_counts_match_full_recount() {
+        let mut buffer = buffer(&["Hello 世界", "🦀 ok", ""]);
+        assert_eq!(buffer.byte_count(), 22);
+        buffer.insert_char('x', at(0, 5));
+        // 在单词中间插入空格把它拆成两个词，再输入与前一个字素组合的重音符
+        buffer.insert_char(' ', at(0, 2));
+        buffer.insert_char('\u{301}', at(0, 3));
+        buffer.delete(at(0, 7));
+        buffer.insert_newline(at(1, 1));
+        buffer.delete(at(2, 0));
+        buffer.finish_undo_step(at(0, 0), at(0, 0), false);
+        let full = |buffer: &Buffer| LineCounts::of(&buffer.lines);
+        assert_eq!(buffer.line_counts(), full(&buffer));
+        buffer.undo();
+        assert_eq!(buffer.line_counts(), full(&buffer));
+        buffer.redo();
+        assert_eq!(buffer.line_counts(), full(&buffer));
+    }
+
+    #[test]
+    fn load_and_save_round_trips_last_line_bytes() {
+        let file = TempFile::new("round-trip");
+        let path = file.name();
+        let cases: [&[u8]; 8] = [b"", b"\n", b"a", b"a\n", b"a\n\n", b"a\n\nb", b"a\r\nb", b"a\r\n\r\n"];
+        for contents in cases {
+            std::fs::write(path, contents).unwrap();
+            let mut buffer = Buffer::load(path).unwrap();
+            buffer.save(false).unwrap();
+            assert_eq!(std::fs::read(path).unwrap(), contents, "{:?}", String::from_utf8_lossy(contents));
+        }
+        // 编辑空的最后一行后仍以换行符结尾，不多出空行
+        std::fs::write(path, "a\n\n").unwrap();
+        let mut buffer = Buffer::load(path).unwrap();
+        buffer.insert_char('x', at(1, 0));
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"a\nx\n");
+    }
+
+    #[test]
+    fn crlf_fixture_keeps_crlf_after_edit_and_save() {
+        let fixture = include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/crlf.txt"));
+        let file = TempFile::with_contents("crlf", fixture);
+        let path = file.name();
+        let mut buffer = Buffer::load(path).unwrap();
+        assert_eq!(buffer.line_ending(), LineEnding::CrLf);
+        assert_eq!(lines(&buffer), ["first", "second", "", "last"]);
+        buffer.insert_char('!', at(0, 5));
+        buffer.insert_newline(at(1, 3));
+        buffer.insert_str("new", at(3, 0));
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"first!\r\nsec\r\nond\r\nnew\r\nlast\r\n");
+    }
+
+    #[test]
+    fn missing_file_opens_as_new_named_buffer() {
+        let file = TempFile::new("not-yet-created.txt");
+        let path = file.name();
+        let mut buffer = Buffer::load(path).unwrap();
+        assert!(buffer.is_file_loaded());
+        assert_eq!(buffer.height(), 0);
+        assert!(!buffer.is_dirty() && !buffer.is_read_only());
+        assert!(!std::path::Path::new(path).exists());
+        buffer.insert_char('x', at(0, 0));
+        buffer.save(false).unwrap();
+        assert_eq!(std::fs::read(path).unwrap(), b"x\n");
+    }
+
+    #[test]
+    fn directory_is_an_error_not_a_new_buffer() {
+        let file = TempFile::new("dir");
+        let path = file.name();
+        std::fs::create_dir_all(path).unwrap();
+        assert!(Buffer::load(path).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn permission_denied_is_an_error_not_a_new_buffer() {
+        use std::os::unix::fs::PermissionsExt;
+        let file = TempFile::with_contents("no-permission", "secret");
+        let path = file.name();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o000)).unwrap();
+        // root 不受文件权限限制，此时无法构造该错误
+        if std::fs::read(path).is_err() {
+            let err = Buffer::load(path).err().unwrap();
+            assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+        }
+    }
+
+    /// 写入测试文件并载入
+    fn load_bytes(name: &str, contents: &[u8]) -> Result<Buffer, Error> {
+        let file = TempFile::with_contents(name, contents);
+        Buffer::load(file.name())
+    }
+
+    #[test]
+    fn valid_utf8_loads_editable() {
+        let buffer = load_bytes("utf8", "héllo 字\n🦀\n".as_bytes()).unwrap();
+        assert_eq!(lines(&buffer), ["héllo 字", "🦀"]);
+        assert!(!buffer.is_lossy() && !buffer.is_read_only());
+    }
+
+    #[test]
+    fn invalid_utf8_loads_lossily_and_read_only() {
+        let buffer = load_bytes("invalid", b"ab\xffcd\n\xc3\n").unwrap();
+        assert_eq!(lines(&buffer), ["ab\u{fffd}cd", "\u{fffd}"]);
+        assert!(buffer.is_lossy() && buffer.is_read_only());
+    }
+
+    #[test]
+    fn nul_heavy_file_is_refused_as_binary() {
+        let mut contents = b"ELF".to_vec();
+        contents.extend(std::iter::repeat_n(0, 100));
+        let err = load_bytes("binary", &contents).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        // 偶尔出现的 NUL 不算二进制文件
+        let mut contents = "x".repeat(100).into_bytes();
+        contents.push(0);
+        let buffer = load_bytes("one-nul", &contents).unwrap();
+        assert_eq!(buffer.height(), 1);
+        assert!(!buffer.is_lossy());
     }
 }
@@ -0,0 +1,116 @@
+// DiffMarks 将缓冲区与磁盘上的文件逐行比较，记录每一行的改动类型，供编辑区左侧的标记栏显示。
+
+use similar::{capture_diff_slices, Algorithm, DiffTag};
+use std::cmp::min;
+
+use super::{Buffer, SearchDirection};
+use crate::editor::AnnotationType;
+use crate::prelude::*;
+
+/// 行的改动类型
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum LineChange {
+    Added,    // 新增的行
+    Modified, // 修改过的行
+    Removed,  // 此行之前（文件末尾时为此行之后）删除了行
+}
+
+impl LineChange {
+    /// 标记栏中显示的符号
+    pub const fn glyph(self) -> char {
+        match self {
+            Self::Added => '+',
+            Self::Modified => '~',
+            Self::Removed => '-',
+        }
+    }
+
+    /// 标记的显示样式
+    pub const fn annotation_type(self) -> AnnotationType {
+        match self {
+            Self::Added => AnnotationType::DiffAdded,
+            Self::Modified => AnnotationType::DiffModified,
+            Self::Removed => AnnotationType::DiffRemoved,
+        }
+    }
+}
+
+/// 缓冲区与磁盘版本的比较结果，缓冲区内容变化后在下一次绘制前重新计算
+pub struct DiffMarks {
+    disk_text: String,                // 磁盘上文件的内容
+    revision: usize,                  // 计算改动时缓冲区的修改次数
+    changes: Vec<Option<LineChange>>, // 缓冲区每一行的改动类型
+}
+
+impl DiffMarks {
+    /// 以磁盘内容为基准，计算缓冲区各行的改动
+    pub fn new(disk_text: String, buffer: &Buffer) -> Self {
+        let mut marks = Self {
+            disk_text,
+            revision: buffer.revision(),
+            changes: Vec::new(),
+        };
+        marks.recompute(buffer);
+        marks
+    }
+
+    /// 缓冲区保存后，以保存的内容作为新的磁盘版本
+    pub fn reset_to(&mut self, buffer: &Buffer) {
+        self.disk_text = buffer.text();
+        self.recompute(buffer);
+    }
+
+    /// 缓冲区内容变化时重新计算改动
+    pub fn refresh(&mut self, buffer: &Buffer) {
+        if self.revision != buffer.revision() {
+            self.recompute(buffer);
+        }
+    }
+
+    /// 逐行比较磁盘内容与缓冲区内容
+    fn recompute(&mut self, buffer: &Buffer) {
+        let buffer_text = buffer.text();
+        let old: Vec<&str> = self.disk_text.lines().collect();
+        let new: Vec<&str> = buffer_text.lines().collect();
+        let mut changes = vec![None; new.len()];
+        for op in capture_diff_slices(Algorithm::Myers, &old, &new) {
+            let (tag, _, new_range) = op.as_tag_tuple();
+            let change = match tag {
+                DiffTag::Equal => continue,
+                DiffTag::Insert => LineChange::Added,
+                DiffTag::Replace => LineChange::Modified,
+                DiffTag::Delete => {
+                    // 删除的行不在缓冲区中，标在其后的一行；删除发生在末尾时标在最后一行
+                    let line_idx = min(new_range.start, new.len().saturating_sub(1));
+                    if let Some(change @ None) = changes.get_mut(line_idx) {
+                        *change = Some(LineChange::Removed);
+                    }
+                    continue;
+                }
+            };
+            for line_idx in new_range {
+                changes[line_idx] = Some(change);
+            }
+        }
+        self.changes = changes;
+        self.revision = buffer.revision();
+    }
+
+    /// 指定行的改动类型
+    pub fn change_at(&self, line_idx: LineIdx) -> Option<LineChange> {
+        self.changes.get(line_idx).copied().flatten()
+    }
+
+    /// 查找 from 之后（或之前）下一处改动的第一行
+    pub fn next_change(&self, from: LineIdx, direction: SearchDirection) -> Option<LineIdx> {
+        let is_change_start = |line_idx: LineIdx| {
+            self.change_at(line_idx).is_some()
+                && (line_idx == 0 || self.change_at(line_idx - 1).is_none())
+        };
+        match direction {
+            SearchDirection::Forward => (from.saturating_add(1)..self.changes.len())
+                .find(|&line_idx| is_change_start(line_idx)),
+            SearchDirection::Backward => (0..from).rev().find(|&line_idx| is_change_start(line_idx)),
+        }
+    }
+}
@@ -5,20 +5,29 @@ use std::{
     path::{Path, PathBuf},
 };
 
-/// 文件信息结构体，保存文件路径
+use crate::editor::{messages, FileType};
+
+/// 文件信息结构体，保存文件路径和文件类型
 #[derive(Default, Debug)]
 pub struct FileInfo {
     path: Option<PathBuf>, // 文件路径
+    file_type: FileType,   // 由扩展名识别的文件类型
 }
 
 impl FileInfo {
     /// 通过文件名创建 FileInfo
     pub fn from(file_name: &str) -> Self {
         let path = PathBuf::from(file_name);
+        let file_type = FileType::from_path(&path);
         Self {
             path: Some(path),
+            file_type,
         }
     }
+    /// 获取文件类型
+    pub const fn get_file_type(&self) -> FileType {
+        self.file_type
+    }
     /// 获取文件路径
     pub fn get_path(&self) -> Option<&Path> {
         self.path.as_deref()
@@ -30,13 +39,13 @@ impl FileInfo {
 }
 
 impl Display for FileInfo {
-    /// 显示文件名（无文件则显示[default]）
+    /// 显示文件名（无文件则显示占位名）
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let name = self
             .get_path()
             .and_then(|path| path.file_name())
             .and_then(|name| name.to_str())
-            .unwrap_or("[default]");
+            .unwrap_or(messages::unnamed_file());
         write!(formatter, "{name}")
     }
 }
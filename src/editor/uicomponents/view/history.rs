@@ -0,0 +1,360 @@
+// History 记录缓冲区的修改，支持撤销和重做。
+// 每次修改只保存受影响的行在修改前后的内容，不复制整个文档；行内的修改只保存被替换的一段，
+// 在长行中输入时不必每次复制整行。一条命令中的所有修改合为一步，
+// 连续输入的字符合并为一步，直到换行、光标跳到别处或执行了其他编辑。
+// 同一步中相邻或重叠的修改合并为一次修改，连续输入时每一步只保存一份修改前和修改后的内容。
+
+use std::cmp::min;
+
+use crate::prelude::*;
+
+/// 最多保留的撤销步数，超出时丢弃最早的一步
+const MAX_STEPS: usize = 1000;
+
+/// 一次修改
+pub enum Change {
+    /// 从 line_idx 开始的 removed 行被替换为 inserted 行
+    Lines(LinesChange),
+    /// 一行中的一段被替换，行数不变
+    InLine(InLineChange),
+}
+
+/// 整行的修改：从 line_idx 开始的 removed 行被替换为 inserted 行
+pub struct LinesChange {
+    pub line_idx: LineIdx,     // 第一行受影响的行
+    pub removed: Vec<String>,  // 修改前这段的内容
+    pub inserted: Vec<String>, // 修改后这段的内容
+}
+
+/// 行内的修改：第 line_idx 行从字节 start 开始的 removed 被替换为 inserted。
+/// 按字节而不是字素定位，输入的组合字符与前一个字素合并时撤销也能准确还原
+pub struct InLineChange {
+    pub line_idx: LineIdx, // 修改的行
+    pub start: ByteIdx,    // 被替换的一段在行中的起始字节
+    pub removed: String,   // 修改前这段的内容
+    pub inserted: String,  // 修改后这段的内容
+}
+
+impl Change {
+    /// 将紧接着发生的 next 并入这次修改，能合并时返回 None，否则不修改并原样返回 next。
+    /// 整行的修改和行内的修改之间不合并
+    fn merge(&mut self, next: Self) -> Option<Self> {
+        match (self, next) {
+            (Self::Lines(change), Self::Lines(next)) => change.merge(next).map(Self::Lines),
+            (Self::InLine(change), Self::InLine(next)) => change.merge(next).map(Self::InLine),
+            (_, next) => Some(next),
+        }
+    }
+}
+
+impl LinesChange {
+    /// 将紧接着发生的 next 并入这次修改，两者涉及的行相邻或重叠时合并后返回 None，
+    /// 否则不修改并原样返回 next
+    fn merge(&mut self, next: Self) -> Option<Self> {
+        // 比较的都是 self 修改之后、next 修改之前的行号
+        let self_end = self.line_idx.saturating_add(self.inserted.len());
+        let next_end = next.line_idx.saturating_add(next.removed.len());
+        if next.line_idx > self_end || next_end < self.line_idx {
+            return Some(next);
+        }
+        let Self { line_idx, removed: mut next_removed, inserted: next_inserted } = next;
+        // next 覆盖了 self 之外的行时，这些行在 self 之前没有变化，补入修改前的内容；
+        // self 修改后的行中 next 没有覆盖的部分保留在修改后的内容中
+        let removed_tail = next_removed.split_off(self_end.saturating_sub(line_idx).min(next_removed.len()));
+        next_removed.truncate(self.line_idx.saturating_sub(line_idx));
+        let inserted_tail = self.inserted.split_off(next_end.saturating_sub(self.line_idx).min(self.inserted.len()));
+        self.inserted.truncate(line_idx.saturating_sub(self.line_idx));
+        self.inserted.extend(next_inserted);
+        self.inserted.extend(inserted_tail);
+        next_removed.append(&mut self.removed);
+        next_removed.extend(removed_tail);
+        self.removed = next_removed;
+        self.line_idx = self.line_idx.min(line_idx);
+        None
+    }
+}
+
+impl InLineChange {
+    /// 将紧接着发生的 next 并入这次修改，两者在同一行且涉及的字节范围相邻或重叠时合并后返回 None，
+    /// 否则不修改并原样返回 next
+    fn merge(&mut self, next: Self) -> Option<Self> {
+        // 比较的都是 self 修改之后、next 修改之前的字节位置
+        let self_end = self.start.saturating_add(self.inserted.len());
+        let next_end = next.start.saturating_add(next.removed.len());
+        if next.line_idx != self.line_idx || next.start > self_end || next_end < self.start {
+            return Some(next);
+        }
+        // 与整行的合并相同：next 替换了 self 之外的内容时补入修改前的内容，
+        // self 插入的内容中 next 没有覆盖的部分保留在修改后的内容中
+        let mut removed = next.removed[..self.start.saturating_sub(next.start)].to_string();
+        removed.push_str(&self.removed);
+        removed.push_str(&next.removed[min(self_end.saturating_sub(next.start), next.removed.len())..]);
+        let mut inserted = self.inserted[..next.start.saturating_sub(self.start)].to_string();
+        inserted.push_str(&next.inserted);
+        inserted.push_str(&self.inserted[min(next_end.saturating_sub(self.start), self.inserted.len())..]);
+        self.start = self.start.min(next.start);
+        self.removed = removed;
+        self.inserted = inserted;
+        None
+    }
+}
+
+/// 将修改追加到 changes 末尾，能与最后一次修改合并时合并
+fn push_change(changes: &mut Vec<Change>, change: Change) {
+    let unmerged = match changes.last_mut() {
+        Some(last) => last.merge(change),
+        None => Some(change),
+    };
+    changes.extend(unmerged);
+}
+
+/// 一个撤销步骤：按顺序发生的若干次修改，以及修改前后的光标位置
+pub struct Step {
+    pub changes: Vec<Change>,
+    pub caret_before: Location,
+    pub caret_after: Location,
+    coalescible: bool, // 之后连续输入的字符是否可以并入这一步
+}
+
+/// 撤销和重做记录
+pub struct History {
+    undo_steps: Vec<Step>, // 可撤销的步骤，最近的在末尾
+    redo_steps: Vec<Step>, // 撤销后可重做的步骤，最近撤销的在末尾
+    pending: Vec<Change>,  // 已发生但尚未归入步骤的修改
+    saved_depth: Option<usize>, // 上次保存时可撤销的步数，None 表示无法通过撤销重做回到保存时的内容
+}
+
+impl Default for History {
+    /// 新建或刚加载的缓冲区视为已保存的状态
+    fn default() -> Self {
+        Self {
+            undo_steps: Vec::new(),
+            redo_steps: Vec::new(),
+            pending: Vec::new(),
+            saved_depth: Some(0),
+        }
+    }
+}
+
+impl History {
+    /// 记录一次修改，等待归入步骤
+    pub fn record(&mut self, change: Change) {
+        push_change(&mut self.pending, change);
+    }
+
+    /// 将尚未归入步骤的修改合为一步。coalesce 为真且上一步也是连续输入、
+    /// 光标没有离开上一步结束的位置时并入上一步
+    pub fn finish_step(&mut self, caret_before: Location, caret_after: Location, coalesce: bool) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let changes = std::mem::take(&mut self.pending);
+        // 新的修改使撤销过的步骤无法再重做；保存时的内容若在其中，也无法再回到
+        if !self.redo_steps.is_empty() {
+            self.redo_steps.clear();
+            if self.saved_depth.is_some_and(|depth| depth > self.undo_steps.len()) {
+                self.saved_depth = None;
+            }
+        }
+        if let Some(last) = self.undo_steps.last_mut().filter(|last| {
+            coalesce && last.coalescible && last.caret_after == caret_before
+        }) {
+            for change in changes {
+                push_change(&mut last.changes, change);
+            }
+            last.caret_after = caret_after;
+            return;
+        }
+        self.undo_steps.push(Step {
+            changes,
+            caret_before,
+            caret_after,
+            coalescible: coalesce,
+        });
+        if self.undo_steps.len() > MAX_STEPS {
+            self.undo_steps.remove(0);
+            self.saved_depth = self.saved_depth.and_then(|depth| depth.checked_sub(1));
+        }
+    }
+
+    /// 取出最近一步用于撤销，撤销后需调用 push_redo 放回
+    pub fn pop_undo(&mut self) -> Option<Step> {
+        self.undo_steps.pop()
+    }
+
+    /// 取出最近撤销的一步用于重做，重做后需调用 push_undo 放回
+    pub fn pop_redo(&mut self) -> Option<Step> {
+        self.redo_steps.pop()
+    }
+
+    /// 放回撤销过的一步
+    pub fn push_redo(&mut self, mut step: Step) {
+        step.coalescible = false;
+        self.redo_steps.push(step);
+    }
+
+    /// 放回重做过的一步
+    pub fn push_undo(&mut self, step: Step) {
+        self.undo_steps.push(step);
+    }
+
+    /// 记录当前内容已保存；之后输入的字符不再并入保存前的一步
+    pub fn mark_saved(&mut self) {
+        self.saved_depth = Some(self.undo_steps.len());
+        if let Some(last) = self.undo_steps.last_mut() {
+            last.coalescible = false;
+        }
+    }
+
+    /// 记录无法通过撤销回到保存时的内容（如更改了换行符）
+    pub fn forget_saved(&mut self) {
+        self.saved_depth = None;
+    }
+
+    /// 判断当前内容是否与上次保存时相同
+    pub fn is_at_saved(&self) -> bool {
+        self.saved_depth == Some(self.undo_steps.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(line_idx: LineIdx, removed: &[&str], inserted: &[&str]) -> Change {
+        Change::Lines(LinesChange {
+            line_idx,
+            removed: removed.iter().map(ToString::to_string).collect(),
+            inserted: inserted.iter().map(ToString::to_string).collect(),
+        })
+    }
+
+    fn in_line(line_idx: LineIdx, start: ByteIdx, removed: &str, inserted: &str) -> Change {
+        Change::InLine(InLineChange {
+            line_idx,
+            start,
+            removed: removed.to_string(),
+            inserted: inserted.to_string(),
+        })
+    }
+
+    fn lines_change(change: &Change) -> &LinesChange {
+        match change {
+            Change::Lines(change) => change,
+            Change::InLine(_) => panic!("expected a whole-line change"),
+        }
+    }
+
+    fn in_line_change(change: &Change) -> &InLineChange {
+        match change {
+            Change::InLine(change) => change,
+            Change::Lines(_) => panic!("expected an in-line change"),
+        }
+    }
+
+    fn caret(line_idx: LineIdx, grapheme_idx: GraphemeIdx) -> Location {
+        Location { line_idx, grapheme_idx }
+    }
+
+    /// 模拟在第 line_idx 行末尾依次输入字符，每个字符一步，允许合并
+    fn type_into(history: &mut History, line_idx: LineIdx, before: &str, text: &str) {
+        let mut line = before.to_string();
+        for character in text.chars() {
+            history.record(in_line(line_idx, line.len(), "", &character.to_string()));
+            line.push(character);
+            let grapheme_idx = line.chars().count();
+            history.finish_step(caret(line_idx, grapheme_idx - 1), caret(line_idx, grapheme_idx), true);
+        }
+    }
+
+    #[test]
+    fn typing_coalesces_into_one_in_line_change() {
+        let mut history = History::default();
+        type_into(&mut history, 2, "x", "abc");
+        let step = history.pop_undo().unwrap();
+        assert!(history.pop_undo().is_none());
+        assert_eq!(step.changes.len(), 1);
+        let change = in_line_change(&step.changes[0]);
+        assert_eq!((change.line_idx, change.start), (2, 1));
+        assert_eq!((change.removed.as_str(), change.inserted.as_str()), ("", "abc"));
+        assert_eq!((step.caret_before, step.caret_after), (caret(2, 1), caret(2, 4)));
+    }
+
+    #[test]
+    fn overlapping_in_line_changes_merge() {
+        let mut history = History::default();
+        // "hello world"：把 "world" 换成 "there"，再删除 "lo th" 并输入 "p"
+        history.record(in_line(0, 6, "world", "there"));
+        history.record(in_line(0, 3, "lo th", "p"));
+        // 退格删除前面未改动过的 "l"
+        history.record(in_line(0, 2, "l", ""));
+        history.finish_step(caret(0, 0), caret(0, 3), false);
+        let step = history.pop_undo().unwrap();
+        assert_eq!(step.changes.len(), 1);
+        let change = in_line_change(&step.changes[0]);
+        assert_eq!(change.start, 2);
+        assert_eq!((change.removed.as_str(), change.inserted.as_str()), ("llo world", "pere"));
+    }
+
+    #[test]
+    fn in_line_changes_on_other_lines_or_far_apart_stay_separate() {
+        let mut history = History::default();
+        history.record(in_line(0, 0, "", "a"));
+        history.record(in_line(1, 1, "", "b"));
+        history.record(in_line(1, 5, "x", ""));
+        history.record(change(1, &["b"], &["b", ""]));
+        history.finish_step(caret(0, 0), caret(2, 0), false);
+        assert_eq!(history.pop_undo().unwrap().changes.len(), 4);
+    }
+
+    #[test]
+    fn adjacent_changes_in_one_step_merge() {
+        let mut history = History::default();
+        // 拆分第 1 行，再修改拆出的新行和其后原有的一行
+        history.record(change(1, &["ab"], &["a", "b"]));
+        history.record(change(2, &["b", "c"], &["bx", "cx"]));
+        history.finish_step(caret(1, 1), caret(3, 2), false);
+        let step = history.pop_undo().unwrap();
+        assert_eq!(step.changes.len(), 1);
+        let change = lines_change(&step.changes[0]);
+        assert_eq!(change.line_idx, 1);
+        assert_eq!(change.removed, ["ab", "c"]);
+        assert_eq!(change.inserted, ["a", "bx", "cx"]);
+    }
+
+    #[test]
+    fn change_before_previous_one_merges() {
+        let mut history = History::default();
+        history.record(change(3, &["d"], &["dd"]));
+        history.record(change(2, &["c", "dd"], &["cdd"]));
+        history.finish_step(caret(3, 0), caret(2, 1), false);
+        let step = history.pop_undo().unwrap();
+        assert_eq!(step.changes.len(), 1);
+        let change = lines_change(&step.changes[0]);
+        assert_eq!(change.line_idx, 2);
+        assert_eq!(change.removed, ["c", "d"]);
+        assert_eq!(change.inserted, ["cdd"]);
+    }
+
+    #[test]
+    fn distant_changes_stay_separate() {
+        let mut history = History::default();
+        history.record(change(0, &["a"], &["b"]));
+        history.record(change(5, &["c"], &["d"]));
+        history.finish_step(caret(0, 0), caret(5, 0), false);
+        assert_eq!(history.pop_undo().unwrap().changes.len(), 2);
+    }
+
+    #[test]
+    fn typing_after_save_starts_a_new_step() {
+        let mut history = History::default();
+        type_into(&mut history, 0, "", "ab");
+        history.mark_saved();
+        assert!(history.is_at_saved());
+        type_into(&mut history, 0, "ab", "c");
+        assert!(!history.is_at_saved());
+        let step = history.pop_undo().unwrap();
+        history.push_redo(step);
+        assert!(history.is_at_saved());
+    }
+}
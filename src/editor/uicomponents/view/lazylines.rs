@@ -0,0 +1,401 @@
+// LazyLines 按需读取很大的文件：打开时只扫描一遍，记录每行的起始字节位置；
+// 显示或查找某一行时才从文件中读出并构建 Line，最近用过的若干行保留在缓存中，
+// 不必把整个文件放进内存。超长的行（如压缩成一行的 JSON）按字节数切成若干段，每段当作一行显示。
+
+use std::{
+    cell::RefCell,
+    cmp::min,
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader, Error, Read, Seek, SeekFrom},
+    ops::Deref,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use crate::prelude::*;
+
+use super::Line;
+
+/// 缓存的行数上限，超出时丢弃最久未用的行
+const CACHE_CAPACITY: usize = 1024;
+/// 缓存的行的总字节数上限，超出时同样丢弃最久未用的行
+const CACHE_BYTE_BUDGET: usize = 16 << 20;
+/// 扫描文件时每次读取的字节数
+const READ_CHUNK_LEN: usize = 1 << 20;
+/// 一行最多的字节数，更长的行在字符边界处切成若干段
+const MAX_LINE_LEN: usize = 1 << 16;
+
+/// 缓存中的一项，按使用顺序串成双向链表
+struct CacheEntry {
+    line_idx: LineIdx,
+    line: Rc<Line>,
+    newer: Option<usize>, // 下一个更近用过的项在 entries 中的位置
+    older: Option<usize>, // 下一个更早用过的项在 entries 中的位置
+}
+
+/// 最近用过的行，按使用顺序淘汰；查找、记为最近用过和淘汰都只需常数时间
+struct LineCache {
+    entries: Vec<CacheEntry>,          // 所有项，淘汰后的位置留给新项
+    free: Vec<usize>,                  // entries 中空出的位置
+    slots: HashMap<LineIdx, usize>,    // 行号到项的位置
+    newest: Option<usize>,             // 最近用过的项
+    oldest: Option<usize>,             // 最久未用的项
+    bytes: usize,                      // 缓存的行的总字节数
+    capacity: usize,                   // 行数上限
+    byte_budget: usize,                // 总字节数上限
+}
+
+impl Default for LineCache {
+    fn default() -> Self {
+        Self::new(CACHE_CAPACITY, CACHE_BYTE_BUDGET)
+    }
+}
+
+impl LineCache {
+    fn new(capacity: usize, byte_budget: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            free: Vec::new(),
+            slots: HashMap::new(),
+            newest: None,
+            oldest: None,
+            bytes: 0,
+            capacity: capacity.max(1),
+            byte_budget,
+        }
+    }
+
+    /// 取出缓存的行，并记为最近用过
+    fn get(&mut self, line_idx: LineIdx) -> Option<Rc<Line>> {
+        let slot = *self.slots.get(&line_idx)?;
+        self.unlink(slot);
+        self.push_newest(slot);
+        Some(Rc::clone(&self.entries[slot].line))
+    }
+
+    /// 放入一行，超出行数或字节数上限时丢弃最久未用的行（刚放入的行总会保留）
+    fn insert(&mut self, line_idx: LineIdx, line: Rc<Line>) {
+        if let Some(slot) = self.slots.remove(&line_idx) {
+            self.remove(slot);
+        }
+        // 先腾出位置，entries 不会超过行数上限
+        while self.slots.len() >= self.capacity && self.evict_oldest() {}
+        self.bytes = self.bytes.saturating_add(line.len());
+        let entry = CacheEntry {
+            line_idx,
+            line,
+            newer: None,
+            older: None,
+        };
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.entries[slot] = entry;
+                slot
+            }
+            None => {
+                self.entries.push(entry);
+                self.entries.len() - 1
+            }
+        };
+        self.slots.insert(line_idx, slot);
+        self.push_newest(slot);
+        while self.bytes > self.byte_budget && self.slots.len() > 1 && self.evict_oldest() {}
+    }
+
+    /// 丢弃最久未用的行，缓存为空时返回 false
+    fn evict_oldest(&mut self) -> bool {
+        let Some(oldest) = self.oldest else {
+            return false;
+        };
+        self.slots.remove(&self.entries[oldest].line_idx);
+        self.remove(oldest);
+        true
+    }
+
+    /// 从链表中移除一项并释放它的位置（调用前已从 slots 中删除）
+    fn remove(&mut self, slot: usize) {
+        self.unlink(slot);
+        self.bytes = self.bytes.saturating_sub(self.entries[slot].line.len());
+        // 释放对行的引用，让仍在使用它的 LineRef 成为唯一的持有者
+        self.entries[slot].line = Rc::default();
+        self.free.push(slot);
+    }
+
+    /// 将一项从链表中摘下
+    fn unlink(&mut self, slot: usize) {
+        let CacheEntry { newer, older, .. } = self.entries[slot];
+        match newer {
+            Some(newer) => self.entries[newer].older = older,
+            None => self.newest = older,
+        }
+        match older {
+            Some(older) => self.entries[older].newer = newer,
+            None => self.oldest = newer,
+        }
+        self.entries[slot].newer = None;
+        self.entries[slot].older = None;
+    }
+
+    /// 将一项接到链表最近用过的一端
+    fn push_newest(&mut self, slot: usize) {
+        self.entries[slot].older = self.newest;
+        match self.newest {
+            Some(newest) => self.entries[newest].newer = Some(slot),
+            None => self.oldest = Some(slot),
+        }
+        self.newest = Some(slot);
+    }
+}
+
+/// 按需读取的文件内容
+pub struct LazyLines {
+    path: PathBuf,         // 文件路径，顺序查找时另外打开
+    file: RefCell<File>,   // 按行读取时使用的文件
+    line_starts: Vec<u64>, // 每行（超长行的每一段）的起始字节位置
+    len: u64,              // 文件的字节数
+    cache: RefCell<LineCache>,
+}
+
+impl LazyLines {
+    /// 打开文件并扫描一遍换行符，建立行的索引
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Self::open_with(path, MAX_LINE_LEN)
+    }
+
+    /// 同 open，超过 max_line_len 字节的行在 UTF-8 字符边界处切成不超过该长度的若干段
+    pub fn open_with(path: &Path, max_line_len: usize) -> Result<Self, Error> {
+        let file = File::open(path)?;
+        // 至少容得下一个完整的 UTF-8 字符
+        let max_line_len = max_line_len.max(4) as u64;
+        let mut line_starts = vec![0];
+        let mut line_start: u64 = 0;
+        let mut offset: u64 = 0;
+        let mut reader = BufReader::with_capacity(READ_CHUNK_LEN, &file);
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            let chunk_len = chunk.len();
+            for (idx, &byte) in chunk.iter().enumerate() {
+                let position = offset.saturating_add(idx as u64);
+                if byte == b'\n' {
+                    line_start = position.saturating_add(1);
+                    line_starts.push(line_start);
+                } else if position.saturating_sub(line_start).saturating_add(Self::char_len(byte)) > max_line_len
+                    && !Self::is_continuation(byte)
+                {
+                    // 放不下这个字符时，从它开始新的一段
+                    line_start = position;
+                    line_starts.push(line_start);
+                }
+            }
+            offset = offset.saturating_add(chunk_len as u64);
+            reader.consume(chunk_len);
+        }
+        // 与 str::lines 一致：以换行符结尾时最后不再多出一个空行
+        if line_starts.last() == Some(&offset) {
+            line_starts.pop();
+        }
+        Ok(Self {
+            path: path.to_path_buf(),
+            file: RefCell::new(file),
+            line_starts,
+            len: offset,
+            cache: RefCell::default(),
+        })
+    }
+
+    /// 判断是否为 UTF-8 多字节字符的后续字节
+    const fn is_continuation(byte: u8) -> bool {
+        byte & 0b1100_0000 == 0b1000_0000
+    }
+
+    /// 以 byte 开头的 UTF-8 字符的字节数，后续字节和无效字节按 1 计
+    const fn char_len(byte: u8) -> u64 {
+        match byte.leading_ones() {
+            2 => 2,
+            3 => 3,
+            4 => 4,
+            _ => 1,
+        }
+    }
+
+    /// 返回行数
+    pub fn height(&self) -> LineIdx {
+        self.line_starts.len()
+    }
+
+    /// 返回文件的字节数
+    pub const fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// 返回指定的行，优先使用缓存；超出范围或读取失败时返回 None
+    pub fn get(&self, line_idx: LineIdx) -> Option<Rc<Line>> {
+        if let Some(line) = self.cache.borrow_mut().get(line_idx) {
+            return Some(line);
+        }
+        let line = Rc::new(Line::from(&self.read_raw(line_idx)?));
+        self.cache.borrow_mut().insert(line_idx, Rc::clone(&line));
+        Some(line)
+    }
+
+    /// 返回一行（含换行符）的字节数，超出范围时返回 None
+    fn raw_len(&self, line_idx: LineIdx) -> Option<usize> {
+        let start = *self.line_starts.get(line_idx)?;
+        let end = self
+            .line_starts
+            .get(line_idx.saturating_add(1))
+            .copied()
+            .unwrap_or(self.len);
+        usize::try_from(end.saturating_sub(start)).ok()
+    }
+
+    /// 从文件中读出一行的文本（去掉换行符，无法解码的字节替换为 U+FFFD），不经过缓存
+    fn read_raw(&self, line_idx: LineIdx) -> Option<String> {
+        let start = *self.line_starts.get(line_idx)?;
+        let mut bytes = vec![0; self.raw_len(line_idx)?];
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(start)).ok()?;
+        file.read_exact(&mut bytes).ok()?;
+        Some(Self::decode(bytes))
+    }
+
+    /// 去掉行末的换行符并解码
+    fn decode(mut bytes: Vec<u8>) -> String {
+        if bytes.last() == Some(&b'\n') {
+            bytes.pop();
+            if bytes.last() == Some(&b'\r') {
+                bytes.pop();
+            }
+        }
+        String::from_utf8(bytes)
+            .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+    }
+
+    /// 从 from 行开始顺序读取文件，依次返回包含 query 的行号；只比较原始文本，不构建 Line。
+    /// 另外打开一次文件，遍历期间仍可按行读取。超长行切开的各段分别比较，跨越两段的匹配找不到
+    pub fn lines_containing<'a>(&'a self, from: LineIdx, query: &'a str) -> impl Iterator<Item = LineIdx> + 'a {
+        let mut reader = self.line_starts.get(from).and_then(|&start| {
+            let mut file = File::open(&self.path).ok()?;
+            file.seek(SeekFrom::Start(start)).ok()?;
+            Some(BufReader::with_capacity(READ_CHUNK_LEN, file))
+        });
+        (from..self.height()).filter(move |&line_idx| {
+            let mut bytes = vec![0; self.raw_len(line_idx).unwrap_or(0)];
+            reader
+                .as_mut()
+                .is_some_and(|reader| reader.read_exact(&mut bytes).is_ok())
+                && Self::decode(bytes).contains(query)
+        })
+    }
+
+    /// 从 from 行开始向前逐行读取，依次返回包含 query 的行号
+    pub fn lines_containing_rev<'a>(&'a self, from: LineIdx, query: &'a str) -> impl Iterator<Item = LineIdx> + 'a {
+        (0..=min(from, self.height().saturating_sub(1)))
+            .rev()
+            .filter(move |&line_idx| self.read_raw(line_idx).is_some_and(|text| text.contains(query)))
+    }
+}
+
+/// 缓冲区中一行的引用：已加载的行直接借用，按需读取的行共享缓存中的副本
+pub enum LineRef<'a> {
+    Loaded(&'a Line),
+    Cached(Rc<Line>),
+}
+
+impl Deref for LineRef<'_> {
+    type Target = Line;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            Self::Loaded(line) => line,
+            Self::Cached(line) => line,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::tempfile::TempFile;
+
+    fn line(text: &str) -> Rc<Line> {
+        Rc::new(Line::from(text))
+    }
+
+    #[test]
+    fn get_reads_single_lines_from_large_fixture() {
+        const LINES: usize = 200_000;
+        let contents: String = (0..LINES).map(|idx| format!("line {idx} 字\n")).collect();
+        let file = TempFile::with_contents("large", &contents);
+        let lines = LazyLines::open(file.path()).unwrap();
+        assert_eq!(lines.height(), LINES);
+        assert_eq!(lines.len(), contents.len() as u64);
+        for idx in [0, 1, 99_999, LINES - 1] {
+            assert_eq!(lines.get(idx).unwrap().to_string(), format!("line {idx} 字"));
+        }
+        assert!(lines.get(LINES).is_none());
+        // 只读过用到的几行
+        assert_eq!(lines.cache.borrow().slots.len(), 4);
+        for idx in 0..CACHE_CAPACITY * 2 {
+            lines.get(idx);
+        }
+        assert_eq!(lines.cache.borrow().slots.len(), CACHE_CAPACITY);
+    }
+
+    #[test]
+    fn crlf_and_missing_final_newline() {
+        let file = TempFile::with_contents("crlf", b"a\r\nb\r\nc");
+        let lines = LazyLines::open(file.path()).unwrap();
+        assert_eq!(lines.height(), 3);
+        assert_eq!(lines.get(1).unwrap().to_string(), "b");
+        assert_eq!(lines.get(2).unwrap().to_string(), "c");
+    }
+
+    #[test]
+    fn giant_line_is_split_at_char_boundaries() {
+        let text = "ab字cdefghij字klm€xyz";
+        let file = TempFile::with_contents("giant", format!("{text}\nshort\n"));
+        let lines = LazyLines::open_with(file.path(), 10).unwrap();
+        let segments: Vec<String> = (0..lines.height()).map(|idx| lines.get(idx).unwrap().to_string()).collect();
+        assert!(segments.iter().all(|segment| segment.len() <= 10), "{segments:?}");
+        assert_eq!(segments.last().map(String::as_str), Some("short"));
+        assert_eq!(segments[..segments.len() - 1].concat(), text);
+        assert_eq!(lines.lines_containing(0, "short").collect::<Vec<_>>(), [segments.len() - 1]);
+        assert_eq!(lines.lines_containing(0, "字k").count(), 1);
+    }
+
+    #[test]
+    fn cache_evicts_least_recently_used() {
+        let mut cache = LineCache::new(2, usize::MAX);
+        cache.insert(1, line("a"));
+        cache.insert(2, line("b"));
+        assert!(cache.get(1).is_some());
+        cache.insert(3, line("c"));
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some());
+        assert!(cache.get(3).is_some());
+        cache.insert(4, line("d"));
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.entries.len(), 2);
+    }
+
+    #[test]
+    fn cache_respects_byte_budget() {
+        let mut cache = LineCache::new(100, 10);
+        cache.insert(1, line("aaaa"));
+        cache.insert(2, line("bbbb"));
+        cache.insert(3, line("cccc"));
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.bytes, 8);
+        // 单独一行超出预算时仍然保留
+        cache.insert(4, line(&"d".repeat(20)));
+        assert_eq!(cache.slots.len(), 1);
+        assert!(cache.get(4).is_some());
+        cache.insert(4, line("e"));
+        assert_eq!(cache.bytes, 1);
+    }
+}
@@ -1,31 +1,153 @@
 // 负责文本内容的显示、编辑、滚动和光标管理。
 
 
-use std::{cmp::min, io::Error};
+use std::{
+    cmp::{max, min},
+    collections::HashMap,
+    io::Error,
+    ops::{Range, RangeInclusive},
+    path::Path,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use crate::editor::RowIdx;
 use crate::prelude::*;
 
 use crate::editor::{
     command::{Edit, Move},
-    DocumentStatus, Line, Terminal,
+    config::Config,
+    syntax,
+    timestamp,
+    AnnotatedString, AnnotationType, DocumentStatus, GraphemeWidth, Line, LineEnding, Screen,
 };
 use super::UIComponent;
 
 mod buffer;
-use buffer::Buffer;
+pub use buffer::Buffer;
+use buffer::is_word_char;
 
 mod fileinfo;
 use fileinfo::FileInfo;
 
+mod history;
+
+mod safewrite;
+
+mod lazylines;
+pub use lazylines::LineRef;
+
+#[cfg(feature = "diff")]
+mod diffmarks;
+#[cfg(feature = "diff")]
+use diffmarks::DiffMarks;
+
+/// 跳转记录最多保留的位置数
+const JUMP_LIST_LIMIT: usize = 100;
+/// 滚轮每格滚动的行数
+const SCROLL_WHEEL_LINES: usize = 3;
+/// 改动标记栏的宽度（标记符号和一个空格）
+#[cfg(feature = "diff")]
+const DIFF_GUTTER_WIDTH: ColIdx = 2;
+
+/// 行号显示方式
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum LineNumbers {
+    #[default]
+    Off,      // 不显示行号
+    Absolute, // 显示每行的行号
+    Relative, // 显示与光标所在行的距离，光标所在行显示行号
+}
+
+impl LineNumbers {
+    /// 依次切换的下一种显示方式
+    pub const fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Absolute,
+            Self::Absolute => Self::Relative,
+            Self::Relative => Self::Off,
+        }
+    }
+
+    /// 通过名称（off、absolute、relative）解析行号显示方式
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "off" => Some(Self::Off),
+            "absolute" => Some(Self::Absolute),
+            "relative" => Some(Self::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// 搜索方向
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum SearchDirection {
+    Forward,  // 向文件末尾方向
+    Backward, // 向文件开头方向
+}
+
+/// 一次搜索的结果
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum SearchOutcome {
+    Found,      // 找到匹配
+    Wrapped,    // 越过文件末尾（或开头）回绕后找到匹配
+    ReachedEnd, // 未开启回绕，已到文件末尾（或开头），光标不动
+    NotFound,   // 全文没有匹配
+}
+
+/// 缓存的全文匹配位置，搜索内容、整词模式或缓冲区内容变化时重新计算
+struct SearchMatches {
+    query: String,           // 搜索内容
+    whole_word: bool,        // 是否为整词模式
+    revision: usize,         // 计算时缓冲区的修改次数
+    locations: Vec<Location>, // 所有匹配的起始位置，按出现顺序排列
+}
+
+/// 单词补全的循环状态
+struct Completion {
+    start: Location,         // 被补全单词的起始位置
+    end: Location,           // 当前插入内容的末尾（光标应在此处）
+    prefix: String,          // 用户输入的前缀
+    candidates: Vec<String>, // 候选单词
+    idx: Option<usize>,      // 当前插入的候选，None 表示恢复为原前缀
+}
+
 /// 编辑区主视图，管理文本缓冲区、滚动、光标等
 #[derive(Default)]
 pub struct View {
     buffer: Buffer,           // 文本缓冲区
+    config: Config,           // 制表符宽度、行号、自动缩进等设置
+    drawn_gutter_width: ColIdx, // 上次绘制时左侧栏的宽度，行数跨过位数边界时据此重新滚动
+    auto_indent: bool,        // 换行时新行是否沿用当前行的缩进，初始值来自配置
+    paste_guard: bool,        // 疑似未经括号粘贴送达的粘贴，暂停自动缩进
     needs_redraw: bool,       // 是否需要重绘
     size: Size,               // 视图区尺寸
     text_location: Location,  // 当前文本位置（行、字素）
+    undo_caret: Location,     // 上一个撤销步骤结束时的光标位置，即下一步开始前的位置
     scroll_offset: Position,  // 当前滚动偏移
+    show_whitespace: bool,    // 是否以可见符号显示空格和制表符
+    highlight_trailing_whitespace: bool, // 是否以红色背景标出行尾空白
+    ruler_column: Option<ColIdx>, // 列标尺所在的列（从 1 开始），None 表示不显示
+    line_length_limit: Option<ColIdx>, // 行长限制，超出部分以警告色显示
+    desired_col: Option<ColIdx>, // 连续垂直移动时希望保持的列
+    marks: HashMap<char, Location>, // 书签：字母到文本位置的映射
+    jump_back_list: Vec<Location>, // 跳转前的位置，最近的在末尾
+    jump_forward_list: Vec<Location>, // 回退前的位置，最近的在末尾
+    completion: Option<Completion>, // 正在进行的单词补全
+    block_anchor: Option<Position>, // 块选择的起点（行号、列），None 表示未处于块选择模式
+    selection_anchor: Option<Location>, // 按住 Shift 移动时选择的起点，选择范围到光标为止；None 表示没有选择
+    search_wrap: bool, // 搜索越过文件末尾（或开头）时是否回绕
+    search_whole_word: bool, // 搜索是否只匹配完整的词
+    search_matches: Option<SearchMatches>, // 缓存的全文匹配位置
+    search_highlight: Option<String>, // 高亮显示其所有匹配的搜索内容，None 表示不高亮
+    #[cfg(feature = "diff")]
+    diff_marks: Option<DiffMarks>, // 与磁盘版本的比较结果，None 表示不显示改动标记
 }
 
 impl View {
@@ -35,11 +157,238 @@ impl View {
         DocumentStatus {
             total_lines: self.buffer.height(),
             current_line_idx: self.text_location.line_idx,
+            current_col_idx: self.text_location.grapheme_idx,
+            current_col: self.text_location_to_position().col,
             file_name: format!("{file_info}"),
+            file_type: file_info.get_file_type(),
+            line_ending: self.buffer.line_ending(),
+            read_only: self.buffer.is_read_only(),
+            lossy: self.buffer.is_lossy(),
+            large_file: self.buffer.is_large_file(),
+            recording_macro: false, // 由 Editor 填写
+            mode: None,             // 由 Editor 填写
             is_modified: self.buffer.is_dirty(),
+            total_words: 0, // 由 Editor 填写
+            total_chars: 0, // 由 Editor 填写
+            total_bytes: 0, // 由 Editor 填写
+            first_visible_line_idx: self.scroll_offset.row,
+            visible_line_count: self.size.height,
+            buffer_idx: 0,   // 由 Editor 填写
+            buffer_count: 1, // 由 Editor 填写
         }
     }
 
+    /// 返回没有内容的新视图，尺寸和显示、搜索设置与当前视图相同
+    pub fn empty_like(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            auto_indent: self.auto_indent,
+            needs_redraw: true,
+            size: self.size,
+            show_whitespace: self.show_whitespace,
+            highlight_trailing_whitespace: self.highlight_trailing_whitespace,
+            ruler_column: self.ruler_column,
+            line_length_limit: self.line_length_limit,
+            search_wrap: self.search_wrap,
+            search_whole_word: self.search_whole_word,
+            ..Self::default()
+        }
+    }
+
+    /// 应用配置文件中的设置
+    pub fn set_config(&mut self, config: &Config) {
+        self.config = config.clone();
+        self.auto_indent = config.auto_indent;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+
+    /// 切换自动缩进，返回切换后的状态
+    pub fn toggle_auto_indent(&mut self) -> bool {
+        self.auto_indent = !self.auto_indent;
+        self.auto_indent
+    }
+
+    /// 切换空白字符的可见显示，返回切换后的状态
+    pub fn toggle_show_whitespace(&mut self) -> bool {
+        self.show_whitespace = !self.show_whitespace;
+        self.set_needs_redraw(true);
+        self.show_whitespace
+    }
+
+    /// 判断当前文件是否只读
+    pub const fn is_read_only(&self) -> bool {
+        self.buffer.is_read_only()
+    }
+
+    /// 判断文件是否按有损方式解码
+    pub const fn is_lossy(&self) -> bool {
+        self.buffer.is_lossy()
+    }
+
+    /// 判断是否为按需读取的大文件
+    pub const fn is_large_file(&self) -> bool {
+        self.buffer.is_large_file()
+    }
+
+    /// 依次切换行号显示方式，返回切换后的方式
+    pub fn cycle_line_numbers(&mut self) -> LineNumbers {
+        self.config.line_numbers = self.config.line_numbers.next();
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        self.config.line_numbers
+    }
+
+    /// 设置是否暂停自动缩进（输入快得不像手动输入时）
+    pub fn set_paste_guard(&mut self, paste_guard: bool) {
+        self.paste_guard = paste_guard;
+    }
+
+    /// 设置是否只读
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.buffer.set_read_only(read_only);
+    }
+
+    /// 切换只读状态，返回切换后的状态
+    pub fn toggle_read_only(&mut self) -> bool {
+        let read_only = !self.buffer.is_read_only();
+        self.buffer.set_read_only(read_only);
+        read_only
+    }
+
+    /// 加载的文件混用了两种换行符时，返回保存时统一使用的换行符
+    pub fn mixed_line_endings(&self) -> Option<LineEnding> {
+        self.buffer
+            .has_mixed_line_endings()
+            .then(|| self.buffer.line_ending())
+    }
+    /// 在 LF 与 CRLF 之间切换全文的换行符，返回切换后的换行符
+    pub fn toggle_line_ending(&mut self) -> LineEnding {
+        let line_ending = self.buffer.line_ending().toggled();
+        self.buffer.set_line_ending(line_ending);
+        line_ending
+    }
+
+    /// 切换行尾空白高亮，返回切换后的状态
+    pub fn toggle_highlight_trailing_whitespace(&mut self) -> bool {
+        self.highlight_trailing_whitespace = !self.highlight_trailing_whitespace;
+        self.set_needs_redraw(true);
+        self.highlight_trailing_whitespace
+    }
+
+    /// 设置列标尺所在的列（从 1 开始），None 关闭标尺
+    pub fn set_ruler_column(&mut self, column: Option<ColIdx>) {
+        self.ruler_column = column.filter(|&column| column > 0);
+        self.set_needs_redraw(true);
+    }
+
+    /// 设置行长限制（列数），超出该列的字素以警告色显示；None 关闭
+    pub fn set_line_length_limit(&mut self, limit: Option<ColIdx>) {
+        self.line_length_limit = limit;
+        self.set_needs_redraw(true);
+    }
+
+    /// 返回光标处的字素及其渲染宽度，光标在行尾时返回 None
+    pub fn grapheme_at_caret(&self) -> Option<(String, GraphemeWidth)> {
+        let line = self.buffer.get_line(self.text_location.line_idx)?;
+        let (grapheme, width) = line.grapheme_at(self.text_location.grapheme_idx)?;
+        Some((grapheme.to_string(), width))
+    }
+
+    /// 缩进中混用制表符和空格时返回能确定混用的第一行
+    pub fn find_mixed_indentation(&self) -> Option<LineIdx> {
+        self.buffer.find_mixed_indentation()
+    }
+
+    /// 统计全文的词数、字符数（按字素计）和保存时的字节数。
+    /// 缓冲区只在修改时重新统计改动的行，每次刷新状态栏时不必重新扫描全文
+    pub fn text_counts(&mut self) -> (usize, usize, usize) {
+        (self.buffer.word_count(), self.buffer.char_count(), self.buffer.byte_count())
+    }
+
+    /// 获取当前文本位置（行、字素）
+    pub const fn text_location(&self) -> Location {
+        self.text_location
+    }
+
+    /// 恢复文本位置（超出当前内容时校正到有效范围）并滚动到可见区域
+    pub fn restore_text_location(&mut self, location: Location) {
+        self.clear_selection();
+        self.text_location = location;
+        self.desired_col = None;
+        self.clamp_caret();
+    }
+
+    /// 获取当前滚动偏移
+    pub const fn scroll_offset(&self) -> Position {
+        self.scroll_offset
+    }
+
+    /// 恢复滚动偏移和文本位置（校正到有效范围），用于恢复会话、取消搜索
+    pub fn restore_view_state(&mut self, location: Location, scroll_offset: Position) {
+        self.scroll_offset = scroll_offset;
+        self.restore_text_location(location);
+        self.set_needs_redraw(true);
+    }
+
+    /// 将光标的行号和字素索引校正到有效范围并滚动到可见区域，
+    /// 用于撤销、重新加载等可能使缓冲区变短的操作之后
+    pub fn clamp_caret(&mut self) {
+        self.snap_to_valid_line();
+        self.snap_to_valid_grapheme();
+        self.scroll_text_location_into_view();
+    }
+
+    /// 将上一次结束以来的修改合为一个撤销步骤，由 Editor 在每条命令之后调用。
+    /// coalesce 为真（输入字符）时可与上一步连续输入的字符合并
+    pub fn finish_undo_step(&mut self, coalesce: bool) {
+        self.buffer.finish_undo_step(self.undo_caret, self.text_location, coalesce);
+        self.undo_caret = self.text_location;
+    }
+
+    /// 撤销最近一步并把光标放回修改之前的位置，没有可撤销的步骤时返回 false
+    pub fn undo(&mut self) -> bool {
+        self.finish_undo_step(false);
+        let Some(location) = self.buffer.undo() else {
+            return false;
+        };
+        self.after_undo_redo(location);
+        true
+    }
+
+    /// 重做最近撤销的一步，没有可重做的步骤时返回 false
+    pub fn redo(&mut self) -> bool {
+        self.finish_undo_step(false);
+        let Some(location) = self.buffer.redo() else {
+            return false;
+        };
+        self.after_undo_redo(location);
+        true
+    }
+
+    /// 撤销或重做之后移动光标并重绘
+    fn after_undo_redo(&mut self, location: Location) {
+        self.completion = None;
+        self.restore_text_location(location);
+        self.undo_caret = self.text_location;
+        self.set_needs_redraw(true);
+    }
+
+    /// 获取当前文件的路径
+    pub fn file_path(&self) -> Option<&Path> {
+        self.buffer.get_file_info().get_path()
+    }
+
+    /// 判断给定路径是否就是当前打开的文件
+    pub fn is_current_file(&self, file_name: &str) -> bool {
+        self.buffer.get_file_info().get_path().is_some_and(|path| {
+            match (path.canonicalize(), Path::new(file_name).canonicalize()) {
+                (Ok(current), Ok(other)) => current == other,
+                _ => path == Path::new(file_name),
+            }
+        })
+    }
+
     /// 判断是否已加载文件
     pub const fn is_file_loaded(&self) -> bool {
         self.buffer.is_file_loaded()
@@ -49,36 +398,269 @@ impl View {
     /// 加载文件内容到缓冲区
     pub fn load(&mut self, file_name: &str) -> Result<(), Error> {
         let buffer = Buffer::load(file_name)?;
+        self.replace_buffer(buffer);
+        Ok(())
+    }
+    /// 用给定的若干行替换缓冲区（不关联文件），用于脚本和测试
+    pub fn load_lines(&mut self, lines: &[String]) {
+        self.replace_buffer(Buffer::from_lines(lines));
+    }
+
+    /// 返回缓冲区的所有行
+    pub fn lines(&self) -> Vec<String> {
+        (0..self.buffer.height())
+            .filter_map(|line_idx| Some(self.buffer.get_line(line_idx)?.to_string()))
+            .collect()
+    }
+    /// 换上新的缓冲区，光标、滚动位置及书签、跳转记录等随之重置
+    fn replace_buffer(&mut self, buffer: Buffer) {
         self.buffer = buffer;
+        self.text_location = Location::default();
+        self.undo_caret = Location::default();
+        self.scroll_offset = Position::default();
+        self.desired_col = None;
+        self.marks.clear();
+        self.jump_back_list.clear();
+        self.jump_forward_list.clear();
+        self.block_anchor = None;
+        self.selection_anchor = None;
+        self.search_matches = None;
+        #[cfg(feature = "diff")]
+        {
+            self.diff_marks = None;
+        }
         self.set_needs_redraw(true);
-        Ok(())
     }
     /// 保存当前缓冲区内容到文件
     pub fn save(&mut self) -> Result<(), Error> {
-        self.buffer.save()?;
-        self.set_needs_redraw(true);
+        self.before_save();
+        self.buffer.save(self.config.backup)?;
+        self.after_save();
         Ok(())
     }
     /// 另存为新文件
     pub fn save_as(&mut self, file_name: &str) -> Result<(), Error> {
-        self.buffer.save_as(file_name)?;
-        self.set_needs_redraw(true);
+        self.before_save();
+        self.buffer.save_as(file_name, self.config.backup)?;
+        self.after_save();
         Ok(())
     }
+    /// 保存前按 ensure-final-newline 设置补上文件末尾的换行符
+    fn before_save(&mut self) {
+        if self.config.ensure_final_newline {
+            self.buffer.add_final_newline();
+        }
+    }
+    /// 保存成功后，改动标记以保存的内容为新的磁盘版本
+    fn after_save(&mut self) {
+        #[cfg(feature = "diff")]
+        if let Some(diff_marks) = &mut self.diff_marks {
+            diff_marks.reset_to(&self.buffer);
+        }
+        self.set_needs_redraw(true);
+    }
+
+    // 改动标记
+    /// 显示或隐藏与磁盘版本比较的改动标记，返回切换后的状态；读取磁盘文件失败时返回错误
+    #[cfg(feature = "diff")]
+    pub fn toggle_diff_marks(&mut self) -> Result<bool, Error> {
+        self.set_needs_redraw(true);
+        if self.diff_marks.take().is_some() {
+            return Ok(false);
+        }
+        let path = self
+            .file_path()
+            .ok_or_else(|| Error::from(std::io::ErrorKind::NotFound))?;
+        let disk_text = std::fs::read_to_string(path)?;
+        self.diff_marks = Some(DiffMarks::new(disk_text, &self.buffer));
+        Ok(true)
+    }
+    /// 跳到下一处（或上一处）改动的第一行；未显示改动标记或没有更多改动时返回 false
+    #[cfg(feature = "diff")]
+    pub fn jump_to_change(&mut self, direction: SearchDirection) -> bool {
+        let Some(diff_marks) = &mut self.diff_marks else {
+            return false;
+        };
+        diff_marks.refresh(&self.buffer);
+        let Some(line_idx) = diff_marks.next_change(self.text_location.line_idx, direction) else {
+            return false;
+        };
+        self.record_jump();
+        self.move_to_match(Location {
+            grapheme_idx: 0,
+            line_idx,
+        });
+        true
+    }
+    /// 判断是否正在显示改动标记
+    #[cfg(feature = "diff")]
+    pub const fn is_showing_diff_marks(&self) -> bool {
+        self.diff_marks.is_some()
+    }
+    /// 编辑区左侧标记栏和行号栏的总宽度
+    fn gutter_width(&self) -> ColIdx {
+        self.diff_gutter_width()
+            .saturating_add(self.line_number_width())
+    }
+    /// 改动标记栏的宽度，不显示标记时为 0
+    fn diff_gutter_width(&self) -> ColIdx {
+        #[cfg(feature = "diff")]
+        if self.diff_marks.is_some() {
+            return DIFF_GUTTER_WIDTH;
+        }
+        0
+    }
+    /// 行号栏的宽度（最大行号的位数加一个空格），不显示行号时为 0
+    fn line_number_width(&self) -> ColIdx {
+        if self.config.line_numbers == LineNumbers::Off {
+            return 0;
+        }
+        self.buffer
+            .height()
+            .max(1)
+            .to_string()
+            .len()
+            .saturating_add(1)
+    }
+    /// 文件末尾之后的行显示的标记：紧接末尾的一行可以单独设置
+    fn end_marker(&self, line_idx: LineIdx) -> &str {
+        // 空缓冲区的第 0 行是光标所在的空行，不显示标记
+        let end = max(self.buffer.height(), 1);
+        if line_idx < end {
+            return "";
+        }
+        match &self.config.first_end_marker {
+            Some(marker) if line_idx == end => marker,
+            _ => &self.config.end_marker,
+        }
+    }
+    /// 绘制指定行的行号，文件末尾之后的行留空
+    fn push_line_number(&self, text: &mut AnnotatedString, line_idx: LineIdx) {
+        let width = self.line_number_width();
+        if width == 0 {
+            return;
+        }
+        if line_idx >= self.buffer.height() {
+            text.push_str(&" ".repeat(width), None);
+            return;
+        }
+        let caret_line_idx = self.text_location.line_idx;
+        let number = match self.config.line_numbers {
+            LineNumbers::Relative if line_idx != caret_line_idx => line_idx.abs_diff(caret_line_idx),
+            _ => line_idx.saturating_add(1),
+        };
+        let digits = width.saturating_sub(1);
+        text.push_str(&format!("{number:>digits$}"), Some(AnnotationType::LineNumber));
+        text.push(' ', None);
+    }
+    /// 编辑区中用于显示文本的宽度（去掉标记栏）
+    fn text_area_width(&self) -> ColIdx {
+        self.size.width.saturating_sub(self.gutter_width())
+    }
+    /// 绘制指定行的标记栏
+    #[cfg(feature = "diff")]
+    fn push_diff_gutter(&self, text: &mut AnnotatedString, line_idx: LineIdx) {
+        let Some(diff_marks) = &self.diff_marks else {
+            return;
+        };
+        match diff_marks.change_at(line_idx) {
+            Some(change) => {
+                text.push(change.glyph(), Some(change.annotation_type()));
+                text.push(' ', None);
+            }
+            None => text.push_str("  ", None),
+        }
+    }
 
     // 命令处理
     /// 处理编辑命令（插入、删除、换行等）
     pub fn handle_edit_command(&mut self, command: Edit) {
+        // 只读时不做任何修改（Editor 已拦截并提示，这里再保证一次）
+        if self.is_read_only() {
+            return;
+        }
+        // 光标位于行首的缩进中或选择跨行时，Tab 缩进整行而不是插入制表符
+        let command = match command {
+            Edit::Insert('\t') if self.block_anchor.is_none() && self.tab_indents_lines() => Edit::Indent,
+            _ => command,
+        };
+        // 缩进和移动行作用于选择覆盖的整行，保留选择
+        if matches!(command, Edit::Indent | Edit::Dedent | Edit::MoveLineUp | Edit::MoveLineDown) {
+            self.clear_block_selection();
+            match command {
+                Edit::MoveLineUp => self.move_lines_up(),
+                Edit::MoveLineDown => self.move_lines_down(),
+                _ => self.shift_lines(matches!(command, Edit::Indent)),
+            }
+            self.completion = None;
+            self.desired_col = None;
+            return;
+        }
+        // 编辑总是结束选择；有选择时删除键删除选中的内容
+        if let Some((start, end)) = self.selection() {
+            self.clear_selection();
+            if matches!(command, Edit::Delete | Edit::DeleteBackward) {
+                self.text_location = self.buffer.delete_range(start, end);
+                self.completion = None;
+                self.desired_col = None;
+                self.scroll_text_location_into_view();
+                self.set_needs_redraw(true);
+                return;
+            }
+        }
+        if self.block_anchor.is_some() {
+            match command {
+                Edit::Insert(character) => {
+                    self.insert_in_block(character);
+                    return;
+                }
+                Edit::DeleteBackward => {
+                    self.delete_backward_in_block();
+                    return;
+                }
+                // 其他编辑命令先退出块选择，再按普通方式执行
+                _ => self.clear_block_selection(),
+            }
+        }
         match command {
             Edit::Insert(character) => self.insert_char(character),
             Edit::Delete => self.delete(),
             Edit::DeleteBackward => self.delete_backward(),
+            Edit::DeleteBackwardCodepoint => self.delete_backward_codepoint(),
             Edit::InsertNewline => self.insert_newline(),
+            Edit::InsertTimestamp(format) => self.insert_str(&timestamp::now(format)),
+            Edit::ExpandTabs => self.expand_tabs(),
+            Edit::UnexpandTabs => self.unexpand_tabs(),
+            Edit::Indent | Edit::Dedent | Edit::MoveLineUp | Edit::MoveLineDown => {}
+            Edit::DeleteToStartOfLine => self.delete_to_start_of_line(),
+            Edit::DeleteLine => self.delete_line(),
+            Edit::DuplicateLine => self.duplicate_line(),
+            Edit::CompleteWord => {
+                self.complete_word();
+                return;
+            }
+            Edit::Undo => {
+                self.undo();
+                return;
+            }
+            Edit::Redo => {
+                self.redo();
+                return;
+            }
         }
+        self.completion = None;
+        self.desired_col = None;
     }
     /// 处理移动命令（上下左右、翻页、行首行尾等）
     pub fn handle_move_command(&mut self, command: Move) {
         let Size { height, .. } = self.size;
+        // 滚轮只移动视口，光标留在原处（可能移出编辑区）
+        match command {
+            Move::ScrollUp => return self.scroll_by_wheel(SearchDirection::Backward),
+            Move::ScrollDown => return self.scroll_by_wheel(SearchDirection::Forward),
+            _ => {}
+        }
+        self.clear_selection();
         // 此匹配移动位置，但不检查所有边界。
         // 最终的边界检查发生在匹配语句之后。
         match command {
@@ -86,21 +668,71 @@ impl View {
             Move::Down => self.move_down(1),
             Move::Left => self.move_left(),
             Move::Right => self.move_right(),
-            Move::PageUp => self.move_up(height.saturating_sub(1)),
-            Move::PageDown => self.move_down(height.saturating_sub(1)),
-            Move::StartOfLine => self.move_to_start_of_line(),
+            Move::PageUp => self.page_up(height.saturating_sub(1)),
+            Move::PageDown => self.page_down(height.saturating_sub(1)),
+            Move::HalfPageUp => self.page_up(height / 2),
+            Move::HalfPageDown => self.page_down(height / 2),
+            Move::StartOfLine => self.move_to_first_non_blank_or_start(),
             Move::EndOfLine => self.move_to_end_of_line(),
+            Move::MatchingBracket => {
+                self.move_to_matching_bracket();
+            }
+            Move::JumpBack => {
+                self.jump_back();
+            }
+            Move::JumpForward => {
+                self.jump_forward();
+            }
+            Move::ScreenPosition(position) => {
+                self.move_to_screen_position(position);
+            }
+            Move::ScrollUp | Move::ScrollDown => {}
+        }
+        self.completion = None;
+        if self.block_anchor.is_some() {
+            self.set_needs_redraw(true);
+        }
+        // 只有垂直移动才保留期望列，其他移动以新位置为准
+        if !matches!(
+            command,
+            Move::Up
+                | Move::Down
+                | Move::PageUp
+                | Move::PageDown
+                | Move::HalfPageUp
+                | Move::HalfPageDown
+        ) {
+            self.desired_col = None;
         }
         self.scroll_text_location_into_view();
     }
 
     // 文本编辑
-    /// 插入换行
+    /// 插入换行；开启自动缩进时新行沿用当前行光标之前的缩进
     fn insert_newline(&mut self) {
+        let indentation = if self.auto_indent && !self.paste_guard {
+            self.indentation_before_caret()
+        } else {
+            String::new()
+        };
         self.buffer.insert_newline(self.text_location);
         self.handle_move_command(Move::Right);
+        if !indentation.is_empty() {
+            self.insert_str(&indentation);
+        }
         self.set_needs_redraw(true);
     }
+    /// 当前行行首的空格和制表符中位于光标之前的部分
+    fn indentation_before_caret(&self) -> String {
+        self.buffer
+            .get_line(self.text_location.line_idx)
+            .map(|line| {
+                let indentation = line.indentation();
+                // 空格和制表符都是单字节字素，字节数即字素数
+                indentation[..min(indentation.len(), self.text_location.grapheme_idx)].to_string()
+            })
+            .unwrap_or_default()
+    }
     /// 向后删除字符
     fn delete_backward(&mut self) {
         if self.text_location.line_idx != 0 || self.text_location.grapheme_idx != 0 {
@@ -108,13 +740,275 @@ impl View {
             self.delete();
         }
     }
+    /// 只删除光标前字素的最后一个码位（如组合重音符），光标按删除后的字素数调整；
+    /// 光标在行首时与向后删除相同，合并到上一行
+    fn delete_backward_codepoint(&mut self) {
+        let Location {
+            grapheme_idx,
+            line_idx,
+        } = self.text_location;
+        if grapheme_idx == 0 {
+            self.delete_backward();
+            return;
+        }
+        let old_count = self.buffer.grapheme_count(line_idx);
+        self.buffer.delete_last_codepoint(Location {
+            grapheme_idx: grapheme_idx.saturating_sub(1),
+            line_idx,
+        });
+        let removed = old_count.saturating_sub(self.buffer.grapheme_count(line_idx));
+        self.text_location.grapheme_idx = grapheme_idx.saturating_sub(removed);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
     /// 删除当前位置字符
     fn delete(&mut self) {
         self.buffer.delete(self.text_location);
         self.set_needs_redraw(true);
     }
-    /// 插入字符
+    /// 用缓冲区中以光标前单词为前缀的单词补全，连续调用时依次切换候选，
+    /// 最后回到原前缀；没有候选时返回 false
+    pub fn complete_word(&mut self) -> bool {
+        let continuing = self
+            .completion
+            .as_ref()
+            .is_some_and(|completion| completion.end == self.text_location);
+        if !continuing {
+            let Some(completion) = self.start_completion() else {
+                self.completion = None;
+                return false;
+            };
+            self.completion = Some(completion);
+        }
+        let Some(completion) = self.completion.as_mut() else {
+            return false;
+        };
+        let next_idx = match completion.idx {
+            None => 0,
+            Some(idx) => idx.saturating_add(1),
+        };
+        completion.idx = (next_idx < completion.candidates.len()).then_some(next_idx);
+        let replacement = completion
+            .idx
+            .map_or(completion.prefix.as_str(), |idx| completion.candidates[idx].as_str());
+        let start = completion.start;
+        self.buffer.delete_range(start, completion.end);
+        let end = self.buffer.insert_str(replacement, start);
+        completion.end = end;
+        self.text_location = end;
+        self.desired_col = None;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        true
+    }
+    /// 取光标前的单词作为前缀，收集候选；前缀为空或没有候选时返回 None
+    fn start_completion(&self) -> Option<Completion> {
+        let Location {
+            line_idx,
+            grapheme_idx,
+        } = self.text_location;
+        let line = self.buffer.get_line(line_idx)?;
+        let mut start_idx = grapheme_idx;
+        while let Some((grapheme, _)) = start_idx
+            .checked_sub(1)
+            .and_then(|idx| line.grapheme_at(idx))
+        {
+            if !grapheme.chars().all(is_word_char) {
+                break;
+            }
+            start_idx = start_idx.saturating_sub(1);
+        }
+        if start_idx == grapheme_idx {
+            return None;
+        }
+        let prefix: String = (start_idx..grapheme_idx)
+            .filter_map(|idx| line.grapheme_at(idx))
+            .map(|(grapheme, _)| grapheme)
+            .collect();
+        let candidates = self.buffer.words_with_prefix(&prefix);
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(Completion {
+            start: Location {
+                grapheme_idx: start_idx,
+                line_idx,
+            },
+            end: self.text_location,
+            prefix,
+            candidates,
+            idx: None,
+        })
+    }
+    /// 删除光标到行首之间的内容
+    fn delete_to_start_of_line(&mut self) {
+        let line_start = Location {
+            line_idx: self.text_location.line_idx,
+            grapheme_idx: 0,
+        };
+        self.text_location = self.buffer.delete_range(line_start, self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 删除光标所在行，光标移到取代它的那一行（删除的是最后一行时为新的最后一行）的行首
+    fn delete_line(&mut self) {
+        let line_idx = self.text_location.line_idx;
+        if line_idx >= self.buffer.height() {
+            return;
+        }
+        self.buffer.delete_line(line_idx);
+        self.text_location = Location {
+            grapheme_idx: 0,
+            line_idx: min(line_idx, self.buffer.height().saturating_sub(1)),
+        };
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 在光标所在行下方插入该行的副本，光标移到副本上的同一位置
+    fn duplicate_line(&mut self) {
+        let line_idx = self.text_location.line_idx;
+        if line_idx >= self.buffer.height() {
+            return;
+        }
+        self.buffer.duplicate_line(line_idx);
+        self.text_location.line_idx = line_idx.saturating_add(1);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 返回全文，每行以换行符结尾
+    pub fn text(&self) -> String {
+        self.buffer.text()
+    }
+    /// 用给定文本替换全文，光标校正到有效范围
+    pub fn replace_text(&mut self, text: &str) {
+        self.buffer.replace_text(text);
+        self.desired_col = None;
+        self.clamp_caret();
+        self.set_needs_redraw(true);
+    }
+    /// 在光标处原样插入文本（不做自动缩进等处理），光标移到插入内容之后
+    pub fn insert_str(&mut self, text: &str) {
+        self.clear_selection();
+        self.text_location = self.buffer.insert_str(text, self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 删除 from 与 to 之间的文本（两者顺序不限），光标移到删除处
+    pub fn delete_range(&mut self, from: Location, to: Location) {
+        self.clear_selection();
+        self.text_location = self.buffer.delete_range(from, to);
+        self.desired_col = None;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 将整个缓冲区的制表符展开为空格
+    fn expand_tabs(&mut self) {
+        self.text_location = self.buffer.expand_tabs(self.config.tab_width, self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 将整个缓冲区行首的空格缩进转换回制表符
+    fn unexpand_tabs(&mut self) {
+        self.text_location = self.buffer.unexpand_tabs(self.config.tab_width, self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// Tab 是否缩进整行：选择跨行，或光标位于行首的缩进中
+    fn tab_indents_lines(&self) -> bool {
+        match self.selection() {
+            Some((start, end)) if start.line_idx != end.line_idx => true,
+            _ => self
+                .buffer
+                .get_line(self.text_location.line_idx)
+                .is_some_and(|line| self.text_location.grapheme_idx <= line.indentation().len()),
+        }
+    }
+    /// 缩进或取消缩进光标所在行（有选择时为选中的各行，跨多行时跳过空行），
+    /// 光标和选择的起点随所在行的内容一起移动，选择保留以便连续缩进
+    fn shift_lines(&mut self, indent: bool) {
+        let (first, last) = self.selected_lines();
+        let tab_width = self.config.tab_width.max(1);
+        let unit = if self.config.expand_tabs { " ".repeat(tab_width) } else { "\t".to_string() };
+        let shifts: Vec<GraphemeIdx> = (first..=last)
+            .map(|line_idx| {
+                if indent && first != last && self.buffer.grapheme_count(line_idx) == 0 {
+                    0
+                } else if indent {
+                    self.buffer.indent_line(line_idx, &unit)
+                } else {
+                    self.buffer.dedent_line(line_idx, tab_width)
+                }
+            })
+            .collect();
+        let shift = |location: Location| {
+            let delta = location
+                .line_idx
+                .checked_sub(first)
+                .and_then(|offset| shifts.get(offset))
+                .copied()
+                .unwrap_or(0);
+            Location {
+                grapheme_idx: if indent {
+                    location.grapheme_idx.saturating_add(delta)
+                } else {
+                    location.grapheme_idx.saturating_sub(delta)
+                },
+                ..location
+            }
+        };
+        self.selection_anchor = self.selection_anchor.map(&shift);
+        self.text_location = shift(self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 选择覆盖的第一行和最后一行，没有选择时都是光标所在行
+    fn selected_lines(&self) -> (LineIdx, LineIdx) {
+        match self.selection() {
+            // 选择结束于某行行首时，那一行不算在内
+            Some((start, end)) if end.line_idx > start.line_idx && end.grapheme_idx == 0 => {
+                (start.line_idx, end.line_idx - 1)
+            }
+            Some((start, end)) => (start.line_idx, end.line_idx),
+            None => (self.text_location.line_idx, self.text_location.line_idx),
+        }
+    }
+    /// 将当前行（有选择时为选择覆盖的所有行）上移一行，光标和选择随之上移
+    fn move_lines_up(&mut self) {
+        let (first, last) = self.selected_lines();
+        if first == 0 || last >= self.buffer.height() {
+            return;
+        }
+        self.buffer.move_lines_up(first..=last);
+        self.shift_selected_lines(|line_idx| line_idx - 1);
+    }
+    /// 将当前行（有选择时为选择覆盖的所有行）下移一行，光标和选择随之下移
+    fn move_lines_down(&mut self) {
+        let (first, last) = self.selected_lines();
+        if first > last || last.saturating_add(1) >= self.buffer.height() {
+            return;
+        }
+        self.buffer.move_lines_down(first..=last);
+        self.shift_selected_lines(|line_idx| line_idx + 1);
+    }
+    /// 行移动之后，按 shift 更新光标和选择起点所在的行
+    fn shift_selected_lines(&mut self, shift: impl Fn(LineIdx) -> LineIdx) {
+        let shift = |location: Location| Location {
+            line_idx: shift(location.line_idx),
+            ..location
+        };
+        self.selection_anchor = self.selection_anchor.map(shift);
+        self.text_location = shift(self.text_location);
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 插入字符；开启 expand-tabs 时制表符以补齐到下一个制表位的空格代替
     fn insert_char(&mut self, character: char) {
+        if character == '\t' && self.config.expand_tabs {
+            let tab_width = self.config.tab_width.max(1);
+            let col = self.text_location_to_position().col;
+            self.insert_str(&" ".repeat(tab_width - col % tab_width));
+            return;
+        }
         let old_len = self.buffer.grapheme_count(self.text_location.line_idx);
         self.buffer.insert_char(character, self.text_location);
         let new_len = self.buffer.grapheme_count(self.text_location.line_idx);
@@ -127,21 +1021,46 @@ impl View {
     }
 
     // 渲染
-    /// 渲染单行文本到指定行
-    fn render_line(at: RowIdx, line_text: &str) -> Result<(), Error> {
-        Terminal::print_row(at, line_text)
+    /// 渲染单行带标注的文本到指定行
+    fn render_line(screen: &mut dyn Screen, at: RowIdx, line_text: &AnnotatedString) -> Result<(), Error> {
+        screen.print_annotated_row(at, line_text)
+    }
+    /// 若列标尺落在可见范围内且该处没有文本，则在行尾补齐空白并绘制标尺
+    fn append_ruler(&self, text: &mut AnnotatedString, text_width: ColIdx) {
+        let Some(ruler_idx) = self.ruler_column.map(|column| column.saturating_sub(1)) else {
+            return;
+        };
+        let left = self.scroll_offset.col;
+        if ruler_idx < left || ruler_idx >= left.saturating_add(self.text_area_width()) {
+            return;
+        }
+        let ruler_col = ruler_idx.saturating_sub(left);
+        if ruler_col >= text_width {
+            text.push_str(&" ".repeat(ruler_col.saturating_sub(text_width)), None);
+            text.push(' ', Some(AnnotationType::Ruler));
+        }
     }
 
     // 滚动
-    /// 垂直滚动到指定行
+    /// 垂直滚动到指定行，并按 scroll-off 设置在其上下保留若干行（不滚过文件末尾）
     fn scroll_vertically(&mut self, to: RowIdx) {
         let Size { height, .. } = self.size;
-        let offset_changed = if to < self.scroll_offset.row {
-            self.scroll_offset.row = to;
-            true
-        } else if to >= self.scroll_offset.row.saturating_add(height) {
-            self.scroll_offset.row = to.saturating_sub(height).saturating_add(1);
-            true
+        let margin = min(self.config.scroll_off, height.saturating_sub(1) / 2);
+        let offset_changed = if to < self.scroll_offset.row.saturating_add(margin) {
+            let row = to.saturating_sub(margin);
+            let changed = row != self.scroll_offset.row;
+            self.scroll_offset.row = row;
+            changed
+        } else if to.saturating_add(margin) >= self.scroll_offset.row.saturating_add(height) {
+            // 视口最多滚动到让最后一行之后的一行（文件末尾的标记）出现在底部
+            let last_top = self.buffer.height().saturating_add(1).saturating_sub(height);
+            let row = min(
+                to.saturating_add(margin).saturating_add(1).saturating_sub(height),
+                max(last_top, to.saturating_add(1).saturating_sub(height)),
+            );
+            let changed = row != self.scroll_offset.row;
+            self.scroll_offset.row = row;
+            changed
         } else {
             false
         };
@@ -151,7 +1070,7 @@ impl View {
     }
     /// 水平滚动到指定列
     fn scroll_horizontally(&mut self, to: ColIdx) {
-        let Size { width, .. } = self.size;
+        let width = self.text_area_width();
         let offset_changed = if to < self.scroll_offset.col {
             self.scroll_offset.col = to;
             true
@@ -170,16 +1089,10 @@ impl View {
         let Position { row, col } = self.text_location_to_position();
         self.scroll_vertically(row);
         self.scroll_horizontally(col);
-    }
-    /// 将光标居中
-    fn center_text_location(&mut self) {
-        let Size { height, width } = self.size;
-        let Position { row, col } = self.text_location_to_position();
-        let vertical_mid = height.div_ceil(2);
-        let horizontal_mid = width.div_ceil(2);
-        self.scroll_offset.row = row.saturating_sub(vertical_mid);
-        self.scroll_offset.col = col.saturating_sub(horizontal_mid);
-        self.set_needs_redraw(true);
+        // 相对行号随光标所在行变化
+        if self.config.line_numbers == LineNumbers::Relative {
+            self.set_needs_redraw(true);
+        }
     }
 
     // 位置和坐标处理
@@ -187,35 +1100,112 @@ impl View {
     pub fn caret_position(&self) -> Position {
         self.text_location_to_position()
             .saturating_sub(self.scroll_offset)
+            .saturating_add(Position {
+                col: self.gutter_width(),
+                row: 0,
+            })
+    }
+    /// 判断光标所在行是否在编辑区内
+    pub fn is_caret_visible(&self) -> bool {
+        let top = self.scroll_offset.row;
+        (top..top.saturating_add(self.size.height)).contains(&self.text_location.line_idx)
+    }
+    /// 将光标移到终端坐标（编辑区从第 0 行开始）下的字素：点在宽字符的任一列上都落在该字素，
+    /// 点在行号栏上落在行首，点在文件末尾之后落在最后一行。点在编辑区之外时不移动并返回 false
+    pub fn move_to_screen_position(&mut self, position: Position) -> bool {
+        if position.row >= self.size.height {
+            return false;
+        }
+        let line_idx = min(
+            self.scroll_offset.row.saturating_add(position.row),
+            self.buffer.height().saturating_sub(1),
+        );
+        let col = self
+            .scroll_offset
+            .col
+            .saturating_add(position.col.saturating_sub(self.gutter_width()));
+        self.text_location = Location {
+            line_idx,
+            grapheme_idx: self.buffer.grapheme_idx_at_col(line_idx, col, self.config.tab_width),
+        };
+        true
+    }
+    /// 按滚轮方向滚动几行，不超过文件首尾，光标不动
+    fn scroll_by_wheel(&mut self, direction: SearchDirection) {
+        // 编辑区变矮等情况下视口可能已经超过 last_top，此时向下不再滚动
+        let last_top = self.buffer.height().saturating_add(1).saturating_sub(self.size.height);
+        let last_top = max(last_top, self.scroll_offset.row);
+        let row = match direction {
+            SearchDirection::Forward => min(self.scroll_offset.row.saturating_add(SCROLL_WHEEL_LINES), last_top),
+            SearchDirection::Backward => self.scroll_offset.row.saturating_sub(SCROLL_WHEEL_LINES),
+        };
+        if row != self.scroll_offset.row {
+            self.scroll_offset.row = row;
+            self.set_needs_redraw(true);
+        }
     }
     /// 将文本位置转换为终端坐标
     fn text_location_to_position(&self) -> Position {
         let row = self.text_location.line_idx;
-        debug_assert!(row.saturating_sub(1) <= self.buffer.height());
+        // 光标总在某一行上；只有空缓冲区时位于第 0 行（尚不存在的空行）
+        debug_assert!(row < max(self.buffer.height(), 1));
         let col = self
             .buffer
-            .width_until(row, self.text_location.grapheme_idx);
+            .width_until(row, self.text_location.grapheme_idx, self.config.tab_width);
         Position { col, row }
     }
 
     // 文本位置移动
     /// 向上移动指定行数
     fn move_up(&mut self, step: usize) {
+        let desired_col = self.remember_desired_col();
         self.text_location.line_idx = self.text_location.line_idx.saturating_sub(step);
-        self.snap_to_valid_grapheme();
+        self.snap_to_desired_col(desired_col);
     }
     /// 向下移动指定行数
     fn move_down(&mut self, step: usize) {
+        let desired_col = self.remember_desired_col();
         self.text_location.line_idx = self.text_location.line_idx.saturating_add(step);
-        self.snap_to_valid_grapheme();
         self.snap_to_valid_line();
+        self.snap_to_desired_col(desired_col);
+    }
+    /// 向上翻动指定行数：光标与视口一起移动，光标在屏幕上的行保持不变
+    fn page_up(&mut self, lines: usize) {
+        let step = min(lines, self.text_location.line_idx);
+        self.move_up(step);
+        self.scroll_offset.row = self.scroll_offset.row.saturating_sub(step);
+        self.set_needs_redraw(true);
+    }
+    /// 向下翻动指定行数：光标与视口一起移动，光标在屏幕上的行保持不变；
+    /// 视口不滚过文件末尾，接近末尾时光标改为在屏幕上下移
+    fn page_down(&mut self, lines: usize) {
+        let old_line_idx = self.text_location.line_idx;
+        self.move_down(lines);
+        let step = self.text_location.line_idx.saturating_sub(old_line_idx);
+        let last_top = self.buffer.height().saturating_add(1).saturating_sub(self.size.height);
+        self.scroll_offset.row = min(
+            self.scroll_offset.row.saturating_add(step),
+            max(last_top, self.scroll_offset.row),
+        );
+        self.set_needs_redraw(true);
     }
-    /// 向右移动一个字素
+    /// 返回期望列；若尚未记录，则以当前光标列为准
+    fn remember_desired_col(&mut self) -> ColIdx {
+        let current_col = self.text_location_to_position().col;
+        *self.desired_col.get_or_insert(current_col)
+    }
+    /// 将字素索引定位到当前行中最接近期望列的位置
+    fn snap_to_desired_col(&mut self, desired_col: ColIdx) {
+        self.text_location.grapheme_idx = self
+            .buffer
+            .grapheme_idx_at_col(self.text_location.line_idx, desired_col, self.config.tab_width);
+    }
+    /// 向右移动一个字素，在行尾时移到下一行行首；已在最后一行行尾时不动
     fn move_right(&mut self) {
         let grapheme_count = self.buffer.grapheme_count(self.text_location.line_idx);
         if self.text_location.grapheme_idx < grapheme_count {
             self.text_location.grapheme_idx += 1;
-        } else {
+        } else if self.text_location.line_idx.saturating_add(1) < self.buffer.height() {
             self.move_to_start_of_line();
             self.move_down(1);
         }
@@ -229,6 +1219,458 @@ impl View {
             self.move_to_end_of_line();
         }
     }
+    /// 按住 Shift 移动光标：没有选择时以移动前的位置为起点开始选择，选择范围随光标扩展或收缩
+    pub fn handle_select_command(&mut self, command: Move) {
+        let anchor = self.selection_anchor.unwrap_or(self.text_location);
+        self.clear_block_selection();
+        self.handle_move_command(command);
+        self.selection_anchor = Some(anchor).filter(|&anchor| anchor != self.text_location);
+        self.set_needs_redraw(true);
+    }
+    /// 取消选择
+    pub fn clear_selection(&mut self) {
+        if self.selection_anchor.take().is_some() {
+            self.set_needs_redraw(true);
+        }
+    }
+    /// 选择的范围（起点在前），没有选择时返回 None
+    fn selection(&self) -> Option<(Location, Location)> {
+        self.selection_anchor
+            .map(|anchor| anchor.ordered(self.text_location))
+    }
+    /// 行中被选中的字素范围，选择跨行时中间各行整行选中
+    fn selected_graphemes(selection: (Location, Location), line_idx: LineIdx, line: &Line) -> Range<GraphemeIdx> {
+        let (start, end) = selection;
+        if line_idx < start.line_idx || line_idx > end.line_idx {
+            return 0..0;
+        }
+        let from = if line_idx == start.line_idx { start.grapheme_idx } else { 0 };
+        let to = if line_idx == end.line_idx {
+            end.grapheme_idx
+        } else {
+            line.grapheme_count()
+        };
+        from..to
+    }
+    /// 进入或退出块选择模式，返回切换后的状态
+    pub fn toggle_block_selection(&mut self) -> bool {
+        if self.block_anchor.is_some() {
+            self.clear_block_selection();
+            false
+        } else {
+            self.clear_selection();
+            let Position { row, col } = self.text_location_to_position();
+            self.block_anchor = Some(Position { col, row });
+            self.set_needs_redraw(true);
+            true
+        }
+    }
+    /// 退出块选择模式
+    pub fn clear_block_selection(&mut self) {
+        if self.block_anchor.take().is_some() {
+            self.set_needs_redraw(true);
+        }
+    }
+    /// 返回要复制的文本：选中的内容或块选择中的内容（各行以换行符分隔），都没有时为光标所在的整行
+    /// （含换行符）。块的宽度为零或光标不在任何一行上时返回 None
+    pub fn selected_text(&self) -> Option<String> {
+        if let Some((start, end)) = self.selection() {
+            return Some(self.buffer.text_between(start, end));
+        }
+        if let Some((lines, cols)) = self.block_selection() {
+            return (!cols.is_empty()).then(|| self.buffer.text_in_columns(lines, cols, self.config.tab_width));
+        }
+        self.buffer
+            .get_line(self.text_location.line_idx)
+            .map(|line| format!("{}\n", *line))
+    }
+    /// 返回选中的文本，没有选择时返回 None（不考虑块选择）
+    pub fn selection_text(&self) -> Option<String> {
+        self.selection()
+            .map(|(start, end)| self.buffer.text_between(start, end))
+    }
+    /// 用给定文本替换选中的内容，光标移到替换内容之后；没有选择时在光标处插入
+    pub fn replace_selection(&mut self, text: &str) {
+        if let Some((start, end)) = self.selection() {
+            self.clear_selection();
+            self.text_location = self.buffer.delete_range(start, end);
+        }
+        self.text_location = self.buffer.insert_str(text, self.text_location);
+        self.desired_col = None;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 删除 selected_text 返回的内容并返回它：有选择时删除选中的内容，块选择时删除块中的文本并退出块选择，
+    /// 否则删除当前行
+    pub fn cut_selected_text(&mut self) -> Option<String> {
+        let text = self.selected_text()?;
+        if let Some((start, end)) = self.selection() {
+            self.clear_selection();
+            self.text_location = self.buffer.delete_range(start, end);
+            self.desired_col = None;
+            self.scroll_text_location_into_view();
+            self.set_needs_redraw(true);
+        } else if let Some((lines, cols)) = self.block_selection() {
+            let line_idx = self.text_location.line_idx;
+            self.buffer.delete_in_columns(lines, cols.clone(), self.config.tab_width);
+            self.clear_block_selection();
+            self.text_location.grapheme_idx = self.buffer.grapheme_idx_at_col(line_idx, cols.start, self.config.tab_width);
+            self.snap_to_valid_grapheme();
+            self.desired_col = None;
+            self.scroll_text_location_into_view();
+            self.set_needs_redraw(true);
+        } else {
+            self.delete_line();
+        }
+        Some(text)
+    }
+    /// 块选择覆盖的行范围和列范围
+    fn block_selection(&self) -> Option<(RangeInclusive<LineIdx>, Range<ColIdx>)> {
+        let anchor = self.block_anchor?;
+        let caret = self.text_location_to_position();
+        Some((
+            min(anchor.row, caret.row)..=max(anchor.row, caret.row),
+            min(anchor.col, caret.col)..max(anchor.col, caret.col),
+        ))
+    }
+    /// 在块选择的每一行左边界列插入字符，之后块收缩为插入位置之后的一列
+    fn insert_in_block(&mut self, character: char) {
+        let Some((lines, cols)) = self.block_selection() else {
+            return;
+        };
+        let line_idx = self.text_location.line_idx;
+        self.buffer.insert_in_column(lines, cols.start, character, self.config.tab_width);
+        let grapheme_idx = self
+            .buffer
+            .grapheme_idx_at_col(line_idx, cols.start, self.config.tab_width)
+            .saturating_add(1);
+        self.move_block_caret(grapheme_idx);
+    }
+    /// 删除块选择中每一行左边界列之前的一个字素
+    fn delete_backward_in_block(&mut self) {
+        let Some((lines, cols)) = self.block_selection() else {
+            return;
+        };
+        if cols.start == 0 {
+            return;
+        }
+        let line_idx = self.text_location.line_idx;
+        // 以光标所在行左边界前的字素宽度为准；该行太短时按一列处理
+        let grapheme_idx = self
+            .buffer
+            .grapheme_idx_at_col(line_idx, cols.start.saturating_sub(1), self.config.tab_width);
+        let from_col = if grapheme_idx < self.buffer.grapheme_count(line_idx) {
+            self.buffer.width_until(line_idx, grapheme_idx, self.config.tab_width)
+        } else {
+            cols.start.saturating_sub(1)
+        };
+        self.buffer.delete_in_columns(lines, from_col..cols.start, self.config.tab_width);
+        let grapheme_idx = self.buffer.grapheme_idx_at_col(line_idx, from_col, self.config.tab_width);
+        self.move_block_caret(grapheme_idx);
+    }
+    /// 将光标移到当前行的指定字素处，并让块的起点与光标列对齐
+    fn move_block_caret(&mut self, grapheme_idx: GraphemeIdx) {
+        self.text_location.grapheme_idx = grapheme_idx;
+        self.snap_to_valid_grapheme();
+        let Position { col, .. } = self.text_location_to_position();
+        if let Some(anchor) = self.block_anchor.as_mut() {
+            anchor.col = col;
+        }
+        self.desired_col = None;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+    }
+    /// 在光标处设置书签
+    pub fn set_mark(&mut self, name: char) {
+        self.marks.insert(name, self.text_location);
+    }
+    /// 跳转到书签；书签位置超出当前内容时校正到有效范围，未设置时返回 false
+    pub fn jump_to_mark(&mut self, name: char) -> bool {
+        let Some(&location) = self.marks.get(&name) else {
+            return false;
+        };
+        self.clear_selection();
+        self.record_jump();
+        self.text_location = location;
+        self.desired_col = None;
+        self.clamp_caret();
+        true
+    }
+    /// 跳转到指定行（从 0 开始），超出末尾时停在最后一行，并将该行滚动到编辑区中间；
+    /// 光标尽量保持在原来的字素索引处，返回实际到达的行
+    pub fn go_to_line(&mut self, line_idx: LineIdx) -> LineIdx {
+        self.clear_selection();
+        let line_idx = min(line_idx, self.buffer.height().saturating_sub(1));
+        self.record_jump();
+        self.text_location.line_idx = line_idx;
+        self.snap_to_valid_grapheme();
+        self.desired_col = None;
+        self.center_text_location();
+        self.scroll_text_location_into_view();
+        line_idx
+    }
+    /// 在大幅跳转前记录当前位置，并清空前进列表
+    fn record_jump(&mut self) {
+        self.record_jump_from(self.text_location);
+    }
+    /// 记录一次从 location 出发的跳转（用于光标已经移动过的情况，如确认搜索）
+    pub fn record_jump_from(&mut self, location: Location) {
+        self.jump_back_list.push(location);
+        if self.jump_back_list.len() > JUMP_LIST_LIMIT {
+            self.jump_back_list.remove(0);
+        }
+        self.jump_forward_list.clear();
+    }
+    /// 回到上一次跳转前的位置，没有记录时返回 false
+    pub fn jump_back(&mut self) -> bool {
+        let Some(location) = self.jump_back_list.pop() else {
+            return false;
+        };
+        self.clear_selection();
+        self.jump_forward_list.push(self.text_location);
+        self.text_location = location;
+        self.desired_col = None;
+        self.clamp_caret();
+        true
+    }
+    /// 重新前往回退前的位置，没有记录时返回 false
+    pub fn jump_forward(&mut self) -> bool {
+        let Some(location) = self.jump_forward_list.pop() else {
+            return false;
+        };
+        self.clear_selection();
+        self.jump_back_list.push(self.text_location);
+        self.text_location = location;
+        self.desired_col = None;
+        self.clamp_caret();
+        true
+    }
+    /// 移动到与光标处（或其后）括号匹配的括号，找不到时返回 false
+    pub fn move_to_matching_bracket(&mut self) -> bool {
+        match self.buffer.find_matching_bracket(self.text_location) {
+            Some(location) => {
+                self.clear_selection();
+                self.record_jump();
+                self.text_location = location;
+                self.desired_col = None;
+                self.scroll_text_location_into_view();
+                true
+            }
+            None => false,
+        }
+    }
+    /// 设置搜索是否回绕
+    pub fn set_search_wrap(&mut self, wrap: bool) {
+        self.search_wrap = wrap;
+    }
+    /// 设置要高亮显示所有匹配的搜索内容，None 或空字符串表示取消高亮
+    pub fn set_search_highlight(&mut self, query: Option<&str>) {
+        let query = query.filter(|query| !query.is_empty());
+        if self.search_highlight.as_deref() != query {
+            self.search_highlight = query.map(str::to_string);
+            self.set_needs_redraw(true);
+        }
+    }
+    /// 返回行中需要高亮的搜索匹配所占的字素范围
+    fn search_highlight_ranges(&self, line: &Line) -> Vec<Range<GraphemeIdx>> {
+        let Some(query) = self.search_highlight.as_deref() else {
+            return Vec::new();
+        };
+        let len = query.graphemes(true).count();
+        line.match_grapheme_indices(query)
+            .into_iter()
+            .filter(|&start| !self.search_whole_word || line.is_whole_word(start, query))
+            .map(|start| start..start.saturating_add(len))
+            .collect()
+    }
+    /// 返回行中需要语法高亮的片段所占的字素范围及标注类型；未开启高亮或文件类型不支持时为空
+    fn syntax_highlight_ranges(&self, line: &Line) -> Vec<(Range<GraphemeIdx>, AnnotationType)> {
+        if !self.config.syntax_highlight {
+            return Vec::new();
+        }
+        syntax::highlight(self.buffer.get_file_info().get_file_type(), line)
+            .into_iter()
+            .map(|(range, annotation_type)| {
+                let start = line.grapheme_idx_at_byte(range.start);
+                (start..line.grapheme_idx_at_byte(range.end), annotation_type)
+            })
+            .collect()
+    }
+    /// 切换搜索回绕，返回切换后的状态
+    pub fn toggle_search_wrap(&mut self) -> bool {
+        self.search_wrap = !self.search_wrap;
+        self.search_wrap
+    }
+    /// 判断搜索是否只匹配完整的词
+    pub const fn is_search_whole_word(&self) -> bool {
+        self.search_whole_word
+    }
+    /// 切换整词搜索，返回切换后的状态
+    pub fn toggle_search_whole_word(&mut self) -> bool {
+        self.search_whole_word = !self.search_whole_word;
+        self.search_whole_word
+    }
+    /// 查找 from 处或其后（向前搜索时为 from 之前）第一个符合条件的匹配，不回绕；
+    /// 整词模式下跳过两端不在单词边界上的匹配
+    fn find_match(&self, from: Location, query: &str, direction: SearchDirection) -> Option<Location> {
+        let mut from = from;
+        loop {
+            let candidate = match direction {
+                SearchDirection::Forward => self.buffer.search_forward(from, query)?,
+                SearchDirection::Backward => self.buffer.search_backward(from, query)?,
+            };
+            let accepted = !self.search_whole_word
+                || self
+                    .buffer
+                    .get_line(candidate.line_idx)
+                    .is_some_and(|line| line.is_whole_word(candidate.grapheme_idx, query));
+            if accepted {
+                return Some(candidate);
+            }
+            from = match direction {
+                SearchDirection::Forward => Location {
+                    grapheme_idx: candidate.grapheme_idx.saturating_add(1),
+                    ..candidate
+                },
+                SearchDirection::Backward => candidate,
+            };
+        }
+    }
+    /// 从 from 开始搜索 query：向后搜索包括 from 本身，向前搜索不包括。
+    /// 找不到时按回绕设置从另一端继续，找到后将光标移到匹配处
+    pub fn search_from(&mut self, from: Location, query: &str, direction: SearchDirection) -> SearchOutcome {
+        let found = self.find_match(from, query, direction);
+        if let Some(location) = found {
+            self.move_to_match(location);
+            return SearchOutcome::Found;
+        }
+        let wrap_from = match direction {
+            SearchDirection::Forward => Location::default(),
+            SearchDirection::Backward => Location {
+                grapheme_idx: 0,
+                line_idx: self.buffer.height(),
+            },
+        };
+        let wrapped = self.find_match(wrap_from, query, direction);
+        match wrapped {
+            None => SearchOutcome::NotFound,
+            Some(_) if !self.search_wrap => SearchOutcome::ReachedEnd,
+            Some(location) => {
+                self.move_to_match(location);
+                SearchOutcome::Wrapped
+            }
+        }
+    }
+    /// 统计全文中 query 的匹配数，并返回光标处匹配的序号（从 1 开始，光标不在匹配上时为 None）。
+    /// 结果按搜索内容和缓冲区修改次数缓存，连续查找下一个时不必重新扫描全文
+    pub fn search_match_count(&mut self, query: &str) -> (Option<usize>, usize) {
+        let revision = self.buffer.revision();
+        let is_current = self.search_matches.as_ref().is_some_and(|matches| {
+            matches.query == query
+                && matches.whole_word == self.search_whole_word
+                && matches.revision == revision
+        });
+        if !is_current {
+            let locations = self
+                .buffer
+                .find_all(query)
+                .into_iter()
+                .filter(|location| {
+                    !self.search_whole_word
+                        || self
+                            .buffer
+                            .get_line(location.line_idx)
+                            .is_some_and(|line| line.is_whole_word(location.grapheme_idx, query))
+                })
+                .collect();
+            self.search_matches = Some(SearchMatches {
+                query: query.to_string(),
+                whole_word: self.search_whole_word,
+                revision,
+                locations,
+            });
+        }
+        let Some(matches) = &self.search_matches else {
+            return (None, 0);
+        };
+        let ordinal = matches
+            .locations
+            .binary_search(&self.text_location)
+            .ok()
+            .map(|idx| idx.saturating_add(1));
+        (ordinal, matches.locations.len())
+    }
+    /// 从光标处搜索下一个（或上一个）匹配，跳过光标处的匹配本身
+    pub fn search_next(&mut self, query: &str, direction: SearchDirection) -> SearchOutcome {
+        let from = match direction {
+            SearchDirection::Forward => Location {
+                grapheme_idx: self.text_location.grapheme_idx.saturating_add(1),
+                ..self.text_location
+            },
+            SearchDirection::Backward => self.text_location,
+        };
+        self.search_from(from, query, direction)
+    }
+    /// 从 from 开始向后查找 query，包括 from 本身；遵循整词设置，不回绕
+    pub fn find_match_from(&self, from: Location, query: &str) -> Option<Location> {
+        self.find_match(from, query, SearchDirection::Forward)
+    }
+    /// 将光标移到 find_match_from 找到的匹配处，匹配不在屏幕内时滚动到编辑区中间
+    pub fn show_match(&mut self, location: Location) {
+        self.move_to_match(location);
+    }
+    /// 将 at 处的 query 替换为 replacement，光标移到替换内容之后，返回该位置
+    pub fn replace_match(&mut self, at: Location, query: &str, replacement: &str) -> Location {
+        self.text_location = self.buffer.replace_match(at, query, replacement);
+        self.desired_col = None;
+        self.scroll_text_location_into_view();
+        self.set_needs_redraw(true);
+        self.text_location
+    }
+    /// 返回指定行的字素数量
+    pub fn grapheme_count(&self, line_idx: LineIdx) -> GraphemeIdx {
+        self.buffer.grapheme_count(line_idx)
+    }
+    /// 将光标移到匹配处并滚动到可见区域
+    fn move_to_match(&mut self, location: Location) {
+        self.clear_selection();
+        let top = self.scroll_offset.row;
+        let visible_lines = top..top.saturating_add(self.size.height);
+        self.text_location = location;
+        self.desired_col = None;
+        if !visible_lines.contains(&location.line_idx) {
+            self.center_text_location();
+        }
+        self.scroll_text_location_into_view();
+    }
+    /// 垂直滚动使光标所在行位于编辑区中间，不滚过文件末尾
+    fn center_text_location(&mut self) {
+        let height = self.size.height;
+        let last_top = self.buffer.height().saturating_add(1).saturating_sub(height);
+        let row = self.text_location.line_idx.saturating_sub(height / 2);
+        self.scroll_offset.row = min(row, last_top);
+        self.set_needs_redraw(true);
+    }
+    /// 不在行中第一个非空白字素处时移到该处，已在该处时移到行首；整行都是空白时直接移到行首
+    fn move_to_first_non_blank_or_start(&mut self) {
+        let first_non_blank = self
+            .buffer
+            .get_line(self.text_location.line_idx)
+            .map_or(0, |line| {
+                // 空格和制表符都是单字节字素，字节数即字素数
+                let indentation = line.indentation().len();
+                if indentation < line.grapheme_count() {
+                    indentation
+                } else {
+                    0
+                }
+            });
+        self.text_location.grapheme_idx = if self.text_location.grapheme_idx == first_non_blank {
+            0
+        } else {
+            first_non_blank
+        };
+    }
     /// 移动到行首
     fn move_to_start_of_line(&mut self) {
         self.text_location.grapheme_idx = 0;
@@ -239,17 +1681,20 @@ impl View {
     }
 
     // 保证光标位置和行号有效
-    /// 校正光标字素索引到有效范围
+    /// 校正行号到有效范围：光标不会越过最后一行，空缓冲区时为第 0 行
+    fn snap_to_valid_line(&mut self) {
+        self.text_location.line_idx = min(
+            self.text_location.line_idx,
+            self.buffer.height().saturating_sub(1),
+        );
+    }
+    /// 校正字素索引到当前行的有效范围
     fn snap_to_valid_grapheme(&mut self) {
         self.text_location.grapheme_idx = min(
             self.text_location.grapheme_idx,
             self.buffer.grapheme_count(self.text_location.line_idx),
         );
     }
-    /// 校正行号到有效范围
-    fn snap_to_valid_line(&mut self) {
-        self.text_location.line_idx = min(self.text_location.line_idx, self.buffer.height());
-    }
 }
 
 impl UIComponent for View {
@@ -264,13 +1709,29 @@ impl UIComponent for View {
     /// 设置视图区尺寸
     fn set_size(&mut self, size: Size) {
         self.size = size;
+        // 视图变高后不必在文件末尾之后留出多余的空行，先把滚动位置往回收
+        let last_top = self.buffer.height().saturating_add(1).saturating_sub(size.height);
+        self.scroll_offset.row = min(self.scroll_offset.row, last_top);
         self.scroll_text_location_into_view();
     }
     /// 绘制编辑区内容
-    fn draw(&mut self, origin_row: RowIdx) -> Result<(), Error> {
-        let Size { height, width } = self.size;
+    fn draw(&mut self, screen: &mut dyn Screen, origin_row: RowIdx) -> Result<(), Error> {
+        #[cfg(feature = "diff")]
+        if let Some(diff_marks) = &mut self.diff_marks {
+            diff_marks.refresh(&self.buffer);
+        }
+        // 行数跨过位数边界（如 99 → 100）时行号栏变宽，可显示文字的宽度随之变化
+        let gutter_width = self.gutter_width();
+        if gutter_width != self.drawn_gutter_width {
+            self.drawn_gutter_width = gutter_width;
+            self.scroll_text_location_into_view();
+        }
+        let height = self.size.height;
+        let width = self.text_area_width();
         let end_y = origin_row.saturating_add(height);
         let scroll_top = self.scroll_offset.row;
+        let block_selection = self.block_selection();
+        let selection = self.selection();
 
         for current_row in origin_row..end_y {
             let line_idx = current_row
@@ -278,13 +1739,706 @@ impl UIComponent for View {
                 .saturating_add(scroll_top);
             let left = self.scroll_offset.col;
             let right = self.scroll_offset.col.saturating_add(width);
-            if let Some(line) = self.buffer.get_line(line_idx) {
-                let text = line.get_visible_graphemes(left..right);
-                Self::render_line(current_row, &text)?;
+            let mut text = AnnotatedString::default();
+            #[cfg(feature = "diff")]
+            self.push_diff_gutter(&mut text, line_idx);
+            self.push_line_number(&mut text, line_idx);
+            let text_width = if let Some(line) = self.buffer.get_line(line_idx).as_deref() {
+                // 左侧有内容被滚出时首列显示 <，右侧还有内容时末列显示 >
+                let overflow_markers = self.config.overflow_markers && width > 1;
+                let overflow_left = overflow_markers && left > 0 && line.width(self.config.tab_width) > 0;
+                let overflow_right = overflow_markers && line.width(self.config.tab_width) > right;
+                let left = if overflow_left { left.saturating_add(1) } else { left };
+                let right = if overflow_right { right.saturating_sub(1) } else { right };
+                if overflow_left {
+                    text.push('<', Some(AnnotationType::OverflowMarker));
+                }
+                let limit = self.line_length_limit.filter(|&limit| line.width(self.config.tab_width) > limit);
+                let trailing_start = Some(line.trailing_whitespace_start()).filter(|&start| {
+                    self.highlight_trailing_whitespace && start < line.grapheme_count()
+                });
+                let selected_cols = block_selection
+                    .as_ref()
+                    .filter(|(lines, cols)| lines.contains(&line_idx) && !cols.is_empty())
+                    .map(|(_, cols)| cols.clone());
+                let selected_graphemes = selection
+                    .map(|selection| Self::selected_graphemes(selection, line_idx, line))
+                    .filter(|range| !range.is_empty());
+                let search_matches = self.search_highlight_ranges(line);
+                let syntax_spans = self.syntax_highlight_ranges(line);
+                if limit.is_some()
+                    || trailing_start.is_some()
+                    || selected_cols.is_some()
+                    || selected_graphemes.is_some()
+                    || !search_matches.is_empty()
+                    || !syntax_spans.is_empty()
+                {
+                    let line_text = line.get_annotated_visible_graphemes(
+                        left..right,
+                        self.show_whitespace,
+                        self.config.tab_width,
+                        |grapheme_idx, col| {
+                            if selected_cols.as_ref().is_some_and(|cols| cols.contains(&col))
+                                || selected_graphemes.as_ref().is_some_and(|range| range.contains(&grapheme_idx))
+                            {
+                                Some(AnnotationType::Selection)
+                            } else if search_matches.iter().any(|range| range.contains(&grapheme_idx)) {
+                                Some(AnnotationType::SearchMatch)
+                            } else if trailing_start.is_some_and(|start| grapheme_idx >= start) {
+                                Some(AnnotationType::TrailingWhitespace)
+                            } else if limit.is_some_and(|limit| col >= limit) {
+                                Some(AnnotationType::OverLimit)
+                            } else {
+                                syntax_spans
+                                    .iter()
+                                    .find(|(range, _)| range.contains(&grapheme_idx))
+                                    .map(|&(_, annotation_type)| annotation_type)
+                            }
+                        },
+                    );
+                    text.append(&line_text);
+                } else {
+                    text.push_str(
+                        &line.get_visible_graphemes(left..right, self.show_whitespace, self.config.tab_width),
+                        None,
+                    );
+                }
+                if overflow_right {
+                    text.push('>', Some(AnnotationType::OverflowMarker));
+                }
+                // 整行都滚出左侧时仍占用首列显示的 <
+                max(
+                    min(line.width(self.config.tab_width).saturating_sub(self.scroll_offset.col), width),
+                    usize::from(overflow_left),
+                )
             } else {
-                Self::render_line(current_row, "_")?;
-            }
+                let marker = self.end_marker(line_idx);
+                text.push_str(marker, None);
+                min(marker.width(), width)
+            };
+            self.append_ruler(&mut text, text_width);
+            Self::render_line(screen, current_row, &text)?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::terminal::RecordingScreen;
+    use std::time::Instant;
+
+    /// 创建 80×20 的视图，内容为给定的若干行
+    fn view(lines: &[&str]) -> View {
+        let lines: Vec<String> = lines.iter().map(|line| (*line).to_string()).collect();
+        let mut view = View::default();
+        view.resize(Size { height: 20, width: 80 });
+        view.load_lines(&lines);
+        view
+    }
+
+    /// 创建使用给定制表符宽度的视图
+    fn view_with_tab_width(lines: &[&str], tab_width: usize) -> View {
+        let mut view = view(lines);
+        view.set_config(&Config { tab_width, ..Config::default() });
+        view
+    }
+
+    #[test]
+    fn typing_into_long_line_is_faster_than_rebuilding() {
+        const EDITS: u32 = 200;
+        const REBUILDS: u32 = 5;
+        // 混合 ASCII、汉字、空格和组合字符的长行
+        let text: String = "ab字 e\u{301}".chars().cycle().take(100_000).collect();
+        let mut view = view(&[&text]);
+        let middle = view.buffer.grapheme_count(0) / 2;
+        view.text_location = Location { line_idx: 0, grapheme_idx: middle };
+        // 每次输入都经过撤销记录、滚动和状态统计，与实际输入时相同
+        let start = Instant::now();
+        for _ in 0..EDITS {
+            view.handle_edit_command(Edit::Insert('x'));
+            view.text_counts();
+        }
+        let incremental = start.elapsed() / EDITS;
+        let start = Instant::now();
+        for _ in 0..REBUILDS {
+            Line::from(&text);
+        }
+        let rebuild = start.elapsed() / REBUILDS;
+        assert_eq!(view.text_location.grapheme_idx, middle + EDITS as usize);
+        assert!(incremental * 10 < rebuild, "incremental {incremental:?}, rebuild {rebuild:?}");
+    }
+
+    #[test]
+    fn tab_moves_caret_to_next_tab_stop() {
+        let mut view = view_with_tab_width(&["ab"], 4);
+        view.handle_move_command(Move::EndOfLine);
+        view.handle_edit_command(Edit::Insert('\t'));
+        assert_eq!(view.lines(), ["ab\t"]);
+        assert_eq!(view.text_location_to_position(), Position { col: 4, row: 0 });
+    }
+
+    #[test]
+    fn tab_width_comes_from_the_config() {
+        let mut view = view_with_tab_width(&["ab"], 8);
+        view.handle_move_command(Move::EndOfLine);
+        view.handle_edit_command(Edit::Insert('\t'));
+        assert_eq!(view.text_location_to_position(), Position { col: 8, row: 0 });
+    }
+
+    fn at(line_idx: LineIdx, grapheme_idx: GraphemeIdx) -> Location {
+        Location { line_idx, grapheme_idx }
+    }
+
+    #[test]
+    fn newline_copies_indentation() {
+        let mut view = view_with_tab_width(&["    foobar"], 4);
+        view.restore_text_location(at(0, 7));
+        view.handle_edit_command(Edit::InsertNewline);
+        assert_eq!(view.lines(), ["    foo", "    bar"]);
+        assert_eq!(view.text_location(), at(1, 4));
+    }
+
+    #[test]
+    fn newline_copies_whitespace_only_line() {
+        let mut view = view_with_tab_width(&["\t  "], 4);
+        view.handle_move_command(Move::EndOfLine);
+        view.handle_edit_command(Edit::InsertNewline);
+        assert_eq!(view.lines(), ["\t  ", "\t  "]);
+        assert_eq!(view.text_location(), at(1, 3));
+        assert_eq!(view.text_location_to_position(), Position { col: 6, row: 1 });
+    }
+
+    #[test]
+    fn newline_without_auto_indent_starts_at_column_zero() {
+        let mut view = view_with_tab_width(&["    foobar"], 4);
+        assert!(!view.toggle_auto_indent());
+        view.restore_text_location(at(0, 7));
+        view.handle_edit_command(Edit::InsertNewline);
+        assert_eq!(view.lines(), ["    foo", "bar"]);
+        assert_eq!(view.text_location(), at(1, 0));
+    }
+
+    #[test]
+    fn move_line_without_selection_moves_caret_line() {
+        let mut view = view(&["a", "b", "c"]);
+        view.handle_move_command(Move::Down);
+        view.handle_edit_command(Edit::MoveLineUp);
+        assert_eq!(view.lines(), ["b", "a", "c"]);
+        assert_eq!(view.text_location(), at(0, 0));
+        view.handle_edit_command(Edit::MoveLineUp);
+        assert_eq!(view.lines(), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn move_line_moves_whole_selected_block() {
+        let mut view = view(&["a", "b", "c", "d", "e"]);
+        view.handle_move_command(Move::Down);
+        view.handle_select_command(Move::Down);
+        view.handle_select_command(Move::Right);
+        view.handle_edit_command(Edit::MoveLineDown);
+        assert_eq!(view.lines(), ["a", "d", "b", "c", "e"]);
+        assert_eq!(view.selection(), Some((at(2, 0), at(3, 1))));
+        view.handle_edit_command(Edit::MoveLineUp);
+        view.handle_edit_command(Edit::MoveLineUp);
+        assert_eq!(view.lines(), ["b", "c", "a", "d", "e"]);
+        assert_eq!(view.selection(), Some((at(0, 0), at(1, 1))));
+        view.handle_edit_command(Edit::MoveLineUp);
+        assert_eq!(view.lines(), ["b", "c", "a", "d", "e"]);
+    }
+
+    #[test]
+    fn selection_ending_at_line_start_excludes_that_line() {
+        let mut view = view(&["a", "b", "c", "d"]);
+        view.handle_select_command(Move::Down);
+        view.handle_select_command(Move::Down);
+        view.handle_edit_command(Edit::MoveLineDown);
+        assert_eq!(view.lines(), ["c", "a", "b", "d"]);
+        assert_eq!(view.selection(), Some((at(1, 0), at(3, 0))));
+    }
+
+    #[test]
+    fn move_line_block_undoes_in_one_step() {
+        let mut view = view(&["a", "b", "c"]);
+        view.handle_select_command(Move::Down);
+        view.handle_select_command(Move::Right);
+        view.handle_edit_command(Edit::MoveLineDown);
+        view.finish_undo_step(false);
+        assert_eq!(view.lines(), ["c", "a", "b"]);
+        assert!(view.undo());
+        assert_eq!(view.lines(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn caret_stops_at_end_of_last_line() {
+        let mut view = view(&["ab", "cd"]);
+        for command in [Move::Down, Move::Down, Move::EndOfLine, Move::Right, Move::Down] {
+            view.handle_move_command(command);
+        }
+        assert_eq!(view.text_location(), at(1, 2));
+        let position = view.caret_position();
+        assert_eq!(position.row, 1);
+        assert_eq!(position.col, view.gutter_width() + 2);
+    }
+
+    #[test]
+    fn caret_at_end_of_scrolled_buffer_stays_inside_view() {
+        let lines: Vec<String> = (0..50).map(|idx| idx.to_string()).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut view = view(&lines);
+        for _ in 0..5 {
+            view.handle_move_command(Move::PageDown);
+        }
+        view.handle_move_command(Move::EndOfLine);
+        assert_eq!(view.text_location(), at(49, 2));
+        assert!(view.is_caret_visible());
+        assert!(view.caret_position().row < 20);
+        view.handle_edit_command(Edit::Insert('!'));
+        view.handle_edit_command(Edit::InsertNewline);
+        assert_eq!(view.text_location(), at(50, 0));
+        assert!(view.caret_position().row < 20);
+    }
+
+    #[test]
+    fn caret_in_empty_buffer_is_on_line_zero() {
+        let mut view = view(&[]);
+        view.handle_move_command(Move::Down);
+        view.handle_move_command(Move::Right);
+        assert_eq!(view.text_location(), at(0, 0));
+        assert_eq!(view.caret_position().row, 0);
+    }
+
+    /// 绘制视图，返回各行输出的文本
+    fn rendered_rows(view: &mut View) -> Vec<String> {
+        let mut screen = RecordingScreen::default();
+        view.draw(&mut screen, 0).unwrap();
+        let rows = screen.rows.borrow().clone();
+        rows
+    }
+
+    #[test]
+    fn horizontal_scroll_follows_rendered_width() {
+        let combined = format!("{}xyz", "e\u{301}".repeat(8));
+        let mut view = view(&["abcdefghijklmnop", "字字字字字字字字", &combined]);
+        view.set_config(&Config { overflow_markers: false, line_numbers: LineNumbers::Off, ..Config::default() });
+        view.resize(Size { height: 6, width: 5 });
+        view.handle_move_command(Move::Down);
+        view.handle_move_command(Move::EndOfLine);
+        // 8 个汉字占 16 列，光标在第 16 列，视口右移到第 12 列开始
+        assert_eq!(view.text_location(), at(1, 8));
+        assert_eq!(view.caret_position().col, 4);
+        let rows = rendered_rows(&mut view);
+        assert_eq!(rows[0], "mnop");
+        assert_eq!(rows[1], "字字");
+        assert_eq!(rows[2], "");
+        // 组合字符不占列：第 2 行共 11 列，行尾在第 11 列，视口左移到该列
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.text_location(), at(2, 11));
+        assert_eq!(view.caret_position().col, 0);
+        let rows = rendered_rows(&mut view);
+        assert_eq!(rows[0], "lmnop");
+        assert_eq!(rows[1], " 字字");
+        assert_eq!(rows[2], "");
+        for _ in 0..4 {
+            view.handle_move_command(Move::Left);
+        }
+        assert_eq!(view.caret_position().col, 0);
+        let rows = rendered_rows(&mut view);
+        assert_eq!(rows[1], " 字字");
+        assert_eq!(rows[2], "e\u{301}xyz");
+    }
+
+    #[test]
+    fn typing_in_empty_buffer_then_down_and_up_returns_to_the_character() {
+        let mut view = view(&[]);
+        view.set_config(&Config { line_numbers: LineNumbers::Off, ..Config::default() });
+        view.handle_edit_command(Edit::Insert('x'));
+        assert_eq!(view.text_location(), at(0, 1));
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.text_location(), at(0, 1));
+        view.handle_move_command(Move::Up);
+        assert_eq!(view.text_location(), at(0, 1));
+        assert_eq!(view.lines(), ["x"]);
+        let rows = rendered_rows(&mut view);
+        assert_eq!(rows[0], "x");
+        assert_eq!(rows[1], "_");
+    }
+
+    #[test]
+    fn caret_at_true_end_of_file_is_never_on_an_end_marker_row() {
+        let mut view = view(&["ab", "cd"]);
+        view.set_config(&Config { line_numbers: LineNumbers::Off, ..Config::default() });
+        for command in [Move::Down, Move::Down, Move::Down, Move::EndOfLine, Move::Right] {
+            view.handle_move_command(command);
+        }
+        assert_eq!(view.text_location(), at(1, 2));
+        view.handle_edit_command(Edit::InsertNewline);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.text_location(), at(2, 0));
+        view.handle_edit_command(Edit::Insert('z'));
+        view.handle_move_command(Move::Up);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.text_location(), at(2, 1));
+        assert_eq!(view.lines(), ["ab", "cd", "z"]);
+        let rows = rendered_rows(&mut view);
+        let caret_row = view.caret_position().row;
+        assert_eq!(rows[caret_row], "z");
+        assert_eq!(rows[caret_row + 1], "_");
+    }
+
+    /// 创建 5 列宽、不显示行号的视图
+    fn narrow_view(lines: &[&str], overflow_markers: bool) -> View {
+        let mut view = view(lines);
+        view.set_config(&Config { overflow_markers, line_numbers: LineNumbers::Off, ..Config::default() });
+        view.resize(Size { height: 4, width: 5 });
+        view
+    }
+
+    #[test]
+    fn wide_graphemes_cut_at_viewport_edges_are_padded() {
+        let mut view = narrow_view(&["字字字字字字字字"], false);
+        let expected = ["字字 ", " 字字", "字字 ", " 字字"];
+        for (offset, expected) in expected.iter().enumerate() {
+            view.scroll_offset.col = offset;
+            assert_eq!(rendered_rows(&mut view)[0], *expected, "offset {offset}");
+        }
+        // 行尾只剩半个字
+        view.scroll_offset.col = 15;
+        assert_eq!(rendered_rows(&mut view)[0], " ");
+    }
+
+    #[test]
+    fn scrolled_cjk_rows_keep_their_width() {
+        let mut view = narrow_view(&["a字b字字c", "字字字字字字字字", "ab字"], false);
+        for offset in 0..8 {
+            view.scroll_offset.col = offset;
+            let rows = rendered_rows(&mut view);
+            assert_eq!(rows[0].width(), min(5, 9_usize.saturating_sub(offset)), "offset {offset}: {:?}", rows[0]);
+            assert_eq!(rows[1].width(), 5, "offset {offset}: {:?}", rows[1]);
+            assert_eq!(rows[2].width(), 4_usize.saturating_sub(offset), "offset {offset}: {:?}", rows[2]);
+        }
+    }
+
+    #[test]
+    fn overflow_markers_replace_cut_wide_graphemes() {
+        let mut view = narrow_view(&["字字字字字字字字"], true);
+        view.scroll_offset.col = 2;
+        // 左侧标记占第 2 列，第 3 列是半个字；右侧标记占第 6 列，也截断了一个字
+        assert_eq!(rendered_rows(&mut view)[0], "< 字>");
+    }
+
+    /// 100 行的视图：每 10 行中有一行很短，其余行为 20 个字符
+    fn paging_view() -> View {
+        let lines: Vec<String> = (0..100)
+            .map(|idx| if idx % 10 == 4 { "ab".to_string() } else { "x".repeat(20) })
+            .collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        view(&lines)
+    }
+
+    #[test]
+    fn page_down_keeps_screen_row_and_desired_column() {
+        let mut view = paging_view();
+        view.restore_text_location(at(5, 10));
+        view.handle_move_command(Move::PageDown);
+        // 每页 19 行：第 24 行很短，光标停在行尾，但记住第 10 列
+        assert_eq!(view.text_location(), at(24, 2));
+        assert_eq!(view.scroll_offset().row, 19);
+        assert_eq!(view.caret_position().row, 5);
+        view.handle_move_command(Move::PageDown);
+        assert_eq!(view.text_location(), at(43, 10));
+        assert_eq!(view.scroll_offset().row, 38);
+        view.handle_move_command(Move::PageUp);
+        assert_eq!(view.text_location(), at(24, 2));
+        assert_eq!(view.scroll_offset().row, 19);
+        view.handle_move_command(Move::PageUp);
+        assert_eq!(view.text_location(), at(5, 10));
+        assert_eq!(view.scroll_offset().row, 0);
+    }
+
+    #[test]
+    fn paging_stops_at_the_ends_of_the_file() {
+        let mut view = paging_view();
+        view.restore_text_location(at(3, 7));
+        view.handle_move_command(Move::PageUp);
+        assert_eq!(view.text_location(), at(0, 7));
+        assert_eq!(view.scroll_offset().row, 0);
+        for _ in 0..10 {
+            view.handle_move_command(Move::PageDown);
+        }
+        // 视口最多滚动到文件末尾的标记出现在底部
+        assert_eq!(view.text_location(), at(99, 7));
+        assert_eq!(view.scroll_offset().row, 81);
+        assert_eq!(view.caret_position().row, 18);
+    }
+
+    #[test]
+    fn half_page_moves_half_the_view_height() {
+        let mut view = paging_view();
+        view.restore_text_location(at(5, 0));
+        view.handle_move_command(Move::HalfPageDown);
+        assert_eq!(view.text_location(), at(15, 0));
+        assert_eq!(view.scroll_offset().row, 10);
+        assert_eq!(view.caret_position().row, 5);
+        view.handle_move_command(Move::HalfPageUp);
+        assert_eq!(view.text_location(), at(5, 0));
+        assert_eq!(view.scroll_offset().row, 0);
+    }
+
+    #[test]
+    fn delete_removes_base_and_combining_mark_together() {
+        let mut view = view(&["ae\u{301}b"]);
+        assert_eq!(view.buffer.grapheme_count(0), 3);
+        view.restore_text_location(at(0, 2));
+        view.handle_edit_command(Edit::DeleteBackward);
+        assert_eq!(view.lines(), ["ab"]);
+        assert_eq!(view.text_location(), at(0, 1));
+        let mut view = self::view(&["ae\u{301}b"]);
+        view.restore_text_location(at(0, 1));
+        view.handle_edit_command(Edit::Delete);
+        assert_eq!(view.lines(), ["ab"]);
+        assert_eq!(view.text_location(), at(0, 1));
+    }
+
+    #[test]
+    fn delete_backward_codepoint_peels_off_the_combining_mark() {
+        let mut view = view(&["ae\u{301}b"]);
+        view.restore_text_location(at(0, 2));
+        view.handle_edit_command(Edit::DeleteBackwardCodepoint);
+        assert_eq!(view.lines(), ["aeb"]);
+        assert_eq!(view.text_location(), at(0, 2));
+        view.handle_edit_command(Edit::DeleteBackwardCodepoint);
+        assert_eq!(view.lines(), ["ab"]);
+        assert_eq!(view.text_location(), at(0, 1));
+    }
+
+    #[test]
+    fn precomposed_e_acute_is_a_single_grapheme() {
+        let mut view = view(&["a\u{e9}b"]);
+        view.restore_text_location(at(0, 2));
+        view.handle_edit_command(Edit::DeleteBackwardCodepoint);
+        assert_eq!(view.lines(), ["ab"]);
+        assert_eq!(view.text_location(), at(0, 1));
+    }
+
+    #[test]
+    fn edits_in_read_only_view_leave_buffer_clean() {
+        let mut view = view(&["abc", "def"]);
+        view.set_read_only(true);
+        for command in [Edit::Insert('x'), Edit::InsertNewline, Edit::Delete, Edit::DeleteBackward, Edit::DeleteLine] {
+            view.handle_edit_command(command);
+        }
+        view.finish_undo_step(false);
+        assert_eq!(view.lines(), ["abc", "def"]);
+        assert!(!view.buffer.is_dirty());
+        assert!(!view.undo());
+    }
+
+    #[test]
+    fn delete_line_moves_caret_to_the_line_taking_its_place() {
+        let mut view = view(&["a", "bc", "d"]);
+        view.restore_text_location(at(1, 2));
+        view.handle_edit_command(Edit::DeleteLine);
+        assert_eq!(view.lines(), ["a", "d"]);
+        assert_eq!(view.text_location(), at(1, 0));
+        // 删除最后一行时光标移到新的最后一行
+        view.handle_edit_command(Edit::DeleteLine);
+        assert_eq!(view.lines(), ["a"]);
+        assert_eq!(view.text_location(), at(0, 0));
+    }
+
+    #[test]
+    fn delete_line_clears_the_only_line() {
+        let mut view = view(&["only"]);
+        view.restore_text_location(at(0, 3));
+        view.handle_edit_command(Edit::DeleteLine);
+        assert_eq!(view.lines(), [""]);
+        assert_eq!(view.text_location(), at(0, 0));
+        view.handle_edit_command(Edit::DeleteLine);
+        assert_eq!(view.lines(), [""]);
+        let mut view = self::view(&[]);
+        view.handle_edit_command(Edit::DeleteLine);
+        assert!(view.lines().is_empty());
+    }
+
+    #[test]
+    fn duplicate_line_copies_below_and_keeps_the_column() {
+        let mut view = view(&["ab", "字c"]);
+        view.restore_text_location(at(1, 1));
+        view.handle_edit_command(Edit::DuplicateLine);
+        assert_eq!(view.lines(), ["ab", "字c", "字c"]);
+        assert_eq!(view.text_location(), at(2, 1));
+        let mut view = self::view(&["one"]);
+        view.restore_text_location(at(0, 3));
+        view.finish_undo_step(false);
+        view.handle_edit_command(Edit::DuplicateLine);
+        assert_eq!(view.lines(), ["one", "one"]);
+        assert_eq!(view.text_location(), at(1, 3));
+        view.finish_undo_step(false);
+        assert!(view.undo());
+        assert_eq!(view.lines(), ["one"]);
+        assert_eq!(view.text_location(), at(0, 3));
+    }
+
+    #[test]
+    fn smart_home_toggles_between_first_non_blank_and_line_start() {
+        let mut view = view(&["   foo"]);
+        view.restore_text_location(at(0, 5));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(0, 3));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(0, 0));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(0, 3));
+        // 从缩进中间出发先到第一个非空白字符
+        view.restore_text_location(at(0, 1));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(0, 3));
+    }
+
+    #[test]
+    fn smart_home_on_tab_indent_and_blank_lines() {
+        let mut view = view(&["\t\tx", "    ", ""]);
+        view.restore_text_location(at(0, 3));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(0, 2));
+        // 只有空白的行直接到行首
+        view.restore_text_location(at(1, 4));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(1, 0));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(1, 0));
+        view.restore_text_location(at(2, 0));
+        view.handle_move_command(Move::StartOfLine);
+        assert_eq!(view.text_location(), at(2, 0));
+    }
+
+    /// 100 行、scroll-off 为 3 的视图，高度为 height
+    fn scroll_off_view(height: usize) -> View {
+        let lines: Vec<String> = (0..100).map(|idx| idx.to_string()).collect();
+        let lines: Vec<&str> = lines.iter().map(String::as_str).collect();
+        let mut view = view(&lines);
+        view.set_config(&Config { scroll_off: 3, ..Config::default() });
+        view.resize(Size { height, width: 80 });
+        view
+    }
+
+    #[test]
+    fn scroll_off_keeps_margin_but_not_past_the_first_line() {
+        let mut view = scroll_off_view(20);
+        for _ in 0..16 {
+            view.handle_move_command(Move::Down);
+        }
+        assert_eq!(view.scroll_offset().row, 0);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.text_location().line_idx, 17);
+        assert_eq!(view.scroll_offset().row, 1);
+        for _ in 0..15 {
+            view.handle_move_command(Move::Up);
+        }
+        assert_eq!(view.scroll_offset().row, 0);
+        view.handle_move_command(Move::Up);
+        view.handle_move_command(Move::Up);
+        assert_eq!(view.text_location().line_idx, 0);
+        assert_eq!(view.scroll_offset().row, 0);
+        assert_eq!(view.caret_position().row, 0);
+    }
+
+    #[test]
+    fn scroll_off_stops_at_the_last_line() {
+        let mut view = scroll_off_view(20);
+        for _ in 0..120 {
+            view.handle_move_command(Move::Down);
+        }
+        assert_eq!(view.text_location().line_idx, 99);
+        // 最后一行之后只剩文件末尾的标记，不再为 scroll-off 继续滚动
+        assert_eq!(view.scroll_offset().row, 81);
+        assert_eq!(view.caret_position().row, 18);
+        for _ in 0..14 {
+            view.handle_move_command(Move::Up);
+        }
+        assert_eq!(view.scroll_offset().row, 81);
+        view.handle_move_command(Move::Up);
+        assert_eq!(view.text_location().line_idx, 84);
+        assert_eq!(view.scroll_offset().row, 81);
+        view.handle_move_command(Move::Up);
+        assert_eq!(view.scroll_offset().row, 80);
+    }
+
+    #[test]
+    fn scroll_off_shrinks_on_short_views() {
+        // 高度为 4 时上下各只保留 1 行
+        let mut view = scroll_off_view(4);
+        view.handle_move_command(Move::Down);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.scroll_offset().row, 0);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.scroll_offset().row, 1);
+        assert_eq!(view.caret_position().row, 2);
+        let mut view = scroll_off_view(1);
+        view.handle_move_command(Move::Down);
+        assert_eq!(view.scroll_offset().row, 1);
+        assert_eq!(view.caret_position().row, 0);
+    }
+
+    /// 创建使用给定缩进方式的视图
+    fn indent_view(lines: &[&str], expand_tabs: bool) -> View {
+        let mut view = view(lines);
+        view.set_config(&Config { tab_width: 4, expand_tabs, ..Config::default() });
+        view
+    }
+
+    #[test]
+    fn indent_and_dedent_single_line_move_the_caret_with_the_text() {
+        let mut view = indent_view(&["foo"], false);
+        view.restore_text_location(at(0, 1));
+        view.handle_edit_command(Edit::Indent);
+        assert_eq!(view.lines(), ["\tfoo"]);
+        assert_eq!(view.text_location(), at(0, 2));
+        view.handle_edit_command(Edit::Dedent);
+        assert_eq!(view.lines(), ["foo"]);
+        assert_eq!(view.text_location(), at(0, 1));
+        // 没有缩进时取消缩进不做修改
+        view.handle_edit_command(Edit::Dedent);
+        assert_eq!(view.lines(), ["foo"]);
+        assert_eq!(view.text_location(), at(0, 1));
+    }
+
+    #[test]
+    fn indent_with_spaces_and_partial_dedent() {
+        let mut view = indent_view(&["  foo"], true);
+        view.restore_text_location(at(0, 3));
+        view.handle_edit_command(Edit::Indent);
+        assert_eq!(view.lines(), ["      foo"]);
+        assert_eq!(view.text_location(), at(0, 7));
+        view.handle_edit_command(Edit::Dedent);
+        view.handle_edit_command(Edit::Dedent);
+        assert_eq!(view.lines(), ["foo"]);
+        assert_eq!(view.text_location(), at(0, 1));
+    }
+
+    #[test]
+    fn indent_and_dedent_selected_lines_skip_blank_lines_and_keep_selection() {
+        let mut view = indent_view(&["a", "", "b", "c"], false);
+        view.handle_select_command(Move::Down);
+        view.handle_select_command(Move::Down);
+        view.handle_select_command(Move::Right);
+        view.handle_edit_command(Edit::Indent);
+        assert_eq!(view.lines(), ["\ta", "", "\tb", "c"]);
+        assert_eq!(view.selection(), Some((at(0, 1), at(2, 2))));
+        view.handle_edit_command(Edit::Dedent);
+        assert_eq!(view.lines(), ["a", "", "b", "c"]);
+        assert_eq!(view.selection(), Some((at(0, 0), at(2, 1))));
+    }
+
+    #[test]
+    fn tab_indents_at_line_start_and_inserts_inside_text() {
+        let mut view = indent_view(&["  foo"], true);
+        view.restore_text_location(at(0, 2));
+        view.handle_edit_command(Edit::Insert('\t'));
+        assert_eq!(view.lines(), ["      foo"]);
+        view.restore_text_location(at(0, 8));
+        view.handle_edit_command(Edit::Insert('\t'));
+        assert_eq!(view.lines(), ["      fo    o"]);
+    }
+}
@@ -0,0 +1,105 @@
+// 安全地保存文件：先把内容写入同一目录下的临时文件，写完后再改名替换原文件，
+// 写入中途出错或程序崩溃都不会留下只写了一半的文件。改名失败（如跨设备）时退回直接覆盖原文件。
+
+use std::{
+    ffi::OsString,
+    fs::{self, File, OpenOptions},
+    io::{Error, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// 将 contents 写入 path。backup 为真且原文件存在时，先把原内容复制到 path.bak；
+/// 替换后的文件沿用原文件的权限
+pub fn write_file(path: &Path, contents: &[u8], backup: bool) -> Result<(), Error> {
+    // path 是符号链接时替换它指向的文件，而不是把链接本身换成普通文件
+    let target = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let permissions = fs::metadata(&target).ok().map(|metadata| metadata.permissions());
+    if backup && permissions.is_some() {
+        fs::copy(&target, with_suffix(&target, ".bak"))?;
+    }
+    let temp_path = temp_path_for(&target);
+    write_new_file(&temp_path, contents)?;
+    if let Some(permissions) = permissions {
+        if let Err(err) = fs::set_permissions(&temp_path, permissions) {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+    }
+    if fs::rename(&temp_path, &target).is_ok() {
+        return Ok(());
+    }
+    let _ = fs::remove_file(&temp_path);
+    let mut file = File::create(&target)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+/// 创建新文件并写入全部内容，写完后确保落盘；写入失败时删除该文件
+fn write_new_file(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    let written = file.write_all(contents).and_then(|()| file.sync_all());
+    if written.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    written
+}
+
+/// 与目标文件同目录的临时文件名（.文件名.进程号.tmp），保证改名时不跨设备
+fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", process::id()));
+    path.with_file_name(name)
+}
+
+/// 在文件名末尾加上后缀
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::tempfile::TempFile;
+
+    #[test]
+    fn failed_temp_write_leaves_original_untouched() {
+        let file = TempFile::with_contents("file.txt", "original");
+        let path = file.path();
+        // 临时文件名已被一个目录占用，创建临时文件失败
+        let temp_path = temp_path_for(path);
+        fs::create_dir(&temp_path).unwrap();
+        assert!(write_file(path, b"new contents", false).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "original");
+        assert!(temp_path.is_dir());
+    }
+
+    #[test]
+    fn failed_backup_leaves_original_untouched() {
+        let file = TempFile::with_contents("file.txt", "original");
+        let path = file.path();
+        // .bak 是目录，复制备份失败，之后的步骤都不执行
+        fs::create_dir(with_suffix(path, ".bak")).unwrap();
+        assert!(write_file(path, b"new contents", true).is_err());
+        assert_eq!(fs::read_to_string(path).unwrap(), "original");
+        assert!(!temp_path_for(path).exists());
+    }
+
+    #[test]
+    fn successful_write_replaces_contents_and_leaves_no_temp_file() {
+        let file = TempFile::with_contents("file.txt", "original");
+        let path = file.path();
+        write_file(path, b"new contents", true).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "new contents");
+        assert_eq!(fs::read_to_string(with_suffix(path, ".bak")).unwrap(), "original");
+        assert!(!temp_path_for(path).exists());
+        let names: Vec<_> = fs::read_dir(path.parent().unwrap()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names.len(), 2);
+    }
+}
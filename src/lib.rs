@@ -0,0 +1,11 @@
+// 以库的形式提供编辑器：可执行文件通过 Editor::new 在终端中运行，
+// 脚本和测试可以用 Document 在不连接终端的情况下载入文本、执行编辑并读回结果。
+
+mod editor;
+mod prelude;
+
+pub use editor::{
+    command::{Command, Edit, Move, System},
+    Buffer, Document, Editor, Line, LineRef,
+};
+pub use prelude::{Location, Size};
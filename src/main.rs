@@ -1,6 +1,4 @@
-mod editor;
-use editor::Editor;
-mod prelude;
+use text_editor::Editor;
 
 fn main() {
     Editor::new().unwrap().run();
@@ -1,9 +1,36 @@
 // Location 用于表示文本中的行和字素位置
 
+use std::cmp::Ordering;
+
 use super::{GraphemeIdx, LineIdx};
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "session", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
     pub grapheme_idx: GraphemeIdx,
     pub line_idx: LineIdx,
 }
+
+impl Location {
+    /// 按文本中的先后顺序返回两个位置（靠前的在前），用于选择范围等
+    pub fn ordered(self, other: Self) -> (Self, Self) {
+        if self <= other {
+            (self, other)
+        } else {
+            (other, self)
+        }
+    }
+}
+
+/// 先比较行号，行号相同时再比较字素索引，即文本中的先后顺序
+impl Ord for Location {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.line_idx, self.grapheme_idx).cmp(&(other.line_idx, other.grapheme_idx))
+    }
+}
+
+impl PartialOrd for Location {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
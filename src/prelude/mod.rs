@@ -16,4 +16,3 @@ mod size;
 pub use size::Size;
 
 pub const NAME: &str = env!("CARGO_PKG_NAME");
-pub const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -1,15 +1,26 @@
 // Position 用于表示光标或元素的行列坐标。
 
+use std::cmp::Ordering;
 
 use super::{ColIdx,RowIdx};
 
-#[derive(Copy, Clone, Default)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Debug)]
+#[cfg_attr(feature = "session", derive(serde::Serialize, serde::Deserialize))]
 pub struct Position {
     pub col: ColIdx,
     pub row: RowIdx,
 }
 
 impl Position {
+    /// 逐分量相加，溢出时取 usize 的最大值
+    pub const fn saturating_add(self, other: Self) -> Self {
+        Self {
+            row: self.row.saturating_add(other.row),
+            col: self.col.saturating_add(other.col),
+        }
+    }
+
+    /// 逐分量相减，结果不小于 0（如光标坐标减去滚动偏移）
     pub const fn saturating_sub(self, other: Self) -> Self {
         Self {
             row: self.row.saturating_sub(other.row),
@@ -17,3 +28,54 @@ impl Position {
         }
     }
 }
+
+/// 先比较行，行相同时再比较列，即屏幕上从左到右、从上到下的顺序
+impl Ord for Position {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.row, self.col).cmp(&(other.row, other.col))
+    }
+}
+
+impl PartialOrd for Position {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn pos(row: RowIdx, col: ColIdx) -> Position {
+        Position { col, row }
+    }
+
+    #[test]
+    fn saturating_add_is_component_wise() {
+        assert_eq!(pos(1, 2).saturating_add(pos(3, 4)), pos(4, 6));
+        assert_eq!(pos(0, 0).saturating_add(pos(0, 0)), pos(0, 0));
+        assert_eq!(pos(usize::MAX, 1).saturating_add(pos(1, 1)), pos(usize::MAX, 2));
+        assert_eq!(pos(1, usize::MAX - 1).saturating_add(pos(0, 5)), pos(1, usize::MAX));
+    }
+
+    #[test]
+    fn saturating_sub_never_goes_below_zero() {
+        assert_eq!(pos(5, 7).saturating_sub(pos(2, 3)), pos(3, 4));
+        assert_eq!(pos(2, 3).saturating_sub(pos(5, 1)), pos(0, 2));
+        assert_eq!(pos(2, 3).saturating_sub(pos(1, 9)), pos(1, 0));
+        assert_eq!(pos(0, 0).saturating_sub(pos(usize::MAX, usize::MAX)), pos(0, 0));
+    }
+
+    #[test]
+    fn sub_undoes_add_without_overflow() {
+        let caret = pos(30, 12);
+        let offset = pos(10, 4);
+        assert_eq!(caret.saturating_sub(offset).saturating_add(offset), caret);
+    }
+
+    #[test]
+    fn ordering_compares_rows_first() {
+        assert!(pos(1, 0) > pos(0, 99));
+        assert!(pos(1, 2) < pos(1, 3));
+    }
+}
@@ -1,6 +1,6 @@
 // Size 用于表示终端或区域的宽高。
 
-#[derive(Default, Copy, Clone, Eq, PartialEq)]
+#[derive(Default, Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Size {
     pub height: usize,
     pub width: usize,
@@ -0,0 +1,52 @@
+// 用 Document 在不连接终端的情况下编辑样例文件，检查编辑后的全文。
+
+use text_editor::{Command, Document, Edit, Location, Move};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sample.rs");
+
+fn at(line_idx: usize, grapheme_idx: usize) -> Location {
+    Location { line_idx, grapheme_idx }
+}
+
+#[test]
+fn fixture_is_loaded_line_by_line() {
+    let document = Document::open(FIXTURE).unwrap();
+    assert_eq!(document.lines(), ["fn main() {", "    println!(\"hi\");", "}"]);
+    assert_eq!(document.text_location(), at(0, 0));
+}
+
+#[test]
+fn inserts_deletes_and_newlines_produce_expected_text() {
+    let mut document = Document::open(FIXTURE).unwrap();
+    // 把 "hi" 改成 "hello"
+    document.delete_range(at(1, 14), at(1, 16));
+    document.insert_str("hello");
+    assert_eq!(document.text_location(), at(1, 19));
+    // 行尾换行后自动缩进，再输入下一条语句
+    document.apply(Command::Move(Move::EndOfLine));
+    document.apply(Command::Edit(Edit::InsertNewline));
+    assert_eq!(document.text_location(), at(2, 4));
+    for character in "let x = 1;".chars() {
+        document.apply(Command::Edit(Edit::Insert(character)));
+    }
+    // 删除第一行的 "main" 中的 "ai"
+    document.delete_range(at(0, 4), at(0, 6));
+    document.apply(Command::Edit(Edit::DeleteBackward));
+    assert_eq!(
+        document.to_string(),
+        "fn n() {\n    println!(\"hello\");\n    let x = 1;\n}"
+    );
+}
+
+#[test]
+fn undo_restores_the_fixture() {
+    let mut document = Document::open(FIXTURE).unwrap();
+    let original = document.to_string();
+    document.set_text_location(at(2, 1));
+    document.apply(Command::Edit(Edit::InsertNewline));
+    document.insert_str("// end");
+    assert_eq!(document.lines().last().unwrap(), "// end");
+    document.apply(Command::Edit(Edit::Undo));
+    document.apply(Command::Edit(Edit::Undo));
+    assert_eq!(document.to_string(), original);
+}
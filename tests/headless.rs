@@ -0,0 +1,77 @@
+// 用 Editor::headless 在不连接终端的情况下执行命令，检查各行内容和光标位置。
+
+use text_editor::{
+    Command::{self, Edit, Move, Select, System},
+    Edit::{Delete, DeleteBackward, Insert, InsertNewline, Undo},
+    Editor, Location,
+    Move::{Down, EndOfLine, Right},
+    Size,
+    System::Quit,
+};
+
+fn editor(lines: &[&str]) -> Editor {
+    let lines: Vec<String> = lines.iter().map(|line| (*line).to_string()).collect();
+    Editor::headless(&lines, Size { height: 24, width: 80 })
+}
+
+fn typed(text: &str) -> Vec<Command> {
+    text.chars().map(|character| Edit(Insert(character))).collect()
+}
+
+fn at(line_idx: usize, grapheme_idx: usize) -> Location {
+    Location { line_idx, grapheme_idx }
+}
+
+#[test]
+fn starts_at_the_top_left() {
+    let editor = editor(&["one", "two"]);
+    assert_eq!(editor.lines(), ["one", "two"]);
+    assert_eq!(editor.text_location(), at(0, 0));
+    assert!(!editor.should_quit());
+}
+
+#[test]
+fn typing_and_moving_update_lines_and_caret() {
+    let mut editor = editor(&["one", "two"]);
+    editor.apply([Move(Down), Move(EndOfLine)]);
+    assert_eq!(editor.text_location(), at(1, 3));
+    editor.apply(typed("字s"));
+    assert_eq!(editor.lines(), ["one", "two字s"]);
+    assert_eq!(editor.text_location(), at(1, 5));
+    editor.apply([Edit(DeleteBackward), Edit(InsertNewline)]);
+    editor.apply(typed("three"));
+    assert_eq!(editor.lines(), ["one", "two字", "three"]);
+    assert_eq!(editor.text_location(), at(2, 5));
+}
+
+#[test]
+fn deleting_a_selection_then_typing_can_be_undone() {
+    let mut editor = editor(&["hello world"]);
+    editor.apply([Select(Right), Select(Right), Select(Right), Select(Right), Select(Right)]);
+    assert_eq!(editor.text_location(), at(0, 5));
+    editor.apply([Edit(Delete)]);
+    assert_eq!(editor.text_location(), at(0, 0));
+    editor.apply(typed("bye"));
+    assert_eq!(editor.lines(), ["bye world"]);
+    assert_eq!(editor.text_location(), at(0, 3));
+    editor.apply([Edit(Undo), Edit(Undo)]);
+    assert_eq!(editor.lines(), ["hello world"]);
+}
+
+#[test]
+fn delete_at_line_end_joins_lines() {
+    let mut editor = editor(&["ab", "cd"]);
+    editor.apply([Move(EndOfLine), Edit(Delete)]);
+    assert_eq!(editor.lines(), ["abcd"]);
+    assert_eq!(editor.text_location(), at(0, 2));
+    editor.apply([Move(Down)]);
+    assert_eq!(editor.text_location(), at(0, 2));
+}
+
+#[test]
+fn commands_after_quit_are_ignored() {
+    let mut editor = editor(&["a"]);
+    editor.apply([System(Quit), Edit(Insert('x'))]);
+    assert!(editor.should_quit());
+    assert_eq!(editor.lines(), ["a"]);
+}